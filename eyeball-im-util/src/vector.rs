@@ -1,27 +1,43 @@
 //! Utilities around [`ObservableVector`][eyeball_im::ObservableVector].
 
+mod aggregate;
+mod concat;
+mod dedup;
 mod filter;
+mod flatten;
+mod group_by;
 mod head;
+mod map;
 mod ops;
+mod reverse;
 mod skip;
 mod sort;
 mod tail;
 mod traits;
+mod window;
 
 use eyeball_im::VectorDiff;
 use futures_core::Stream;
 
 use self::ops::{VectorDiffContainerFamilyMember, VectorDiffContainerOps};
 pub use self::{
-    filter::{Filter, FilterMap},
+    aggregate::{Aggregate, Aggregator, CountWhere, Fold, MaxByKey, MinByKey, SumBy},
+    concat::Concat,
+    dedup::DedupByKey,
+    filter::{DynamicFilter, Filter, FilterMap},
+    flatten::Flatten,
+    group_by::{Group, GroupBy},
     head::{EmptyLimitStream, Head},
+    map::Map,
+    reverse::Reverse,
     skip::{EmptyCountStream, Skip},
-    sort::{Sort, SortBy, SortByKey},
+    sort::{DynamicSortBy, Sort, SortBy, SortByKey},
     tail::Tail,
     traits::{
         BatchedVectorSubscriber, VectorDiffContainer, VectorObserver, VectorObserverExt,
         VectorSubscriberExt,
     },
+    window::{EmptyRangeStream, Window},
 };
 
 /// Type alias for extracting the element type from a stream of
@@ -44,6 +60,41 @@ type VectorDiffContainerStreamFamily<S> =
 /// Type alias for a `VectorDiff` of `VectorDiffContainerStreamElement`s.
 type VectorDiffContainerDiff<S> = VectorDiff<VectorDiffContainerStreamElement<S>>;
 
+/// Type alias for extracting the buffer type from a stream of
+/// [`VectorDiffContainer`]s' `ConcatBuf`.
+type VectorDiffContainerStreamConcatBuf<S> =
+    <<S as Stream>::Item as VectorDiffContainerOps<VectorDiffContainerStreamElement<S>>>::ConcatBuf;
+
+/// Type alias for extracting the buffer type from a stream of
+/// [`VectorDiffContainer`]s' `DedupBuf`.
+type VectorDiffContainerStreamDedupBuf<S> =
+    <<S as Stream>::Item as VectorDiffContainerOps<VectorDiffContainerStreamElement<S>>>::DedupBuf;
+
+/// Type alias for extracting the buffer type from a stream of
+/// [`VectorDiffContainer`]s' `FilterBuf`.
+type VectorDiffContainerStreamFilterBuf<S> =
+    <<S as Stream>::Item as VectorDiffContainerOps<VectorDiffContainerStreamElement<S>>>::FilterBuf;
+
+/// Type alias for extracting the `FlattenBuf` buffer type of the stream items
+/// of a [`Flatten`] adapter, from the stream of [`VectorDiffContainer`]s of
+/// the outer vector and the stream of those of the inner vectors.
+type VectorDiffContainerStreamFlattenBuf<S, I> = <VectorDiffContainerStreamMappedItem<
+    S,
+    VectorDiffContainerStreamElement<I>,
+> as VectorDiffContainerOps<
+    VectorDiffContainerStreamElement<I>,
+>>::FlattenBuf;
+
+/// Type alias for extracting the `GroupByBuf` buffer type of the stream items
+/// of a [`GroupBy`] adapter with keys of type `K`, from the stream of
+/// [`VectorDiffContainer`]s of the grouped vector.
+type VectorDiffContainerStreamGroupByBuf<S, K> = <VectorDiffContainerStreamMappedItem<
+    S,
+    Group<K, VectorDiffContainerStreamElement<S>>,
+> as VectorDiffContainerOps<
+    Group<K, VectorDiffContainerStreamElement<S>>,
+>>::GroupByBuf;
+
 /// Type alias for extracting the buffer type from a stream of
 /// [`VectorDiffContainer`]s' `HeadBuf`.
 type VectorDiffContainerStreamHeadBuf<S> =
@@ -63,3 +114,14 @@ type VectorDiffContainerStreamSkipBuf<S> =
 /// [`VectorDiffContainer`]s' `SortBuf`.
 type VectorDiffContainerStreamSortBuf<S> =
     <<S as Stream>::Item as VectorDiffContainerOps<VectorDiffContainerStreamElement<S>>>::SortBuf;
+
+/// Type alias for extracting the buffer type from a stream of
+/// [`VectorDiffContainer`]s' `ReverseBuf`.
+type VectorDiffContainerStreamReverseBuf<S> = <<S as Stream>::Item as VectorDiffContainerOps<
+    VectorDiffContainerStreamElement<S>,
+>>::ReverseBuf;
+
+/// Type alias for extracting the buffer type from a stream of
+/// [`VectorDiffContainer`]s' `WindowBuf`.
+type VectorDiffContainerStreamWindowBuf<S> =
+    <<S as Stream>::Item as VectorDiffContainerOps<VectorDiffContainerStreamElement<S>>>::WindowBuf;
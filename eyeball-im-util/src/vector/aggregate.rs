@@ -0,0 +1,696 @@
+use std::{
+    cmp::{max, min},
+    fmt,
+    ops::{AddAssign, SubAssign},
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use super::{VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+/// An aggregation of the values of a vector that is updated incrementally, as
+/// values are inserted into and removed from the vector.
+///
+/// This is used by the [`Aggregate`] adapter. Implementations are provided
+/// for counting ([`CountWhere`]), summing ([`SumBy`]), finding the smallest or
+/// largest value ([`MinByKey`], [`MaxByKey`]) and for arbitrary folds
+/// ([`Fold`]).
+pub trait Aggregator<T> {
+    /// The result of the aggregation.
+    type Output;
+
+    /// Take into account the value that was inserted at `index`.
+    ///
+    /// `values` are the values of the vector after the insertion.
+    ///
+    /// Returns whether the output may have changed.
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool;
+
+    /// Stop taking into account `value`, that was removed from `index`.
+    ///
+    /// `values` are the values of the vector after the removal.
+    ///
+    /// Returns whether the output may have changed.
+    fn remove(&mut self, values: &Vector<T>, index: usize, value: &T) -> bool;
+
+    /// Stop taking into account `removed`, the values that were removed from
+    /// the indices starting at `start`.
+    ///
+    /// `values` are the values of the vector after the removal. The default
+    /// implementation calls [`remove`][Self::remove] for every removed value,
+    /// starting with the last one; override it if removing many values at
+    /// once can be done more efficiently.
+    ///
+    /// Returns whether the output may have changed.
+    fn remove_range(&mut self, values: &Vector<T>, start: usize, removed: Vector<T>) -> bool
+    where
+        T: Clone,
+    {
+        // Restore the values before the removal, then remove them one by one.
+        let mut values = values.clone();
+        let rest = values.split_off(start);
+        let end = start + removed.len();
+        values.append(removed);
+        values.append(rest);
+
+        let mut changed = false;
+        for index in (start..end).rev() {
+            let value = values.remove(index);
+            changed |= self.remove(&values, index, &value);
+        }
+        changed
+    }
+
+    /// Reset the aggregation to the one of an empty vector.
+    ///
+    /// Returns whether the output may have changed.
+    fn clear(&mut self) -> bool;
+
+    /// Get the result of the aggregation, given the current `values` of the
+    /// vector.
+    fn output(&self, values: &Vector<T>) -> Self::Output;
+}
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that reduces the underlying
+    /// [`ObservableVector`]s items to a single value, using an
+    /// [`Aggregator`].
+    ///
+    /// Contrary to the other adapters of this module, this is not a stream of
+    /// `VectorDiff`s but a stream of the results of the aggregation: every
+    /// time the underlying stream produces an item that changes the result, the
+    /// new result is produced. The aggregation is updated incrementally, so the
+    /// observed vector is not scanned again for every item. Results that are
+    /// equal to the previous one are skipped.
+    ///
+    /// The results can be forwarded to an [`eyeball::Observable`] if they need
+    /// to be shared with multiple subscribers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::ObservableVector;
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+    ///
+    /// // Our vector of unread counts.
+    /// let mut ob = ObservableVector::<u32>::from(vector![0, 2, 1]);
+    /// let (total, mut sub) = ob.subscribe().sum_by(|count| *count);
+    ///
+    /// assert_eq!(total, 3);
+    ///
+    /// ob.push_back(4);
+    /// assert_next_eq!(sub, 7);
+    ///
+    /// // Nothing is produced if the total doesn't change.
+    /// ob.push_back(0);
+    /// assert_pending!(sub);
+    ///
+    /// ob.set(1, 0);
+    /// assert_next_eq!(sub, 5);
+    ///
+    /// ob.clear();
+    /// assert_next_eq!(sub, 0);
+    ///
+    /// assert_pending!(sub);
+    /// drop(ob);
+    /// assert_closed!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    /// [`eyeball::Observable`]: https://docs.rs/eyeball/latest/eyeball/struct.Observable.html
+    #[project = AggregateProj]
+    pub struct Aggregate<S, A>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+        A: Aggregator<VectorDiffContainerStreamElement<S>>,
+    {
+        // The main stream to poll items from.
+        #[pin]
+        inner_stream: S,
+
+        // The buffered vector that is updated with the main stream's items.
+        // It's used to know which values are removed.
+        buffered_vector: Vector<VectorDiffContainerStreamElement<S>>,
+
+        // The aggregation of the values of `buffered_vector`.
+        aggregator: A,
+
+        // The last result of the aggregation, to skip results equal to it.
+        last_output: A::Output,
+    }
+}
+
+impl<S, A> Aggregate<S, A>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    A: Aggregator<VectorDiffContainerStreamElement<S>>,
+    A::Output: Clone + PartialEq,
+{
+    /// Create a new `Aggregate` with the given initial values, stream of
+    /// `VectorDiff` updates for those values, and aggregator.
+    ///
+    /// Returns the result of the aggregation of the initial values, as well as
+    /// a stream of the results of the aggregation after the updates.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        mut aggregator: A,
+    ) -> (A::Output, Self) {
+        let mut buffered_vector = Vector::new();
+        reset(&mut aggregator, &mut buffered_vector, initial_values);
+
+        let output = aggregator.output(&buffered_vector);
+        let last_output = output.clone();
+        (output, Self { inner_stream, buffered_vector, aggregator, last_output })
+    }
+}
+
+impl<S, A> Stream for Aggregate<S, A>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    A: Aggregator<VectorDiffContainerStreamElement<S>>,
+    A::Output: Clone + PartialEq,
+{
+    type Item = A::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let AggregateProj { mut inner_stream, buffered_vector, aggregator, last_output } =
+            self.project();
+
+        loop {
+            let Some(diffs) = ready!(inner_stream.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            let mut changed = false;
+            diffs.for_each(|diff| {
+                changed |= handle_diff(diff, aggregator, buffered_vector);
+            });
+
+            if changed {
+                let output = aggregator.output(buffered_vector);
+                if output != *last_output {
+                    *last_output = output.clone();
+                    return Poll::Ready(Some(output));
+                }
+            }
+        }
+    }
+}
+
+/// Apply `diff` to `buffered_vector` and update `aggregator` accordingly.
+///
+/// Returns whether the output of `aggregator` may have changed.
+fn handle_diff<T, A>(
+    diff: VectorDiff<T>,
+    aggregator: &mut A,
+    buffered_vector: &mut Vector<T>,
+) -> bool
+where
+    T: Clone,
+    A: Aggregator<T>,
+{
+    match diff {
+        VectorDiff::Append { values } => {
+            let mut changed = false;
+            for value in values {
+                buffered_vector.push_back(value);
+                changed |= aggregator.insert(buffered_vector, buffered_vector.len() - 1);
+            }
+            changed
+        }
+        VectorDiff::Clear => {
+            buffered_vector.clear();
+            aggregator.clear()
+        }
+        VectorDiff::PushFront { value } => {
+            buffered_vector.push_front(value);
+            aggregator.insert(buffered_vector, 0)
+        }
+        VectorDiff::PushBack { value } => {
+            buffered_vector.push_back(value);
+            aggregator.insert(buffered_vector, buffered_vector.len() - 1)
+        }
+        VectorDiff::PopFront => {
+            let value = buffered_vector.pop_front().expect("the buffered vector is not empty");
+            aggregator.remove(buffered_vector, 0, &value)
+        }
+        VectorDiff::PopBack => {
+            let value = buffered_vector.pop_back().expect("the buffered vector is not empty");
+            aggregator.remove(buffered_vector, buffered_vector.len(), &value)
+        }
+        VectorDiff::Insert { index, value } => {
+            buffered_vector.insert(index, value);
+            aggregator.insert(buffered_vector, index)
+        }
+        VectorDiff::Set { index, value } => {
+            let old_value = buffered_vector.remove(index);
+            let removed = aggregator.remove(buffered_vector, index, &old_value);
+            buffered_vector.insert(index, value);
+            let inserted = aggregator.insert(buffered_vector, index);
+            removed || inserted
+        }
+        VectorDiff::Remove { index } => {
+            let value = buffered_vector.remove(index);
+            aggregator.remove(buffered_vector, index, &value)
+        }
+        VectorDiff::Truncate { length } => {
+            let removed = buffered_vector.split_off(length);
+            !removed.is_empty() && aggregator.remove_range(buffered_vector, length, removed)
+        }
+        VectorDiff::Reset { values } => reset(aggregator, buffered_vector, values),
+        VectorDiff::Move { from, to } => {
+            let value = buffered_vector.remove(from);
+            let removed = aggregator.remove(buffered_vector, from, &value);
+            buffered_vector.insert(to, value);
+            let inserted = aggregator.insert(buffered_vector, to);
+            removed || inserted
+        }
+        VectorDiff::Swap { first, second } => {
+            // Swapping is equivalent to two moves.
+            let (first, second) = (min(first, second), max(first, second));
+            let moved_first = handle_diff(
+                VectorDiff::Move { from: first, to: second },
+                aggregator,
+                buffered_vector,
+            );
+            let moved_second = handle_diff(
+                VectorDiff::Move { from: second - 1, to: first },
+                aggregator,
+                buffered_vector,
+            );
+            moved_first || moved_second
+        }
+        VectorDiff::InsertMany { index, values } => {
+            let mut changed = false;
+            for (offset, value) in values.into_iter().enumerate() {
+                buffered_vector.insert(index + offset, value);
+                changed |= aggregator.insert(buffered_vector, index + offset);
+            }
+            changed
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            let mut removed = buffered_vector.split_off(start);
+            buffered_vector.append(removed.split_off(end - start));
+            !removed.is_empty() && aggregator.remove_range(buffered_vector, start, removed)
+        }
+        VectorDiff::Splice { start, end, values } => {
+            // Splicing is equivalent to a removal followed by an insertion.
+            let removed =
+                handle_diff(VectorDiff::RemoveRange { start, end }, aggregator, buffered_vector);
+            let inserted = handle_diff(
+                VectorDiff::InsertMany { index: start, values },
+                aggregator,
+                buffered_vector,
+            );
+            removed || inserted
+        }
+    }
+}
+
+/// Replace the values of `buffered_vector` by `values` and update `aggregator`
+/// accordingly.
+///
+/// Returns whether the output of `aggregator` may have changed.
+fn reset<T, A>(aggregator: &mut A, buffered_vector: &mut Vector<T>, values: Vector<T>) -> bool
+where
+    T: Clone,
+    A: Aggregator<T>,
+{
+    buffered_vector.clear();
+    let mut changed = aggregator.clear();
+
+    // Insert the values one by one so that the aggregator never sees a value
+    // before it was inserted.
+    for value in values {
+        buffered_vector.push_back(value);
+        changed |= aggregator.insert(buffered_vector, buffered_vector.len() - 1);
+    }
+
+    changed
+}
+
+/// An [`Aggregator`] counting the values for which a predicate returns `true`.
+///
+/// See [`VectorObserverExt::count_where`](super::VectorObserverExt::count_where).
+pub struct CountWhere<F> {
+    predicate: F,
+    count: usize,
+}
+
+impl<F> CountWhere<F> {
+    /// Create a new `CountWhere` with the given predicate.
+    pub fn new(predicate: F) -> Self {
+        Self { predicate, count: 0 }
+    }
+}
+
+impl<F> fmt::Debug for CountWhere<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountWhere").field("count", &self.count).finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Aggregator<T> for CountWhere<F>
+where
+    F: Fn(&T) -> bool,
+{
+    type Output = usize;
+
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool {
+        let matches = (self.predicate)(&values[index]);
+        if matches {
+            self.count += 1;
+        }
+        matches
+    }
+
+    fn remove(&mut self, _values: &Vector<T>, _index: usize, value: &T) -> bool {
+        let matches = (self.predicate)(value);
+        if matches {
+            self.count -= 1;
+        }
+        matches
+    }
+
+    fn clear(&mut self) -> bool {
+        let changed = self.count != 0;
+        self.count = 0;
+        changed
+    }
+
+    fn output(&self, _values: &Vector<T>) -> usize {
+        self.count
+    }
+}
+
+/// An [`Aggregator`] summing the values returned by a function for every value.
+///
+/// See [`VectorObserverExt::sum_by`](super::VectorObserverExt::sum_by).
+pub struct SumBy<U, F> {
+    f: F,
+    sum: U,
+}
+
+impl<U, F> SumBy<U, F>
+where
+    U: Default,
+{
+    /// Create a new `SumBy` with the given function.
+    pub fn new(f: F) -> Self {
+        Self { f, sum: U::default() }
+    }
+}
+
+impl<U, F> fmt::Debug for SumBy<U, F>
+where
+    U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SumBy").field("sum", &self.sum).finish_non_exhaustive()
+    }
+}
+
+impl<T, U, F> Aggregator<T> for SumBy<U, F>
+where
+    U: Clone + Default + PartialEq + AddAssign + SubAssign,
+    F: Fn(&T) -> U,
+{
+    type Output = U;
+
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool {
+        let term = (self.f)(&values[index]);
+        let changed = term != U::default();
+        self.sum += term;
+        changed
+    }
+
+    fn remove(&mut self, _values: &Vector<T>, _index: usize, value: &T) -> bool {
+        let term = (self.f)(value);
+        let changed = term != U::default();
+        self.sum -= term;
+        changed
+    }
+
+    fn clear(&mut self) -> bool {
+        let changed = self.sum != U::default();
+        self.sum = U::default();
+        changed
+    }
+
+    fn output(&self, _values: &Vector<T>) -> U {
+        self.sum.clone()
+    }
+}
+
+/// An [`Aggregator`] finding the value with the smallest key.
+///
+/// If several values have the smallest key, the first one is returned, like
+/// [`Iterator::min_by_key`].
+///
+/// See [`VectorObserverExt::min_by_key`](super::VectorObserverExt::min_by_key).
+pub struct MinByKey<F> {
+    inner: ExtremeByKey<F>,
+}
+
+impl<F> MinByKey<F> {
+    /// Create a new `MinByKey` with the given key function.
+    pub fn new(key_fn: F) -> Self {
+        Self { inner: ExtremeByKey { key_fn, index: None, is_max: false } }
+    }
+}
+
+impl<F> fmt::Debug for MinByKey<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinByKey").field("index", &self.inner.index).finish_non_exhaustive()
+    }
+}
+
+impl<T, K, F> Aggregator<T> for MinByKey<F>
+where
+    T: Clone,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    type Output = Option<T>;
+
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool {
+        self.inner.insert(values, index)
+    }
+
+    fn remove(&mut self, values: &Vector<T>, index: usize, _value: &T) -> bool {
+        self.inner.remove_range(values, index, 1)
+    }
+
+    fn remove_range(&mut self, values: &Vector<T>, start: usize, removed: Vector<T>) -> bool {
+        self.inner.remove_range(values, start, removed.len())
+    }
+
+    fn clear(&mut self) -> bool {
+        self.inner.index.take().is_some()
+    }
+
+    fn output(&self, values: &Vector<T>) -> Option<T> {
+        self.inner.index.map(|index| values[index].clone())
+    }
+}
+
+/// An [`Aggregator`] finding the value with the largest key.
+///
+/// If several values have the largest key, the last one is returned, like
+/// [`Iterator::max_by_key`].
+///
+/// See [`VectorObserverExt::max_by_key`](super::VectorObserverExt::max_by_key).
+pub struct MaxByKey<F> {
+    inner: ExtremeByKey<F>,
+}
+
+impl<F> MaxByKey<F> {
+    /// Create a new `MaxByKey` with the given key function.
+    pub fn new(key_fn: F) -> Self {
+        Self { inner: ExtremeByKey { key_fn, index: None, is_max: true } }
+    }
+}
+
+impl<F> fmt::Debug for MaxByKey<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaxByKey").field("index", &self.inner.index).finish_non_exhaustive()
+    }
+}
+
+impl<T, K, F> Aggregator<T> for MaxByKey<F>
+where
+    T: Clone,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    type Output = Option<T>;
+
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool {
+        self.inner.insert(values, index)
+    }
+
+    fn remove(&mut self, values: &Vector<T>, index: usize, _value: &T) -> bool {
+        self.inner.remove_range(values, index, 1)
+    }
+
+    fn remove_range(&mut self, values: &Vector<T>, start: usize, removed: Vector<T>) -> bool {
+        self.inner.remove_range(values, start, removed.len())
+    }
+
+    fn clear(&mut self) -> bool {
+        self.inner.index.take().is_some()
+    }
+
+    fn output(&self, values: &Vector<T>) -> Option<T> {
+        self.inner.index.map(|index| values[index].clone())
+    }
+}
+
+/// The shared implementation of [`MinByKey`] and [`MaxByKey`].
+///
+/// Values are compared by key first, then by index, so that the first value is
+/// the smallest amongst equal keys, and the last value is the largest.
+struct ExtremeByKey<F> {
+    key_fn: F,
+
+    // The index of the smallest or largest value, `None` if the vector is
+    // empty.
+    index: Option<usize>,
+
+    // Whether the largest value is looked for, rather than the smallest.
+    is_max: bool,
+}
+
+impl<F> ExtremeByKey<F> {
+    fn insert<T, K>(&mut self, values: &Vector<T>, index: usize) -> bool
+    where
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        let Some(current) = &mut self.index else {
+            self.index = Some(index);
+            return true;
+        };
+
+        // The current value is shifted by the insertion.
+        if *current >= index {
+            *current += 1;
+        }
+
+        let new = ((self.key_fn)(&values[index]), index);
+        let old = ((self.key_fn)(&values[*current]), *current);
+        let replace = if self.is_max { new > old } else { new < old };
+
+        if replace {
+            *current = index;
+        }
+
+        replace
+    }
+
+    /// Take into account the removal of `len` values starting at `start`.
+    fn remove_range<T, K>(&mut self, values: &Vector<T>, start: usize, len: usize) -> bool
+    where
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        let Some(current) = &mut self.index else {
+            return false;
+        };
+
+        if (start..start + len).contains(current) {
+            // The current value was removed, find a new one.
+            let enumerated = values.iter().enumerate();
+            self.index = if self.is_max {
+                enumerated.max_by_key(|(_, value)| (self.key_fn)(value))
+            } else {
+                enumerated.min_by_key(|(_, value)| (self.key_fn)(value))
+            }
+            .map(|(index, _)| index);
+
+            true
+        } else {
+            // The current value is shifted by the removal.
+            if *current >= start + len {
+                *current -= len;
+            }
+
+            false
+        }
+    }
+}
+
+/// An [`Aggregator`] folding the values into an accumulator.
+///
+/// The accumulator is updated with an `add` function when values are
+/// inserted, and with a `remove` function when values are removed, which must
+/// undo what `add` did for the same value.
+///
+/// See [`VectorObserverExt::fold`](super::VectorObserverExt::fold).
+pub struct Fold<A, F, G> {
+    init: A,
+    accumulator: A,
+    add: F,
+    remove: G,
+}
+
+impl<A, F, G> Fold<A, F, G>
+where
+    A: Clone,
+{
+    /// Create a new `Fold` with the given initial value of the accumulator,
+    /// and functions to add and remove values from the accumulator.
+    pub fn new(init: A, add: F, remove: G) -> Self {
+        Self { accumulator: init.clone(), init, add, remove }
+    }
+}
+
+impl<A, F, G> fmt::Debug for Fold<A, F, G>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fold").field("accumulator", &self.accumulator).finish_non_exhaustive()
+    }
+}
+
+impl<T, A, F, G> Aggregator<T> for Fold<A, F, G>
+where
+    A: Clone + PartialEq,
+    F: FnMut(&mut A, &T),
+    G: FnMut(&mut A, &T),
+{
+    type Output = A;
+
+    fn insert(&mut self, values: &Vector<T>, index: usize) -> bool {
+        let previous = self.accumulator.clone();
+        (self.add)(&mut self.accumulator, &values[index]);
+        self.accumulator != previous
+    }
+
+    fn remove(&mut self, _values: &Vector<T>, _index: usize, value: &T) -> bool {
+        let previous = self.accumulator.clone();
+        (self.remove)(&mut self.accumulator, value);
+        self.accumulator != previous
+    }
+
+    fn clear(&mut self) -> bool {
+        let changed = self.accumulator != self.init;
+        self.accumulator = self.init.clone();
+        changed
+    }
+
+    fn output(&self, _values: &Vector<T>) -> A {
+        self.accumulator.clone()
+    }
+}
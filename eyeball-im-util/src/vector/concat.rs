@@ -0,0 +1,335 @@
+use smallvec::{smallvec, SmallVec};
+use std::{
+    iter::repeat,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use super::{
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamConcatBuf,
+    VectorDiffContainerStreamElement, VectorObserver,
+};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents the concatenation of
+    /// multiple underlying [`ObservableVector`]s' items as a single vector.
+    ///
+    /// The indices of every source's `VectorDiff`s are shifted by the lengths
+    /// of the preceding sources. `VectorDiff`s that only make sense for the
+    /// whole vector, like `VectorDiff::Clear` or `VectorDiff::Reset`, are
+    /// translated into the equivalent removals and insertions for the range of
+    /// the source they come from.
+    ///
+    /// The stream ends once the streams of all of the sources have ended.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_next_eq, assert_pending};
+    ///
+    /// let mut pinned = ObservableVector::<char>::from(vector!['a']);
+    /// let mut regular = ObservableVector::<char>::from(vector!['x', 'y']);
+    /// let (values, mut sub) = pinned.subscribe().chain(regular.subscribe());
+    ///
+    /// assert_eq!(values, vector!['a', 'x', 'y']);
+    ///
+    /// pinned.push_back('b');
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 'b' });
+    ///
+    /// regular.push_front('w');
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 'w' });
+    ///
+    /// // | `pinned`  | a b         |
+    /// // | `regular` |     w x y   |
+    /// // | `sub`     | a b w x y   |
+    ///
+    /// pinned.clear();
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    ///
+    /// assert_pending!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    pub struct Concat<S>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+    {
+        // The sources, in order.
+        sources: Vec<ConcatSource<S>>,
+
+        // This adapter can produce many items per item of the underlying
+        // streams.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-batched, can't
+        // just add diffs to a `poll_next` result), we need a buffer to store
+        // the possible extra items in.
+        ready_values: VectorDiffContainerStreamConcatBuf<S>,
+    }
+}
+
+pub(super) struct ConcatSource<S> {
+    // The stream of the source, `None` once it has ended.
+    pub(super) stream: Option<S>,
+
+    // The current length of the source's vector.
+    pub(super) len: usize,
+}
+
+impl<S> Concat<S>
+where
+    S: Stream + Unpin,
+    S::Item: VectorDiffContainer,
+{
+    /// Create a new `Concat` from the given observers, e.g.
+    /// [`VectorSubscriber`][eyeball_im::VectorSubscriber]s.
+    ///
+    /// Returns the concatenation of the observers' initial values as well as
+    /// a stream of updates for it.
+    pub fn new<O>(
+        observers: impl IntoIterator<Item = O>,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self)
+    where
+        O: VectorObserver<VectorDiffContainerStreamElement<S>, Stream = S>,
+    {
+        let mut values = Vector::new();
+        let sources = observers
+            .into_iter()
+            .map(|observer| {
+                let (source_values, stream) = observer.into_parts();
+                let len = source_values.len();
+                values.append(source_values);
+                ConcatSource { stream: Some(stream), len }
+            })
+            .collect();
+
+        (values, Self { sources, ready_values: Default::default() })
+    }
+}
+
+impl<S> Stream for Concat<S>
+where
+    S: Stream + Unpin,
+    S::Item: VectorDiffContainer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        'poll: loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_concat_buf(this.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            let mut all_ended = true;
+            for source_idx in 0..this.sources.len() {
+                let Some(stream) = &mut this.sources[source_idx].stream else {
+                    continue;
+                };
+
+                match Pin::new(stream).poll_next(cx) {
+                    Poll::Ready(Some(diffs)) => {
+                        let ready = diffs.push_into_concat_buf(this.ready_values, |diff| {
+                            handle_diff(diff, this.sources, source_idx)
+                        });
+
+                        if let Some(diff) = ready {
+                            return Poll::Ready(Some(diff));
+                        }
+
+                        // Poll the streams again.
+                        continue 'poll;
+                    }
+                    Poll::Ready(None) => {
+                        this.sources[source_idx].stream = None;
+                    }
+                    Poll::Pending => {
+                        all_ended = false;
+                    }
+                }
+            }
+
+            return if all_ended { Poll::Ready(None) } else { Poll::Pending };
+        }
+    }
+}
+
+/// Translate a `VectorDiff` of the source at `source_idx` into `VectorDiff`s
+/// of the concatenated vector, and update the source's length.
+pub(super) fn handle_diff<S, T: Clone>(
+    diff: VectorDiff<T>,
+    sources: &mut [ConcatSource<S>],
+    source_idx: usize,
+) -> SmallVec<[VectorDiff<T>; 2]> {
+    // The position of the source's items in the concatenated vector.
+    let offset: usize = sources[..source_idx].iter().map(|source| source.len).sum();
+    // Whether there are no items after the source's items.
+    let is_last = sources[source_idx + 1..].iter().all(|source| source.len == 0);
+    // Whether the source's items are the only items.
+    let is_only = offset == 0 && is_last;
+
+    let len = &mut sources[source_idx].len;
+    let prev_len = *len;
+
+    match diff {
+        VectorDiff::Append { values } => {
+            *len += values.len();
+
+            if is_last {
+                smallvec![VectorDiff::Append { values }]
+            } else {
+                insert_all(offset + prev_len, values)
+            }
+        }
+        VectorDiff::Clear => {
+            *len = 0;
+
+            if is_only {
+                smallvec![VectorDiff::Clear]
+            } else {
+                remove_range(offset, prev_len, is_last)
+            }
+        }
+        VectorDiff::PushFront { value } => {
+            *len += 1;
+
+            if offset == 0 {
+                smallvec![VectorDiff::PushFront { value }]
+            } else {
+                smallvec![VectorDiff::Insert { index: offset, value }]
+            }
+        }
+        VectorDiff::PushBack { value } => {
+            *len += 1;
+
+            if is_last {
+                smallvec![VectorDiff::PushBack { value }]
+            } else {
+                smallvec![VectorDiff::Insert { index: offset + prev_len, value }]
+            }
+        }
+        VectorDiff::PopFront => {
+            *len -= 1;
+
+            if offset == 0 {
+                smallvec![VectorDiff::PopFront]
+            } else {
+                smallvec![VectorDiff::Remove { index: offset }]
+            }
+        }
+        VectorDiff::PopBack => {
+            *len -= 1;
+
+            if is_last {
+                smallvec![VectorDiff::PopBack]
+            } else {
+                smallvec![VectorDiff::Remove { index: offset + prev_len - 1 }]
+            }
+        }
+        VectorDiff::Insert { index, value } => {
+            *len += 1;
+            smallvec![VectorDiff::Insert { index: offset + index, value }]
+        }
+        VectorDiff::Set { index, value } => {
+            smallvec![VectorDiff::Set { index: offset + index, value }]
+        }
+        VectorDiff::Remove { index } => {
+            *len -= 1;
+            smallvec![VectorDiff::Remove { index: offset + index }]
+        }
+        VectorDiff::Truncate { length } => {
+            *len = length;
+            remove_range(offset + length, prev_len - length, is_last)
+        }
+        VectorDiff::Reset { values } => {
+            *len = values.len();
+
+            if is_only {
+                smallvec![VectorDiff::Reset { values }]
+            } else {
+                let mut diffs = remove_range(offset, prev_len, is_last);
+                if is_last {
+                    if !values.is_empty() {
+                        diffs.push(VectorDiff::Append { values });
+                    }
+                } else {
+                    diffs.extend(insert_all(offset, values));
+                }
+                diffs
+            }
+        }
+        VectorDiff::Move { from, to } => {
+            smallvec![VectorDiff::Move { from: offset + from, to: offset + to }]
+        }
+        VectorDiff::Swap { first, second } => {
+            smallvec![VectorDiff::Swap { first: offset + first, second: offset + second }]
+        }
+        VectorDiff::InsertMany { index, values } => {
+            *len += values.len();
+            smallvec![VectorDiff::InsertMany { index: offset + index, values }]
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            *len -= end - start;
+            smallvec![VectorDiff::RemoveRange { start: offset + start, end: offset + end }]
+        }
+        VectorDiff::Splice { start, end, values } => {
+            *len = prev_len - (end - start) + values.len();
+            smallvec![VectorDiff::Splice { start: offset + start, end: offset + end, values }]
+        }
+    }
+}
+
+/// Remove `count` items starting at `index`, where `is_last` tells whether
+/// there are no items after them.
+pub(super) fn remove_range<T: Clone>(
+    index: usize,
+    count: usize,
+    is_last: bool,
+) -> SmallVec<[VectorDiff<T>; 2]> {
+    if count == 0 {
+        SmallVec::new()
+    } else if is_last {
+        smallvec![VectorDiff::Truncate { length: index }]
+    } else {
+        repeat(VectorDiff::Remove { index }).take(count).collect()
+    }
+}
+
+/// Replace the items in `start..end` by `values`, using the most specific
+/// `VectorDiff`.
+///
+/// Returns `None` if there is nothing to replace.
+pub(super) fn splice<T: Clone>(
+    start: usize,
+    end: usize,
+    values: Vector<T>,
+) -> Option<VectorDiff<T>> {
+    match (start == end, values.is_empty()) {
+        (true, true) => None,
+        (true, false) => Some(VectorDiff::InsertMany { index: start, values }),
+        (false, true) => Some(VectorDiff::RemoveRange { start, end }),
+        (false, false) => Some(VectorDiff::Splice { start, end, values }),
+    }
+}
+
+/// Insert all `values` starting at `index`.
+pub(super) fn insert_all<T: Clone>(
+    index: usize,
+    values: Vector<T>,
+) -> SmallVec<[VectorDiff<T>; 2]> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| VectorDiff::Insert { index: index + i, value })
+        .collect()
+}
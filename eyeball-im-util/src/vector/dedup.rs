@@ -0,0 +1,485 @@
+use smallvec::SmallVec;
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use super::{
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamDedupBuf,
+    VectorDiffContainerStreamElement,
+};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a deduplicated view of
+    /// the underlying [`ObservableVector`]s items.
+    ///
+    /// Only the first value with a given key is visible, later values with
+    /// the same key are hidden. When the visible value is removed or gets a
+    /// different key, the next value with its key (if any) becomes visible in
+    /// its place, and when a value is added before the visible value with the
+    /// same key, it replaces that value.
+    ///
+    /// The number of values with every key is tracked, such that values with
+    /// a new key are handled in constant time. Revealing the next value with
+    /// a key when the visible one is removed requires scanning the values
+    /// after it though.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+    ///
+    /// let mut ob = ObservableVector::<(u32, char)>::from(vector![(1, 'a'), (2, 'b'), (1, 'c')]);
+    /// let (values, mut sub) = ob.subscribe().dedup_by_key(|&(id, _)| id);
+    ///
+    /// assert_eq!(values, vector![(1, 'a'), (2, 'b')]);
+    ///
+    /// ob.push_back((2, 'd'));
+    /// assert_pending!(sub);
+    ///
+    /// ob.push_back((3, 'e'));
+    /// assert_next_eq!(sub, VectorDiff::PushBack { value: (3, 'e') });
+    ///
+    /// // | `ob`  | 1a 2b 1c 2d 3e |
+    /// // | `sub` | 1a 2b       3e |
+    ///
+    /// // Removing a visible value reveals the next value with the same key.
+    /// ob.remove(0);
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: (1, 'c') });
+    ///
+    /// // | `ob`  | 2b 1c 2d 3e |
+    /// // | `sub` | 2b 1c    3e |
+    ///
+    /// // Giving a value the key of an earlier value hides it.
+    /// ob.set(3, (1, 'f'));
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+    ///
+    /// // | `ob`  | 2b 1c 2d 1f |
+    /// // | `sub` | 2b 1c       |
+    ///
+    /// assert_pending!(sub);
+    /// drop(ob);
+    /// assert_closed!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = DedupByKeyProj]
+    pub struct DedupByKey<S, K, F>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+    {
+        #[pin]
+        inner_stream: S,
+
+        state: DedupState<VectorDiffContainerStreamElement<S>, K, F>,
+
+        // This adapter can produce multiple items per item of the underlying
+        // stream.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-batched, can't
+        // just add diffs to a `poll_next` result), we need a buffer to store
+        // the possible extra items in.
+        ready_values: VectorDiffContainerStreamDedupBuf<S>,
+    }
+}
+
+impl<S, K, F> DedupByKey<S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Hash + Eq,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    /// Create a new `DedupByKey` with the given (non-deduplicated) initial
+    /// values, stream of `VectorDiff` updates for those values, and key
+    /// function.
+    ///
+    /// Returns the deduplicated initial values as well as a stream of updates
+    /// for them.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        key_fn: F,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        let (visible_values, visible_indices, key_counts) = dedup(&initial_values, &key_fn);
+        let state = DedupState { key_fn, values: initial_values, visible_indices, key_counts };
+        let stream = Self { inner_stream, state, ready_values: Default::default() };
+        (visible_values, stream)
+    }
+}
+
+impl<S, K, F> Stream for DedupByKey<S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Hash + Eq,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, K, F> DedupByKeyProj<'_, S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Hash + Eq,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_dedup_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll `VectorDiff`s from the `inner_stream`.
+            let Some(diffs) = ready!(self.inner_stream.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Consume and apply the diffs if possible.
+            let ready =
+                diffs.push_into_dedup_buf(self.ready_values, |diff| self.state.handle_diff(diff));
+
+            if let Some(diff) = ready {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Else loop and poll the streams again.
+        }
+    }
+}
+
+struct DedupState<T, K, F> {
+    // The function to get the key of a value.
+    key_fn: F,
+
+    // The values of the underlying vector.
+    values: Vector<T>,
+
+    // Original indices of the visible values, in ascending order.
+    visible_indices: VecDeque<usize>,
+
+    // The number of values with each key, keys without values are removed.
+    key_counts: HashMap<K, usize>,
+}
+
+impl<T, K, F> DedupState<T, K, F>
+where
+    T: Clone,
+    K: Hash + Eq,
+    F: Fn(&T) -> K,
+{
+    fn handle_diff(&mut self, diff: VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]> {
+        let mut diffs = SmallVec::new();
+
+        match diff {
+            VectorDiff::Append { values } => {
+                let mut visible_values = Vector::new();
+                for value in values {
+                    if self.add_key((self.key_fn)(&value)) {
+                        self.visible_indices.push_back(self.values.len());
+                        visible_values.push_back(value.clone());
+                    }
+                    self.values.push_back(value);
+                }
+
+                if !visible_values.is_empty() {
+                    diffs.push(VectorDiff::Append { values: visible_values });
+                }
+            }
+            VectorDiff::Clear => {
+                self.values.clear();
+                self.key_counts.clear();
+                if !self.visible_indices.is_empty() {
+                    self.visible_indices.clear();
+                    diffs.push(VectorDiff::Clear);
+                }
+            }
+            VectorDiff::PushFront { value } => {
+                self.insert(0, value, &mut diffs);
+            }
+            VectorDiff::PushBack { value } => {
+                // A new last value is only visible if its key is new.
+                if self.add_key((self.key_fn)(&value)) {
+                    self.visible_indices.push_back(self.values.len());
+                    diffs.push(VectorDiff::PushBack { value: value.clone() });
+                }
+                self.values.push_back(value);
+            }
+            VectorDiff::PopFront => {
+                self.remove(0, &mut diffs);
+            }
+            VectorDiff::PopBack => {
+                // If the last value is visible, it is the only one with its
+                // key, so there is no other value to reveal.
+                if let Some(value) = self.values.pop_back() {
+                    self.remove_key(&(self.key_fn)(&value));
+                }
+                if self.visible_indices.back() == Some(&self.values.len()) {
+                    self.visible_indices.pop_back();
+                    diffs.push(VectorDiff::PopBack);
+                }
+            }
+            VectorDiff::Insert { index, value } => {
+                self.insert(index, value, &mut diffs);
+            }
+            VectorDiff::Set { index, value } => {
+                self.set(index, value, &mut diffs);
+            }
+            VectorDiff::Remove { index } => {
+                self.remove(index, &mut diffs);
+            }
+            VectorDiff::Truncate { length } => {
+                // The first value with any of the remaining keys is before
+                // `length`, so no hidden value is revealed.
+                for value in self.values.split_off(length) {
+                    self.remove_key(&(self.key_fn)(&value));
+                }
+                let visible_len = self.visible_indices.partition_point(|&i| i < length);
+                if visible_len < self.visible_indices.len() {
+                    self.visible_indices.truncate(visible_len);
+                    diffs.push(VectorDiff::Truncate { length: visible_len });
+                }
+            }
+            VectorDiff::Reset { values } => {
+                let (visible_values, visible_indices, key_counts) = dedup(&values, &self.key_fn);
+                self.values = values;
+                self.visible_indices = visible_indices;
+                self.key_counts = key_counts;
+                diffs.push(VectorDiff::Reset { values: visible_values });
+            }
+            VectorDiff::Move { from, to } => {
+                self.move_value(from, to, &mut diffs);
+            }
+            VectorDiff::Swap { first, second } => {
+                // Swapping is equivalent to two moves.
+                let (first, second) = (first.min(second), first.max(second));
+                self.move_value(first, second, &mut diffs);
+                self.move_value(second - 1, first, &mut diffs);
+            }
+            VectorDiff::InsertMany { index, values } => {
+                for (offset, value) in values.into_iter().enumerate() {
+                    self.insert(index + offset, value, &mut diffs);
+                }
+            }
+            VectorDiff::RemoveRange { start, end } => {
+                for _ in start..end {
+                    self.remove(start, &mut diffs);
+                }
+            }
+            VectorDiff::Splice { start, end, values } => {
+                for _ in start..end {
+                    self.remove(start, &mut diffs);
+                }
+                for (offset, value) in values.into_iter().enumerate() {
+                    self.insert(start + offset, value, &mut diffs);
+                }
+            }
+        }
+
+        diffs
+    }
+
+    /// Move the value at the original index `from` to the original index `to`.
+    fn move_value(&mut self, from: usize, to: usize, diffs: &mut SmallVec<[VectorDiff<T>; 2]>) {
+        let was_visible = self.is_visible(from);
+
+        let mut moved = SmallVec::new();
+        let value = self.values[from].clone();
+        self.remove(from, &mut moved);
+        self.insert(to, value, &mut moved);
+
+        if was_visible && self.is_visible(to) {
+            // The value stays visible, so it's the only visible value to
+            // change position.
+            match moved.as_slice() {
+                // It stays at the same position.
+                [VectorDiff::Set { .. }] => return,
+                [VectorDiff::Remove { index: from }, VectorDiff::Insert { index: to, .. }] => {
+                    diffs.push(VectorDiff::Move { from: *from, to: *to });
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        for diff in moved {
+            push_diff(diffs, diff);
+        }
+    }
+
+    /// Whether the value at the original index `index` is visible.
+    fn is_visible(&self, index: usize) -> bool {
+        self.visible_indices.binary_search(&index).is_ok()
+    }
+
+    /// Insert `value` at the original index `index`.
+    fn insert(&mut self, index: usize, value: T, diffs: &mut SmallVec<[VectorDiff<T>; 2]>) {
+        let key = (self.key_fn)(&value);
+        let prev_first = self.first_index(&key);
+        self.add_key(key);
+        self.values.insert(index, value.clone());
+
+        let pos = self.visible_indices.partition_point(|&i| i < index);
+        for idx in self.visible_indices.iter_mut().skip(pos) {
+            *idx += 1;
+        }
+
+        match prev_first {
+            // There is an earlier value with the same key, the new value is
+            // hidden.
+            Some(first) if first < index => {}
+            prev_first => {
+                if let Some(first) = prev_first {
+                    // The new value comes before the value with the same key
+                    // that was visible so far, which is hidden now.
+                    let first_pos = self.visible_indices.partition_point(|&i| i <= first);
+                    self.visible_indices.remove(first_pos);
+                    push_diff(diffs, VectorDiff::Remove { index: first_pos });
+                }
+
+                self.visible_indices.insert(pos, index);
+                push_diff(diffs, VectorDiff::Insert { index: pos, value });
+            }
+        }
+    }
+
+    /// Replace the value at the original index `index` by `value`.
+    fn set(&mut self, index: usize, value: T, diffs: &mut SmallVec<[VectorDiff<T>; 2]>) {
+        if (self.key_fn)(&self.values[index]) == (self.key_fn)(&value) {
+            // Same key, so the value stays visible or hidden.
+            self.values.set(index, value.clone());
+            let pos = self.visible_indices.partition_point(|&i| i < index);
+            if self.visible_indices.get(pos) == Some(&index) {
+                diffs.push(VectorDiff::Set { index: pos, value });
+            }
+        } else {
+            self.remove(index, diffs);
+            self.insert(index, value, diffs);
+        }
+    }
+
+    /// Remove the value at the original index `index`.
+    fn remove(&mut self, index: usize, diffs: &mut SmallVec<[VectorDiff<T>; 2]>) {
+        let value = self.values.remove(index);
+        let key = (self.key_fn)(&value);
+        let has_other_values = self.remove_key(&key);
+
+        let pos = self.visible_indices.partition_point(|&i| i < index);
+        let was_visible = self.visible_indices.get(pos) == Some(&index);
+        if was_visible {
+            self.visible_indices.remove(pos);
+        }
+        for idx in self.visible_indices.iter_mut().skip(pos) {
+            *idx -= 1;
+        }
+
+        if was_visible {
+            push_diff(diffs, VectorDiff::Remove { index: pos });
+
+            // Reveal the next value with the same key, if any. It can only be
+            // after the removed one, which was the first.
+            if has_other_values {
+                let next = self.values.iter().skip(index).position(|v| (self.key_fn)(v) == key);
+                let next = index + next.expect("counted values with the key exist");
+                let next_pos = self.visible_indices.partition_point(|&i| i < next);
+                self.visible_indices.insert(next_pos, next);
+                let value = self.values[next].clone();
+                push_diff(diffs, VectorDiff::Insert { index: next_pos, value });
+            }
+        }
+    }
+
+    /// Find the original index of the first value with the given key.
+    fn first_index(&self, key: &K) -> Option<usize> {
+        if !self.key_counts.contains_key(key) {
+            return None;
+        }
+
+        // The first value with any key is visible.
+        let first = self.visible_indices.iter().find(|&&i| (self.key_fn)(&self.values[i]) == *key);
+        Some(*first.expect("counted keys have a visible value"))
+    }
+
+    /// Count a new value with the given key, returning whether it is the only
+    /// value with that key.
+    fn add_key(&mut self, key: K) -> bool {
+        let count = self.key_counts.entry(key).or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
+    /// Stop counting a value with the given key, returning whether there are
+    /// other values with that key.
+    fn remove_key(&mut self, key: &K) -> bool {
+        let count = self.key_counts.get_mut(key).expect("the keys of all values are counted");
+        *count -= 1;
+        if *count == 0 {
+            self.key_counts.remove(key);
+            return false;
+        }
+        true
+    }
+}
+
+/// Push `diff` to `diffs`, merging it with the previous diff where possible.
+///
+/// An `Insert` directly following a `Remove` at the same index becomes a
+/// `Set`, and a `Remove` directly following an `Insert` at the same index
+/// cancels it out.
+fn push_diff<T: Clone>(diffs: &mut SmallVec<[VectorDiff<T>; 2]>, diff: VectorDiff<T>) {
+    match (diffs.last(), diff) {
+        (Some(VectorDiff::Remove { index: removed }), VectorDiff::Insert { index, value })
+            if *removed == index =>
+        {
+            diffs.pop();
+            diffs.push(VectorDiff::Set { index, value });
+        }
+        (Some(VectorDiff::Insert { index: inserted, .. }), VectorDiff::Remove { index })
+            if *inserted == index =>
+        {
+            diffs.pop();
+        }
+        (_, diff) => diffs.push(diff),
+    }
+}
+
+/// Find the first value for every key among `values`, returning those values,
+/// their indices and the number of values with every key.
+fn dedup<T, K, F>(values: &Vector<T>, key_fn: &F) -> (Vector<T>, VecDeque<usize>, HashMap<K, usize>)
+where
+    T: Clone,
+    K: Hash + Eq,
+    F: Fn(&T) -> K,
+{
+    let mut key_counts = HashMap::new();
+    let mut visible_values = Vector::new();
+    let mut visible_indices = VecDeque::new();
+
+    for (idx, value) in values.iter().enumerate() {
+        let count = key_counts.entry(key_fn(value)).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            visible_values.push_back(value.clone());
+            visible_indices.push_back(idx);
+        }
+    }
+
+    (visible_values, visible_indices, key_counts)
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::VecDeque,
+    mem,
     ops::Not,
     pin::Pin,
     task::{self, ready, Poll},
@@ -10,8 +11,9 @@ use futures_core::Stream;
 use pin_project_lite::pin_project;
 
 use super::{
-    VectorDiffContainer, VectorDiffContainerDiff, VectorDiffContainerOps,
-    VectorDiffContainerStreamElement, VectorDiffContainerStreamMappedItem,
+    concat, VectorDiffContainer, VectorDiffContainerDiff, VectorDiffContainerOps,
+    VectorDiffContainerStreamElement, VectorDiffContainerStreamFilterBuf,
+    VectorDiffContainerStreamMappedItem,
 };
 
 pin_project! {
@@ -126,6 +128,267 @@ where
     }
 }
 
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a filtered view of the
+    /// underlying [`ObservableVector`]s items, with a filter that can be
+    /// replaced at runtime.
+    ///
+    /// New filters are polled from a stream. Whenever a new filter is
+    /// received, the minimal set of `VectorDiff::Insert`s and
+    /// `VectorDiff::Remove`s needed to get from the previously filtered items
+    /// to the newly filtered ones is produced. If no items were previously
+    /// visible, this is a single `VectorDiff::Append`, and if no items are
+    /// visible anymore, a single `VectorDiff::Clear`.
+    ///
+    /// An internal buffered vector is kept so that the adapter knows which
+    /// items to check against the new filter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball::Observable;
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use futures_util::StreamExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_next_eq, assert_pending};
+    ///
+    /// let ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4]);
+    /// let mut max = Observable::new(2);
+    ///
+    /// // All filters must have the same type, so create them the same way.
+    /// let make_filter = |max: u32| move |&i: &u32| i <= max;
+    /// let filter_stream = Observable::subscribe(&max).map(make_filter);
+    ///
+    /// let (values, mut sub) =
+    ///     ob.subscribe().dynamic_filter_with_initial_value(make_filter(2), filter_stream);
+    /// assert_eq!(values, vector![1, 2]);
+    /// assert_pending!(sub);
+    ///
+    /// Observable::set(&mut max, 3);
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 3 });
+    ///
+    /// Observable::set(&mut max, 1);
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    /// assert_pending!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = DynamicFilterProj]
+    pub struct DynamicFilter<S, P>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+        P: Stream,
+    {
+        #[pin]
+        inner: FilterImpl<S>,
+
+        // The filter stream to poll new filters from.
+        #[pin]
+        filter_stream: P,
+
+        // The current filter, `None` until the first filter is received if
+        // there was no initial filter.
+        filter: Option<P::Item>,
+
+        // The buffered vector that is updated with the main stream's items.
+        // It's used to check all of the items against a new filter.
+        buffered_vector: Vector<VectorDiffContainerStreamElement<S>>,
+
+        // A new filter can produce multiple items. If the item type is just
+        // `VectorDiff<_>` (non-batched), we need a buffer to store the extra
+        // items in.
+        ready_values: VectorDiffContainerStreamFilterBuf<S>,
+    }
+}
+
+impl<S, P> DynamicFilter<S, P>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    P: Stream,
+    P::Item: Fn(&VectorDiffContainerStreamElement<S>) -> bool,
+{
+    /// Create a new `DynamicFilter` with the given (unfiltered) initial
+    /// values, stream of `VectorDiff` updates for those values, and stream of
+    /// filters.
+    ///
+    /// This is equivalent to `with_initial_filter` with a filter that doesn't
+    /// match anything, except that it doesn't return the filtered vector as it
+    /// would be empty anyways.
+    ///
+    /// Note that the returned `DynamicFilter` won't produce anything until the
+    /// first filter is produced by the filter stream.
+    pub fn new(
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner: S,
+        filter_stream: P,
+    ) -> Self {
+        let original_len = values.len();
+        let inner = FilterImpl { inner, filtered_indices: VecDeque::new(), original_len };
+        Self {
+            inner,
+            filter_stream,
+            filter: None,
+            buffered_vector: values,
+            ready_values: Default::default(),
+        }
+    }
+
+    /// Create a new `DynamicFilter` with the given (unfiltered) initial
+    /// values, stream of `VectorDiff` updates for those values, and an initial
+    /// filter as well as a stream of new filters.
+    pub fn with_initial_filter(
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner: S,
+        initial_filter: P::Item,
+        filter_stream: P,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        let buffered_vector = values.clone();
+        let (values, Filter { inner, filter }) = Filter::new(values, inner, initial_filter);
+        let stream = Self {
+            inner,
+            filter_stream,
+            filter: Some(filter),
+            buffered_vector,
+            ready_values: Default::default(),
+        };
+
+        (values, stream)
+    }
+}
+
+impl<S, P> Stream for DynamicFilter<S, P>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    P: Stream,
+    P::Item: Fn(&VectorDiffContainerStreamElement<S>) -> bool,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, P> DynamicFilterProj<'_, S, P>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    P: Stream,
+    P::Item: Fn(&VectorDiffContainerStreamElement<S>) -> bool,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_filter_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll a new filter from `filter_stream` before polling `inner`.
+            while let Poll::Ready(Some(next_filter)) = self.filter_stream.as_mut().poll_next(cx) {
+                let diffs = self.update_filter(next_filter);
+                if !diffs.is_empty() {
+                    return Poll::Ready(S::Item::extend_filter_buf(diffs, self.ready_values));
+                }
+
+                // If the filtered items didn't change, poll the filter stream
+                // again.
+            }
+
+            // Poll `VectorDiff`s from the inner stream.
+            let mut inner = self.inner.as_mut().project();
+            let Some(diffs) = ready!(inner.inner.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Without a filter, nothing is visible.
+            let filter = &*self.filter;
+            let f = |value: &_| filter.as_ref().map_or(false, |f| f(value));
+
+            let buffered_vector = &mut *self.buffered_vector;
+            let result = diffs.filter_map(|diff| {
+                // Update the `buffered_vector`. It's a replica of the original
+                // observed `Vector`.
+                diff.clone().apply(buffered_vector);
+                inner.handle_filter(diff, &f)
+            });
+
+            if let Some(diffs) = result {
+                return Poll::Ready(Some(diffs));
+            }
+        }
+    }
+
+    /// Replace the filter and compute the `VectorDiff`s that turn the
+    /// previously filtered items into the newly filtered ones.
+    fn update_filter(&mut self, new_filter: P::Item) -> Vec<VectorDiffContainerDiff<S>> {
+        let new_indices: VecDeque<_> = self
+            .buffered_vector
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, value)| new_filter(value).then_some(idx))
+            .collect();
+        *self.filter = Some(new_filter);
+
+        let inner = self.inner.as_mut().project();
+        let old_indices = mem::replace(inner.filtered_indices, new_indices);
+        let new_indices = &*inner.filtered_indices;
+
+        if old_indices.is_empty() {
+            return if new_indices.is_empty() {
+                Vec::new()
+            } else {
+                let values =
+                    new_indices.iter().map(|&idx| self.buffered_vector[idx].clone()).collect();
+                vec![VectorDiff::Append { values }]
+            };
+        }
+        if new_indices.is_empty() {
+            return vec![VectorDiff::Clear];
+        }
+
+        // Both lists of indices are sorted, walk through them in parallel.
+        let mut diffs = Vec::new();
+        let mut old_iter = old_indices.iter().copied().peekable();
+        let mut new_iter = new_indices.iter().copied().peekable();
+        // The position in the filtered view.
+        let mut index = 0;
+
+        loop {
+            let insert_idx = match (old_iter.peek(), new_iter.peek()) {
+                (Some(old_idx), Some(new_idx)) if old_idx == new_idx => {
+                    // The item is visible before and after.
+                    old_iter.next();
+                    new_iter.next();
+                    index += 1;
+                    continue;
+                }
+                (Some(old_idx), Some(new_idx)) if old_idx > new_idx => *new_idx,
+                (None, Some(new_idx)) => *new_idx,
+                (Some(_), _) => {
+                    // The item was visible, but isn't anymore.
+                    old_iter.next();
+                    diffs.push(VectorDiff::Remove { index });
+                    continue;
+                }
+                (None, None) => break,
+            };
+
+            // The item wasn't visible, but is now.
+            new_iter.next();
+            let value = self.buffered_vector[insert_idx].clone();
+            diffs.push(VectorDiff::Insert { index, value });
+            index += 1;
+        }
+
+        diffs
+    }
+}
+
 pin_project! {
     #[project = FilterImplProj]
     pub(super) struct FilterImpl<S> {
@@ -356,6 +619,146 @@ where
         result
     }
 
+    fn handle_move<U>(&mut self, from: usize, to: usize) -> Option<VectorDiff<U>> {
+        let from_index = self.filtered_indices.partition_point(|&i| i < from);
+        let matched = self.filtered_indices.get(from_index).map_or(false, |&i| i == from);
+        if matched {
+            self.filtered_indices.remove(from_index);
+        }
+
+        // The values between `from` and `to` are shifted towards `from`.
+        for idx in self.filtered_indices.iter_mut() {
+            if from < to && from < *idx && *idx <= to {
+                *idx -= 1;
+            } else if to < from && to <= *idx && *idx < from {
+                *idx += 1;
+            }
+        }
+
+        if matched {
+            // The value that was moved matched the filter
+            let to_index = self.filtered_indices.partition_point(|&i| i < to);
+            self.filtered_indices.insert(to_index, to);
+            (from_index != to_index).then_some(VectorDiff::Move { from: from_index, to: to_index })
+        } else {
+            None
+        }
+    }
+
+    fn handle_swap<U>(&mut self, first: usize, second: usize) -> Option<VectorDiff<U>> {
+        let position = |filtered_indices: &VecDeque<usize>, original_idx| {
+            let index = filtered_indices.partition_point(|&i| i < original_idx);
+            filtered_indices.get(index).map_or(false, |&i| i == original_idx).then_some(index)
+        };
+
+        match (position(self.filtered_indices, first), position(self.filtered_indices, second)) {
+            // Both values matched the filter
+            (Some(first), Some(second)) => Some(VectorDiff::Swap { first, second }),
+            // Only one of the values matched the filter, it moves to the
+            // position of the other value
+            (Some(from_index), None) => self.move_filtered(from_index, second),
+            (None, Some(from_index)) => self.move_filtered(from_index, first),
+            (None, None) => None,
+        }
+    }
+
+    /// Move the matching value at `from_index` in the filtered values to
+    /// `original_idx` in the original values.
+    fn move_filtered<U>(
+        &mut self,
+        from_index: usize,
+        original_idx: usize,
+    ) -> Option<VectorDiff<U>> {
+        self.filtered_indices.remove(from_index);
+        let to_index = self.filtered_indices.partition_point(|&i| i < original_idx);
+        self.filtered_indices.insert(to_index, original_idx);
+        (from_index != to_index).then_some(VectorDiff::Move { from: from_index, to: to_index })
+    }
+
+    fn handle_insert_many<U, F>(
+        &mut self,
+        index: usize,
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        f: &F,
+    ) -> Option<VectorDiff<U>>
+    where
+        U: Clone,
+        F: Fn(VectorDiffContainerStreamElement<S>) -> Option<U>,
+    {
+        let (index, _, values) = self.splice(index, index, values, f);
+        values.is_empty().not().then_some(VectorDiff::InsertMany { index, values })
+    }
+
+    fn handle_remove_range<U>(&mut self, start: usize, end: usize) -> Option<VectorDiff<U>>
+    where
+        U: Clone,
+    {
+        let (start, end, _) =
+            self.splice(start, end, Vector::new(), &|_: VectorDiffContainerStreamElement<S>| {
+                None::<U>
+            });
+        (start < end).then_some(VectorDiff::RemoveRange { start, end })
+    }
+
+    fn handle_splice<U, F>(
+        &mut self,
+        start: usize,
+        end: usize,
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        f: &F,
+    ) -> Option<VectorDiff<U>>
+    where
+        U: Clone,
+        F: Fn(VectorDiffContainerStreamElement<S>) -> Option<U>,
+    {
+        let (start, end, values) = self.splice(start, end, values, f);
+        concat::splice(start, end, values)
+    }
+
+    /// Replace the original values in `start..end` by `values`.
+    ///
+    /// Returns the range of the filtered values that were replaced, and the
+    /// values replacing them.
+    fn splice<U, F>(
+        &mut self,
+        start: usize,
+        end: usize,
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        f: &F,
+    ) -> (usize, usize, Vector<U>)
+    where
+        U: Clone,
+        F: Fn(VectorDiffContainerStreamElement<S>) -> Option<U>,
+    {
+        let removed_len = end - start;
+        let inserted_len = values.len();
+        *self.original_len = *self.original_len - removed_len + inserted_len;
+
+        let filtered_start = self.filtered_indices.partition_point(|&i| i < start);
+        let filtered_end = self.filtered_indices.partition_point(|&i| i < end);
+        self.filtered_indices.drain(filtered_start..filtered_end);
+        for idx in self.filtered_indices.iter_mut().skip(filtered_start) {
+            *idx = *idx - removed_len + inserted_len;
+        }
+
+        let mut original_idx = start;
+        let mut filtered_idx = filtered_start;
+        let mapped_values = values
+            .into_iter()
+            .filter_map(|value| {
+                let result = f(value).map(|mapped| {
+                    self.filtered_indices.insert(filtered_idx, original_idx);
+                    filtered_idx += 1;
+                    mapped
+                });
+                original_idx += 1;
+                result
+            })
+            .collect();
+
+        (filtered_start, filtered_end, mapped_values)
+    }
+
     fn handle_truncate<U>(&mut self, len: usize) -> Option<VectorDiff<U>> {
         *self.original_len = len;
         let new_filtered_len = self.filtered_indices.iter().take_while(|&&idx| idx < len).count();
@@ -375,7 +778,12 @@ where
     {
         self.filtered_indices.clear();
         *self.original_len = 0;
-        self.append_filter(values, f).map(|values| VectorDiff::Reset { values })
+        // If none of the values pass the filter, the view is still reset.
+        let diff = match self.append_filter(values, f) {
+            Some(values) => VectorDiff::Reset { values },
+            None => VectorDiff::Clear,
+        };
+        Some(diff)
     }
 
     fn handle_reset_filter_map<U, F>(
@@ -389,33 +797,24 @@ where
     {
         self.filtered_indices.clear();
         *self.original_len = 0;
-        self.append_filter_map(values, f).map(|values| VectorDiff::Reset { values })
+        // If none of the values pass the filter, the view is still reset.
+        let diff = match self.append_filter_map(values, f) {
+            Some(values) => VectorDiff::Reset { values },
+            None => VectorDiff::Clear,
+        };
+        Some(diff)
     }
 
     fn handle_diff_filter<F>(&mut self, f: &F, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>>
     where
         F: Fn(&VectorDiffContainerStreamElement<S>) -> bool,
     {
-        // Transform filter function into filter_map function.
-        let f2 = |value| f(&value).then_some(value);
         loop {
             let Some(diffs) = ready!(self.inner.as_mut().poll_next(cx)) else {
                 return Poll::Ready(None);
             };
 
-            let result = diffs.filter_map(|diff| match diff {
-                VectorDiff::Append { values } => self.handle_append_filter(values, f),
-                VectorDiff::Clear => self.handle_clear(),
-                VectorDiff::PushFront { value } => self.handle_push_front(value, &f2),
-                VectorDiff::PushBack { value } => self.handle_push_back(value, &f2),
-                VectorDiff::PopFront => self.handle_pop_front(),
-                VectorDiff::PopBack => self.handle_pop_back(),
-                VectorDiff::Insert { index, value } => self.handle_insert(index, value, &f2),
-                VectorDiff::Set { index, value } => self.handle_set(index, value, &f2),
-                VectorDiff::Remove { index } => self.handle_remove(index),
-                VectorDiff::Truncate { length } => self.handle_truncate(length),
-                VectorDiff::Reset { values } => self.handle_reset_filter(values, f),
-            });
+            let result = diffs.filter_map(|diff| self.handle_filter(diff, f));
 
             if let Some(diffs) = result {
                 return Poll::Ready(Some(diffs));
@@ -423,6 +822,38 @@ where
         }
     }
 
+    fn handle_filter<F>(
+        &mut self,
+        diff: VectorDiffContainerDiff<S>,
+        f: &F,
+    ) -> Option<VectorDiffContainerDiff<S>>
+    where
+        F: Fn(&VectorDiffContainerStreamElement<S>) -> bool,
+    {
+        // Transform filter function into filter_map function.
+        let f2 = |value| f(&value).then_some(value);
+        match diff {
+            VectorDiff::Append { values } => self.handle_append_filter(values, f),
+            VectorDiff::Clear => self.handle_clear(),
+            VectorDiff::PushFront { value } => self.handle_push_front(value, &f2),
+            VectorDiff::PushBack { value } => self.handle_push_back(value, &f2),
+            VectorDiff::PopFront => self.handle_pop_front(),
+            VectorDiff::PopBack => self.handle_pop_back(),
+            VectorDiff::Insert { index, value } => self.handle_insert(index, value, &f2),
+            VectorDiff::Set { index, value } => self.handle_set(index, value, &f2),
+            VectorDiff::Remove { index } => self.handle_remove(index),
+            VectorDiff::Truncate { length } => self.handle_truncate(length),
+            VectorDiff::Reset { values } => self.handle_reset_filter(values, f),
+            VectorDiff::Move { from, to } => self.handle_move(from, to),
+            VectorDiff::Swap { first, second } => self.handle_swap(first, second),
+            VectorDiff::InsertMany { index, values } => self.handle_insert_many(index, values, &f2),
+            VectorDiff::RemoveRange { start, end } => self.handle_remove_range(start, end),
+            VectorDiff::Splice { start, end, values } => {
+                self.handle_splice(start, end, values, &f2)
+            }
+        }
+    }
+
     fn handle_diff_filter_map<U, F>(
         &mut self,
         f: &F,
@@ -449,6 +880,15 @@ where
                 VectorDiff::Remove { index } => self.handle_remove(index),
                 VectorDiff::Truncate { length } => self.handle_truncate(length),
                 VectorDiff::Reset { values } => self.handle_reset_filter_map(values, f),
+                VectorDiff::Move { from, to } => self.handle_move(from, to),
+                VectorDiff::Swap { first, second } => self.handle_swap(first, second),
+                VectorDiff::InsertMany { index, values } => {
+                    self.handle_insert_many(index, values, f)
+                }
+                VectorDiff::RemoveRange { start, end } => self.handle_remove_range(start, end),
+                VectorDiff::Splice { start, end, values } => {
+                    self.handle_splice(start, end, values, f)
+                }
             });
 
             if let Some(diffs) = result {
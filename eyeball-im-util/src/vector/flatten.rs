@@ -0,0 +1,457 @@
+use std::{
+    cmp::{max, min},
+    iter::repeat,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use super::{
+    concat::{handle_diff, insert_all, remove_range, splice, ConcatSource},
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    VectorDiffContainerStreamFlattenBuf, VectorDiffContainerStreamMappedItem, VectorObserver,
+};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a flattened view of an
+    /// underlying [`ObservableVector`] whose items are observable vectors
+    /// themselves.
+    ///
+    /// For every item of the outer vector, a [`VectorObserver`] (e.g. a
+    /// [`VectorSubscriber`]) of the inner vector is obtained through the
+    /// `subscribe` function. The flattened vector is the concatenation of
+    /// all of the inner vectors, in the order of the outer vector.
+    ///
+    /// Changes to the outer vector are translated into the insertion or
+    /// removal of the whole range of items of the affected inner vectors, and
+    /// changes to an inner vector are shifted by the lengths of the preceding
+    /// inner vectors, like with [`Concat`](super::Concat).
+    ///
+    /// The stream ends once the stream of the outer vector and the streams of
+    /// all of the inner vectors have ended.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_next_eq, assert_pending};
+    ///
+    /// type Section = Arc<Mutex<ObservableVector<&'static str>>>;
+    ///
+    /// let fruits: Section = Arc::new(Mutex::new(ObservableVector::from(vector!["apple"])));
+    /// let vegetables: Section = Arc::new(Mutex::new(ObservableVector::from(vector!["leek"])));
+    ///
+    /// let mut sections = ObservableVector::from(vector![fruits.clone()]);
+    /// let (values, mut sub) =
+    ///     sections.subscribe().flatten(|section: &Section| section.lock().unwrap().subscribe());
+    ///
+    /// assert_eq!(values, vector!["apple"]);
+    ///
+    /// sections.push_back(vegetables.clone());
+    /// assert_next_eq!(sub, VectorDiff::Append { values: vector!["leek"] });
+    ///
+    /// fruits.lock().unwrap().push_back("pear");
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: "pear" });
+    ///
+    /// // | `fruits`     | apple pear      |
+    /// // | `vegetables` |            leek |
+    /// // | `sub`        | apple pear leek |
+    ///
+    /// sections.remove(0);
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    /// assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    ///
+    /// assert_pending!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    /// [`VectorSubscriber`]: eyeball_im::VectorSubscriber
+    #[project = FlattenProj]
+    pub struct Flatten<S, I, F>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+        I: Stream,
+        I::Item: VectorDiffContainer,
+    {
+        // The stream of the outer vector.
+        #[pin]
+        outer_stream: S,
+
+        // Whether `outer_stream` has ended.
+        outer_ended: bool,
+
+        // The function to obtain an observer of an inner vector.
+        subscribe: F,
+
+        // The inner vectors, in the order of the outer vector.
+        sections: Vec<ConcatSource<I>>,
+
+        // This adapter can produce many items per item of the underlying
+        // streams.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-batched, can't
+        // just add diffs to a `poll_next` result), we need a buffer to store
+        // the possible extra items in.
+        ready_values: VectorDiffContainerStreamFlattenBuf<S, I>,
+    }
+}
+
+impl<S, I, F, O> Flatten<S, I, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    I: Stream + Unpin,
+    I::Item: VectorDiffContainer,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> O,
+    O: VectorObserver<VectorDiffContainerStreamElement<I>, Stream = I>,
+{
+    /// Create a new `Flatten` with the given initial values of the outer
+    /// vector, stream of `VectorDiff` updates for those values, and function
+    /// to obtain an observer of the inner vector of each value.
+    ///
+    /// Returns the concatenation of the inner vectors' values as well as a
+    /// stream of updates for it.
+    pub fn new(
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        outer_stream: S,
+        subscribe: F,
+    ) -> (Vector<VectorDiffContainerStreamElement<I>>, Self) {
+        let mut flattened = Vector::new();
+        let sections =
+            values.iter().map(|value| new_section(&subscribe, value, &mut flattened)).collect();
+
+        let stream = Self {
+            outer_stream,
+            outer_ended: false,
+            subscribe,
+            sections,
+            ready_values: Default::default(),
+        };
+        (flattened, stream)
+    }
+}
+
+impl<S, I, F, O> Stream for Flatten<S, I, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    I: Stream + Unpin,
+    I::Item: VectorDiffContainer,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> O,
+    O: VectorObserver<VectorDiffContainerStreamElement<I>, Stream = I>,
+{
+    type Item = VectorDiffContainerStreamMappedItem<S, VectorDiffContainerStreamElement<I>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, I, F, O> FlattenProj<'_, S, I, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    I: Stream + Unpin,
+    I::Item: VectorDiffContainer,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> O,
+    O: VectorObserver<VectorDiffContainerStreamElement<I>, Stream = I>,
+{
+    fn poll_next(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Option<VectorDiffContainerStreamMappedItem<S, VectorDiffContainerStreamElement<I>>>>
+    {
+        type Item<S, I> =
+            VectorDiffContainerStreamMappedItem<S, VectorDiffContainerStreamElement<I>>;
+
+        'poll: loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = Item::<S, I>::pop_from_flatten_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll changes to the outer vector first, such that the inner
+            // vectors' changes are applied to an up-to-date list of sections.
+            if !*self.outer_ended {
+                match self.outer_stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(outer_diffs)) => {
+                        let mut diffs = Vec::new();
+                        outer_diffs.for_each(|diff| {
+                            handle_outer_diff(diff, &*self.subscribe, self.sections, &mut diffs);
+                        });
+
+                        if let Some(diff) =
+                            Item::<S, I>::extend_flatten_buf(diffs, self.ready_values)
+                        {
+                            return Poll::Ready(Some(diff));
+                        }
+
+                        // Poll the streams again.
+                        continue 'poll;
+                    }
+                    Poll::Ready(None) => {
+                        *self.outer_ended = true;
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            let mut all_ended = *self.outer_ended;
+            for section_idx in 0..self.sections.len() {
+                let Some(stream) = &mut self.sections[section_idx].stream else {
+                    continue;
+                };
+
+                match Pin::new(stream).poll_next(cx) {
+                    Poll::Ready(Some(inner_diffs)) => {
+                        let mut diffs = Vec::new();
+                        inner_diffs.for_each(|diff| {
+                            diffs.extend(handle_diff(diff, self.sections, section_idx));
+                        });
+
+                        if let Some(diff) =
+                            Item::<S, I>::extend_flatten_buf(diffs, self.ready_values)
+                        {
+                            return Poll::Ready(Some(diff));
+                        }
+
+                        // Poll the streams again.
+                        continue 'poll;
+                    }
+                    Poll::Ready(None) => {
+                        self.sections[section_idx].stream = None;
+                    }
+                    Poll::Pending => {
+                        all_ended = false;
+                    }
+                }
+            }
+
+            return if all_ended { Poll::Ready(None) } else { Poll::Pending };
+        }
+    }
+}
+
+/// Obtain an observer of the inner vector of `value`, append its values to
+/// `values` and return the section for it.
+fn new_section<T, U, I, F, O>(subscribe: &F, value: &T, values: &mut Vector<U>) -> ConcatSource<I>
+where
+    U: Clone,
+    F: Fn(&T) -> O,
+    O: VectorObserver<U, Stream = I>,
+{
+    let (section_values, stream) = subscribe(value).into_parts();
+    let len = section_values.len();
+    values.append(section_values);
+    ConcatSource { stream: Some(stream), len }
+}
+
+/// Apply a `VectorDiff` of the outer vector to `sections`, and push the
+/// resulting `VectorDiff`s of the flattened vector to `diffs`.
+fn handle_outer_diff<T, U, I, F, O>(
+    diff: VectorDiff<T>,
+    subscribe: &F,
+    sections: &mut Vec<ConcatSource<I>>,
+    diffs: &mut Vec<VectorDiff<U>>,
+) where
+    U: Clone,
+    F: Fn(&T) -> O,
+    O: VectorObserver<U, Stream = I>,
+{
+    match diff {
+        VectorDiff::Append { values } => {
+            let mut new_values = Vector::new();
+            sections
+                .extend(values.iter().map(|value| new_section(subscribe, value, &mut new_values)));
+
+            if !new_values.is_empty() {
+                diffs.push(VectorDiff::Append { values: new_values });
+            }
+        }
+        VectorDiff::Clear => {
+            let len = total_len(sections);
+            sections.clear();
+
+            if len > 0 {
+                diffs.push(VectorDiff::Clear);
+            }
+        }
+        VectorDiff::PushFront { value } => {
+            insert_section(0, &value, subscribe, sections, diffs);
+        }
+        VectorDiff::PushBack { value } => {
+            insert_section(sections.len(), &value, subscribe, sections, diffs);
+        }
+        VectorDiff::PopFront => {
+            remove_section(0, sections, diffs);
+        }
+        VectorDiff::PopBack => {
+            remove_section(sections.len() - 1, sections, diffs);
+        }
+        VectorDiff::Insert { index, value } => {
+            insert_section(index, &value, subscribe, sections, diffs);
+        }
+        VectorDiff::Set { index, value } => {
+            remove_section(index, sections, diffs);
+            insert_section(index, &value, subscribe, sections, diffs);
+        }
+        VectorDiff::Remove { index } => {
+            remove_section(index, sections, diffs);
+        }
+        VectorDiff::Truncate { length } => {
+            let prev_len = total_len(sections);
+            sections.truncate(length);
+            let len = total_len(sections);
+
+            if len < prev_len {
+                diffs.push(if len == 0 {
+                    VectorDiff::Clear
+                } else {
+                    VectorDiff::Truncate { length: len }
+                });
+            }
+        }
+        VectorDiff::Reset { values } => {
+            let mut new_values = Vector::new();
+            *sections =
+                values.iter().map(|value| new_section(subscribe, value, &mut new_values)).collect();
+            diffs.push(VectorDiff::Reset { values: new_values });
+        }
+        VectorDiff::Move { from, to } => {
+            move_section(from, to, sections, diffs);
+        }
+        VectorDiff::Swap { first, second } => {
+            // Swapping is equivalent to two moves.
+            let (first, second) = (min(first, second), max(first, second));
+            move_section(first, second, sections, diffs);
+            move_section(second - 1, first, sections, diffs);
+        }
+        VectorDiff::InsertMany { index, values } => {
+            let offset = total_len(&sections[..index]);
+            let is_last = offset == total_len(sections);
+
+            let mut new_values = Vector::new();
+            let new_sections: Vec<_> =
+                values.iter().map(|value| new_section(subscribe, value, &mut new_values)).collect();
+            sections.splice(index..index, new_sections);
+
+            if new_values.is_empty() {
+                // No values were inserted.
+            } else if is_last {
+                diffs.push(VectorDiff::Append { values: new_values });
+            } else {
+                diffs.push(VectorDiff::InsertMany { index: offset, values: new_values });
+            }
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            let offset = total_len(&sections[..start]);
+            let len = total_len(&sections[start..end]);
+            let is_last = total_len(&sections[end..]) == 0;
+            sections.drain(start..end);
+
+            if len == 0 {
+                // No values were removed.
+            } else if offset == 0 && is_last {
+                diffs.push(VectorDiff::Clear);
+            } else if is_last {
+                diffs.push(VectorDiff::Truncate { length: offset });
+            } else {
+                diffs.push(VectorDiff::RemoveRange { start: offset, end: offset + len });
+            }
+        }
+        VectorDiff::Splice { start, end, values } => {
+            let offset = total_len(&sections[..start]);
+            let len = total_len(&sections[start..end]);
+
+            let mut new_values = Vector::new();
+            let new_sections: Vec<_> =
+                values.iter().map(|value| new_section(subscribe, value, &mut new_values)).collect();
+            sections.splice(start..end, new_sections);
+
+            diffs.extend(splice(offset, offset + len, new_values));
+        }
+    }
+}
+
+/// Insert a new section for `value` at `index`.
+fn insert_section<T, U, I, F, O>(
+    index: usize,
+    value: &T,
+    subscribe: &F,
+    sections: &mut Vec<ConcatSource<I>>,
+    diffs: &mut Vec<VectorDiff<U>>,
+) where
+    U: Clone,
+    F: Fn(&T) -> O,
+    O: VectorObserver<U, Stream = I>,
+{
+    let offset = total_len(&sections[..index]);
+    let is_last = offset == total_len(sections);
+
+    let mut values = Vector::new();
+    sections.insert(index, new_section(subscribe, value, &mut values));
+
+    if values.is_empty() {
+        return;
+    }
+
+    if is_last {
+        diffs.push(VectorDiff::Append { values });
+    } else {
+        diffs.extend(insert_all(offset, values));
+    }
+}
+
+/// Remove the section at `index`.
+fn remove_section<U: Clone, I>(
+    index: usize,
+    sections: &mut Vec<ConcatSource<I>>,
+    diffs: &mut Vec<VectorDiff<U>>,
+) {
+    let offset = total_len(&sections[..index]);
+    let is_last = total_len(&sections[index + 1..]) == 0;
+    let section = sections.remove(index);
+
+    if offset == 0 && is_last && section.len > 0 {
+        diffs.push(VectorDiff::Clear);
+    } else {
+        diffs.extend(remove_range(offset, section.len, is_last));
+    }
+}
+
+/// Move the section at `from` to `to`, moving its values one by one.
+fn move_section<U: Clone, I>(
+    from: usize,
+    to: usize,
+    sections: &mut Vec<ConcatSource<I>>,
+    diffs: &mut Vec<VectorDiff<U>>,
+) {
+    let from_offset = total_len(&sections[..from]);
+    let section = sections.remove(from);
+    let len = section.len;
+    let to_offset = total_len(&sections[..to]);
+    sections.insert(to, section);
+
+    if from_offset < to_offset {
+        // Move the first value of the section to the end of its new range,
+        // as many times as there are values.
+        let to = to_offset + len - 1;
+        diffs.extend(repeat(VectorDiff::Move { from: from_offset, to }).take(len));
+    } else if to_offset < from_offset {
+        diffs.extend(
+            (0..len).map(|i| VectorDiff::Move { from: from_offset + i, to: to_offset + i }),
+        );
+    }
+}
+
+fn total_len<I>(sections: &[ConcatSource<I>]) -> usize {
+    sections.iter().map(|section| section.len).sum()
+}
@@ -0,0 +1,579 @@
+use std::{
+    collections::VecDeque,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{self, ready, Poll},
+};
+
+use super::{
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    VectorDiffContainerStreamGroupByBuf, VectorDiffContainerStreamMappedItem,
+};
+use eyeball_im::{ObservableVector, VectorDiff, VectorSubscriber};
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+/// A group of the values of a [`GroupBy`] adapter that have the same key.
+///
+/// The values of the group are kept in the order of the underlying vector. A
+/// `Group` is a cheap handle to them; all clones of a `Group` share the same
+/// values, which are updated by the `GroupBy` stream as it is polled.
+#[derive(Clone, Debug)]
+pub struct Group<K, T> {
+    key: K,
+    values: Arc<Mutex<ObservableVector<T>>>,
+}
+
+impl<K, T: Clone + 'static> Group<K, T> {
+    fn new(key: K, values: Vector<T>) -> Self {
+        Self { key, values: Arc::new(Mutex::new(values.into())) }
+    }
+
+    /// The key that all of the values of this group have.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Get a clone of the current values of this group.
+    pub fn values(&self) -> Vector<T> {
+        Vector::clone(&self.lock())
+    }
+
+    /// Obtain a new subscriber for the values of this group.
+    ///
+    /// This can be used with [`VectorObserverExt::flatten`] to turn the
+    /// groups back into a single vector.
+    ///
+    /// [`VectorObserverExt::flatten`]: super::VectorObserverExt::flatten
+    pub fn subscribe(&self) -> VectorSubscriber<T> {
+        self.lock().subscribe()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ObservableVector<T>> {
+        self.values.lock().unwrap()
+    }
+}
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents the underlying
+    /// [`ObservableVector`]s items grouped by a key.
+    ///
+    /// The adapter produces a vector of [`Group`]s, one per distinct key, in
+    /// ascending order of their keys. Every group holds an observable vector
+    /// of the underlying values with its key, in their original order.
+    ///
+    /// A new group is inserted when a value with a new key is added, and a
+    /// group is removed once its last value is removed or gets a different
+    /// key. All other changes are applied to the values of the affected
+    /// groups, and can be observed through [`Group::subscribe`].
+    ///
+    /// Like [`Filter`](super::Filter), the adapter keeps track of the original
+    /// indices of the values of each group, so updates to the underlying
+    /// vector can be mapped to updates of the groups.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use futures_util::{FutureExt, StreamExt};
+    /// use imbl::vector;
+    /// use stream_assert::assert_pending;
+    ///
+    /// let mut ob = ObservableVector::<&str>::from(vector!["apple", "banana", "avocado"]);
+    /// let (groups, mut sub) = ob.subscribe().group_by(|fruit| fruit.chars().next().unwrap());
+    ///
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(*groups[0].key(), 'a');
+    /// assert_eq!(groups[0].values(), vector!["apple", "avocado"]);
+    /// assert_eq!(*groups[1].key(), 'b');
+    /// assert_eq!(groups[1].values(), vector!["banana"]);
+    ///
+    /// let mut a_sub = groups[0].subscribe().into_stream();
+    ///
+    /// // A value with a new key adds a new group.
+    /// ob.push_back("cherry");
+    /// let diff = sub.next().now_or_never().unwrap().unwrap();
+    /// let diff = diff.map(|group| (*group.key(), group.values()));
+    /// assert_eq!(diff, VectorDiff::Insert { index: 2, value: ('c', vector!["cherry"]) });
+    ///
+    /// // A value with an existing key is added to its group.
+    /// ob.insert(0, "apricot");
+    /// assert_pending!(sub);
+    /// let diff = a_sub.next().now_or_never().unwrap().unwrap();
+    /// assert_eq!(diff, VectorDiff::PushFront { value: "apricot" });
+    ///
+    /// // Removing the last value of a group removes the group.
+    /// ob.remove(2);
+    /// let diff = sub.next().now_or_never().unwrap().unwrap();
+    /// assert_eq!(diff.map(|group| *group.key()), VectorDiff::Remove { index: 1 });
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = GroupByProj]
+    pub struct GroupBy<S, K, F>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+    {
+        #[pin]
+        inner_stream: S,
+
+        // The function to get the key of a value.
+        key_fn: F,
+
+        // The groups, in ascending order of their keys.
+        groups: Vec<GroupState<K, VectorDiffContainerStreamElement<S>>>,
+
+        // The key of every value of the original vector, by original index.
+        //
+        // Used to find the group of a value without searching all groups.
+        keys: Vector<K>,
+
+        // This adapter can produce many items per item of the underlying
+        // stream.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-batched, can't
+        // just add diffs to a `poll_next` result), we need a buffer to store
+        // the possible extra items in.
+        ready_values: VectorDiffContainerStreamGroupByBuf<S, K>,
+    }
+}
+
+/// Type alias for the groups of a [`GroupBy`] of the stream `S`.
+type StreamGroup<S, K> = Group<K, VectorDiffContainerStreamElement<S>>;
+
+/// Type alias for the stream items of a [`GroupBy`] of the stream `S`.
+type GroupByItem<S, K> = VectorDiffContainerStreamMappedItem<S, StreamGroup<S, K>>;
+
+struct GroupState<K, T> {
+    group: Group<K, T>,
+
+    // Original indices of the values of the group, in ascending order.
+    indices: VecDeque<usize>,
+}
+
+impl<S, K, F> GroupBy<S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Ord + Clone,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    /// Create a new `GroupBy` with the given initial values, stream of
+    /// `VectorDiff` updates for those values, and key function.
+    ///
+    /// Returns the initial groups as well as a stream of updates for them.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        key_fn: F,
+    ) -> (Vector<StreamGroup<S, K>>, Self) {
+        let (keys, groups) = group_values(initial_values, &key_fn);
+        let groups: Vec<_> = groups
+            .into_iter()
+            .map(|(key, indices, values)| GroupState { group: Group::new(key, values), indices })
+            .collect();
+        let initial_groups = groups.iter().map(|state| state.group.clone()).collect();
+
+        let stream = Self { inner_stream, key_fn, groups, keys, ready_values: Default::default() };
+        (initial_groups, stream)
+    }
+}
+
+impl<S, K, F> Stream for GroupBy<S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Ord + Clone,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    type Item = GroupByItem<S, K>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, K, F> GroupByProj<'_, S, K, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    K: Ord + Clone,
+    F: Fn(&VectorDiffContainerStreamElement<S>) -> K,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<GroupByItem<S, K>>> {
+        loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = GroupByItem::<S, K>::pop_from_group_by_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll `VectorDiff`s from the `inner_stream`.
+            let Some(diffs) = ready!(self.inner_stream.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Consume and apply the diffs if possible.
+            let mut group_diffs = Vec::new();
+            diffs.for_each(|diff| self.handle_diff(diff, &mut group_diffs));
+
+            if let Some(diff) =
+                GroupByItem::<S, K>::extend_group_by_buf(group_diffs, self.ready_values)
+            {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Else loop and poll the streams again.
+        }
+    }
+
+    /// Apply a `VectorDiff` of the underlying vector to the groups, and push
+    /// the resulting `VectorDiff`s of the vector of groups to `diffs`.
+    fn handle_diff(
+        &mut self,
+        diff: VectorDiff<VectorDiffContainerStreamElement<S>>,
+        diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>,
+    ) {
+        match diff {
+            VectorDiff::Append { values } => {
+                for value in values {
+                    self.insert(self.keys.len(), value, diffs);
+                }
+            }
+            VectorDiff::Clear => {
+                self.keys.clear();
+                if !self.groups.is_empty() {
+                    for state in self.groups.drain(..) {
+                        state.group.lock().clear();
+                    }
+                    diffs.push(VectorDiff::Clear);
+                }
+            }
+            VectorDiff::PushFront { value } => {
+                self.insert(0, value, diffs);
+            }
+            VectorDiff::PushBack { value } => {
+                self.insert(self.keys.len(), value, diffs);
+            }
+            VectorDiff::PopFront => {
+                self.remove(0, diffs);
+            }
+            VectorDiff::PopBack => {
+                self.remove(self.keys.len() - 1, diffs);
+            }
+            VectorDiff::Insert { index, value } => {
+                self.insert(index, value, diffs);
+            }
+            VectorDiff::Set { index, value } => {
+                self.set(index, value, diffs);
+            }
+            VectorDiff::Remove { index } => {
+                self.remove(index, diffs);
+            }
+            VectorDiff::Truncate { length } => {
+                self.truncate(length, diffs);
+            }
+            VectorDiff::Reset { values } => {
+                self.reset(values, diffs);
+            }
+            VectorDiff::Move { from, to } => {
+                self.move_value(from, to);
+            }
+            VectorDiff::Swap { first, second } => {
+                self.swap(first, second);
+            }
+            VectorDiff::InsertMany { index, values } => {
+                for (offset, value) in values.into_iter().enumerate() {
+                    self.insert(index + offset, value, diffs);
+                }
+            }
+            VectorDiff::RemoveRange { start, end } => {
+                for index in (start..end).rev() {
+                    self.remove(index, diffs);
+                }
+            }
+            VectorDiff::Splice { start, end, values } => {
+                for index in (start..end).rev() {
+                    self.remove(index, diffs);
+                }
+                for (offset, value) in values.into_iter().enumerate() {
+                    self.insert(start + offset, value, diffs);
+                }
+            }
+        }
+    }
+
+    /// Move the value at the original index `from` to the original index
+    /// `to`.
+    ///
+    /// The groups don't change, only the position of the value within its
+    /// group can.
+    fn move_value(&mut self, from: usize, to: usize) {
+        let (group_idx, pos) = self.position(from);
+        let key = self.keys.remove(from);
+        self.keys.insert(to, key);
+
+        for state in &mut *self.groups {
+            for idx in state.indices.iter_mut() {
+                if from < to && from < *idx && *idx <= to {
+                    *idx -= 1;
+                } else if to < from && to <= *idx && *idx < from {
+                    *idx += 1;
+                }
+            }
+        }
+
+        self.move_in_group(group_idx, pos, to);
+    }
+
+    /// Swap the values at the original indices `first` and `second`.
+    ///
+    /// The groups don't change, only the position of the values within their
+    /// groups can.
+    fn swap(&mut self, first: usize, second: usize) {
+        let (first_group_idx, first_pos) = self.position(first);
+        let (second_group_idx, second_pos) = self.position(second);
+        self.keys.swap(first, second);
+
+        if first_group_idx == second_group_idx {
+            // The original indices of the group stay the same.
+            self.groups[first_group_idx].group.lock().swap(first_pos, second_pos);
+        } else {
+            self.move_in_group(first_group_idx, first_pos, second);
+            self.move_in_group(second_group_idx, second_pos, first);
+        }
+    }
+
+    /// Move the value at `pos` in the group at `group_idx` to the position of
+    /// the original index `index` within the group.
+    fn move_in_group(&mut self, group_idx: usize, pos: usize, index: usize) {
+        let state = &mut self.groups[group_idx];
+        state.indices.remove(pos);
+        let new_pos = state.indices.partition_point(|&i| i < index);
+        state.indices.insert(new_pos, index);
+        state.group.lock().move_item(pos, new_pos);
+    }
+
+    /// Insert `value` at the original index `index`.
+    fn insert(
+        &mut self,
+        index: usize,
+        value: VectorDiffContainerStreamElement<S>,
+        diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>,
+    ) {
+        for state in &mut *self.groups {
+            let pos = state.indices.partition_point(|&i| i < index);
+            for idx in state.indices.iter_mut().skip(pos) {
+                *idx += 1;
+            }
+        }
+
+        let key = (self.key_fn)(&value);
+        self.keys.insert(index, key.clone());
+        self.add(index, key, value, diffs);
+    }
+
+    /// Remove the value at the original index `index`.
+    fn remove(&mut self, index: usize, diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>) {
+        self.take(index, diffs);
+
+        self.keys.remove(index);
+        for state in &mut *self.groups {
+            let pos = state.indices.partition_point(|&i| i < index);
+            for idx in state.indices.iter_mut().skip(pos) {
+                *idx -= 1;
+            }
+        }
+    }
+
+    /// Replace the value at the original index `index` by `value`.
+    fn set(
+        &mut self,
+        index: usize,
+        value: VectorDiffContainerStreamElement<S>,
+        diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>,
+    ) {
+        let key = (self.key_fn)(&value);
+        if self.keys[index] == key {
+            // The value stays in the same group.
+            let (group_idx, pos) = self.position(index);
+            self.groups[group_idx].group.lock().set(pos, value);
+        } else {
+            self.take(index, diffs);
+            self.keys.set(index, key.clone());
+            self.add(index, key, value, diffs);
+        }
+    }
+
+    /// Remove all values from the original index `length` onwards.
+    fn truncate(&mut self, length: usize, diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>) {
+        self.keys.truncate(length);
+
+        let mut removed = Vec::new();
+        for group_idx in (0..self.groups.len()).rev() {
+            let state = &mut self.groups[group_idx];
+            let new_len = state.indices.partition_point(|&i| i < length);
+            if new_len == state.indices.len() {
+                continue;
+            }
+
+            state.indices.truncate(new_len);
+            state.group.lock().truncate(new_len);
+            if new_len == 0 {
+                self.groups.remove(group_idx);
+                removed.push(VectorDiff::Remove { index: group_idx });
+            }
+        }
+
+        if self.groups.is_empty() && !removed.is_empty() {
+            diffs.push(VectorDiff::Clear);
+        } else {
+            diffs.extend(removed);
+        }
+    }
+
+    /// Replace all values by `values`.
+    ///
+    /// Groups with a key that is still in use are kept, with their values
+    /// replaced.
+    fn reset(
+        &mut self,
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>,
+    ) {
+        let (keys, groups) = group_values(values, &*self.key_fn);
+        *self.keys = keys;
+        let mut old_groups = mem::take(self.groups).into_iter().peekable();
+
+        for (key, indices, values) in groups {
+            // Groups that come before `key` are no longer in use.
+            while let Some(state) = old_groups.next_if(|state| state.group.key < key) {
+                state.group.lock().clear();
+            }
+
+            let group = match old_groups.next_if(|state| state.group.key == key) {
+                Some(state) => {
+                    let mut vector = state.group.lock();
+                    let mut txn = vector.transaction();
+                    txn.clear();
+                    txn.append(values);
+                    txn.commit();
+                    drop(vector);
+                    state.group
+                }
+                None => Group::new(key, values),
+            };
+            self.groups.push(GroupState { group, indices });
+        }
+
+        for state in old_groups {
+            state.group.lock().clear();
+        }
+
+        let groups = self.groups.iter().map(|state| state.group.clone()).collect();
+        diffs.push(VectorDiff::Reset { values: groups });
+    }
+
+    /// Add `value` with the given `key` at the original index `index` to its
+    /// group, without updating the other indices or keys.
+    fn add(
+        &mut self,
+        index: usize,
+        key: K,
+        value: VectorDiffContainerStreamElement<S>,
+        diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>,
+    ) {
+        match self.groups.binary_search_by(|state| state.group.key.cmp(&key)) {
+            Ok(group_idx) => {
+                let state = &mut self.groups[group_idx];
+                let pos = state.indices.partition_point(|&i| i < index);
+                let mut vector = state.group.lock();
+                if pos == 0 {
+                    vector.push_front(value);
+                } else if pos == state.indices.len() {
+                    vector.push_back(value);
+                } else {
+                    vector.insert(pos, value);
+                }
+                drop(vector);
+                state.indices.insert(pos, index);
+            }
+            Err(group_idx) => {
+                let group = Group::new(key, Vector::unit(value));
+                let indices = VecDeque::from([index]);
+                self.groups.insert(group_idx, GroupState { group: group.clone(), indices });
+                diffs.push(VectorDiff::Insert { index: group_idx, value: group });
+            }
+        }
+    }
+
+    /// Take the value at the original index `index` out of its group, without
+    /// updating the other indices or keys.
+    fn take(&mut self, index: usize, diffs: &mut Vec<VectorDiff<StreamGroup<S, K>>>) {
+        let (group_idx, pos) = self.position(index);
+        let state = &mut self.groups[group_idx];
+
+        let mut vector = state.group.lock();
+        if pos == 0 {
+            vector.pop_front();
+        } else if pos == state.indices.len() - 1 {
+            vector.pop_back();
+        } else {
+            vector.remove(pos);
+        }
+        drop(vector);
+        state.indices.remove(pos);
+        if state.indices.is_empty() {
+            self.groups.remove(group_idx);
+            diffs.push(VectorDiff::Remove { index: group_idx });
+        }
+    }
+
+    /// Find the index of the group of the value at the original index `index`,
+    /// and the value's position within that group.
+    fn position(&self, index: usize) -> (usize, usize) {
+        let key = &self.keys[index];
+        let group_idx = self
+            .groups
+            .binary_search_by(|state| state.group.key.cmp(key))
+            .expect("every original key has a group");
+        let pos = self.groups[group_idx].indices.partition_point(|&i| i < index);
+        (group_idx, pos)
+    }
+}
+
+/// Group `values` by their key.
+///
+/// Returns the key of every value by original index, and the key, original
+/// indices and values of every group in ascending order of the keys.
+fn group_values<T, K, F>(values: Vector<T>, key_fn: &F) -> (Vector<K>, Vec<ValuesGroup<K, T>>)
+where
+    T: Clone,
+    K: Ord + Clone,
+    F: Fn(&T) -> K,
+{
+    let mut keyed: Vec<_> =
+        values.into_iter().enumerate().map(|(idx, value)| (key_fn(&value), idx, value)).collect();
+    let keys = keyed.iter().map(|(key, _, _)| key.clone()).collect();
+    // The sort is stable, so values with the same key keep their order.
+    keyed.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+    let mut groups: Vec<ValuesGroup<K, T>> = Vec::new();
+    for (key, idx, value) in keyed {
+        match groups.last_mut() {
+            Some((last_key, indices, values)) if *last_key == key => {
+                indices.push_back(idx);
+                values.push_back(value);
+            }
+            _ => groups.push((key, VecDeque::from([idx]), Vector::unit(value))),
+        }
+    }
+
+    (keys, groups)
+}
+
+/// The key, original indices and values of a group built by [`group_values`].
+type ValuesGroup<K, T> = (K, VecDeque<usize>, Vector<T>);
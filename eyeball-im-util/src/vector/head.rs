@@ -7,7 +7,7 @@ use std::{
 };
 
 use super::{
-    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    concat::splice, VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
     VectorDiffContainerStreamHeadBuf, VectorObserver,
 };
 use eyeball_im::VectorDiff;
@@ -335,7 +335,7 @@ impl Stream for EmptyLimitStream {
     }
 }
 
-fn handle_diff<T: Clone>(
+pub(super) fn handle_diff<T: Clone>(
     diff: VectorDiff<T>,
     limit: usize,
     prev_len: usize,
@@ -442,6 +442,113 @@ fn handle_diff<T: Clone>(
             // There is space for these new items.
             res.push(VectorDiff::Reset { values: new_values });
         }
+        VectorDiff::Move { from, to } => {
+            match (from < limit, to < limit) {
+                // Move inside `limit`.
+                (true, true) => {
+                    res.push(VectorDiff::Move { from, to });
+                }
+                // The item leaves the view, and a previously-truncated item
+                // enters it.
+                (true, false) => {
+                    res.push(VectorDiff::Remove { index: from });
+                    res.push(VectorDiff::PushBack { value: buffered_vector[limit - 1].clone() });
+                }
+                // The item enters the view, and the last item leaves it.
+                (false, true) => {
+                    res.push(VectorDiff::PopBack);
+                    res.push(VectorDiff::Insert { index: to, value: buffered_vector[to].clone() });
+                }
+                // Move after `limit`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+        VectorDiff::Swap { first, second } => {
+            match (first < limit, second < limit) {
+                // Swap inside `limit`.
+                (true, true) => {
+                    res.push(VectorDiff::Swap { first, second });
+                }
+                // Only one of the items is inside `limit`, it's replaced.
+                (true, false) => {
+                    res.push(VectorDiff::Set {
+                        index: first,
+                        value: buffered_vector[first].clone(),
+                    });
+                }
+                (false, true) => {
+                    res.push(VectorDiff::Set {
+                        index: second,
+                        value: buffered_vector[second].clone(),
+                    });
+                }
+                // Swap after `limit`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+        VectorDiff::InsertMany { index, mut values } => {
+            if index >= limit {
+                // Insert after `limit`, ignore the diff.
+            } else {
+                // Truncate the `values` to fit inside the view.
+                values.truncate(limit - index);
+
+                let visible_len = min(prev_len, limit);
+                if visible_len + values.len() > limit {
+                    // Create enough free space.
+                    res.push(VectorDiff::Truncate { length: limit - values.len() });
+                }
+
+                res.push(VectorDiff::InsertMany { index, values });
+            }
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            if start >= limit {
+                // Remove after `limit`, ignore the diff.
+            } else {
+                let end = min(end, limit);
+                res.push(VectorDiff::RemoveRange { start, end });
+
+                // Previously-truncated items enter the view, push them back.
+                let remaining_len = min(prev_len, limit) - (end - start);
+                let new_len = min(buffered_vector.len(), limit);
+                if remaining_len < new_len {
+                    let values = buffered_vector
+                        .iter()
+                        .skip(remaining_len)
+                        .take(new_len - remaining_len)
+                        .cloned()
+                        .collect();
+                    res.push(VectorDiff::Append { values });
+                }
+            }
+        }
+        VectorDiff::Splice { start, end, mut values } => {
+            if start >= limit {
+                // Splice after `limit`, ignore the diff.
+            } else {
+                let end = min(end, limit);
+                values.truncate(limit - start);
+
+                let spliced_len = min(prev_len, limit) - (end - start) + values.len();
+                res.extend(splice(start, end, values));
+
+                let new_len = min(buffered_vector.len(), limit);
+                if spliced_len > new_len {
+                    // Too many items in the view, truncate.
+                    res.push(VectorDiff::Truncate { length: new_len });
+                } else if spliced_len < new_len {
+                    // Previously-truncated items enter the view, push them back.
+                    let values = buffered_vector
+                        .iter()
+                        .skip(spliced_len)
+                        .take(new_len - spliced_len)
+                        .cloned()
+                        .collect();
+                    res.push(VectorDiff::Append { values });
+                }
+            }
+        }
     }
 
     res
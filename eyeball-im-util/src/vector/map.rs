@@ -0,0 +1,99 @@
+use std::{
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use eyeball_im::Vector;
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use super::{
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    VectorDiffContainerStreamMappedItem,
+};
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a mapped view of the
+    /// underlying [`ObservableVector`]s items.
+    ///
+    /// Unlike [`FilterMap`](super::FilterMap), no bookkeeping is needed since
+    /// every item is kept, so every `VectorDiff` is simply mapped with
+    /// [`VectorDiff::map`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+    ///
+    /// let mut ob = ObservableVector::<u32>::from(vector![1, 2]);
+    /// let (values, mut sub) = ob.subscribe().map(|i| i * 10);
+    ///
+    /// assert_eq!(values, vector![10, 20]);
+    /// assert_pending!(sub);
+    ///
+    /// ob.push_back(3);
+    /// assert_next_eq!(sub, VectorDiff::PushBack { value: 30 });
+    ///
+    /// ob.set(0, 4);
+    /// assert_next_eq!(sub, VectorDiff::Set { index: 0, value: 40 });
+    ///
+    /// assert_pending!(sub);
+    /// drop(ob);
+    /// assert_closed!(sub);
+    /// ```
+    ///
+    /// [`VectorDiff`]: eyeball_im::VectorDiff
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    pub struct Map<S, F> {
+        #[pin]
+        inner: S,
+        f: F,
+    }
+}
+
+impl<S, U, F> Map<S, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    U: Clone,
+    F: Fn(VectorDiffContainerStreamElement<S>) -> U,
+{
+    /// Create a new `Map` with the given (unmapped) initial values, stream of
+    /// `VectorDiff` updates for those values, and mapping function.
+    pub fn new(
+        values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner: S,
+        f: F,
+    ) -> (Vector<U>, Self) {
+        let values = values.into_iter().map(&f).collect();
+        (values, Self { inner, f })
+    }
+}
+
+impl<S, U, F> Stream for Map<S, F>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    U: Clone,
+    F: Fn(VectorDiffContainerStreamElement<S>) -> U,
+{
+    type Item = VectorDiffContainerStreamMappedItem<S, U>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let Some(diffs) = ready!(this.inner.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Mapping never drops a diff, so this only returns `None` for an
+            // empty batch.
+            if let Some(diffs) = diffs.filter_map(|diff| Some(diff.map(&*this.f))) {
+                return Poll::Ready(Some(diffs));
+            }
+        }
+    }
+}
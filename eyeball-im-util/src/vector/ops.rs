@@ -4,10 +4,17 @@ use smallvec::SmallVec;
 
 pub trait VectorDiffContainerOps<T>: Sized {
     type Family: VectorDiffContainerFamily;
+    type ConcatBuf: Default;
+    type DedupBuf: Default;
+    type FilterBuf: Default;
+    type FlattenBuf: Default;
+    type GroupByBuf: Default;
     type HeadBuf: Default;
     type TailBuf: Default;
     type SkipBuf: Default;
     type SortBuf: Default;
+    type ReverseBuf: Default;
+    type WindowBuf: Default;
 
     fn from_item(vector_diff: VectorDiff<T>) -> Self;
 
@@ -16,6 +23,40 @@ pub trait VectorDiffContainerOps<T>: Sized {
         f: impl FnMut(VectorDiff<T>) -> Option<VectorDiff<U>>,
     ) -> Option<VectorDiffContainerFamilyMember<Self::Family, U>>;
 
+    fn for_each(self, f: impl FnMut(VectorDiff<T>));
+
+    fn push_into_concat_buf(
+        self,
+        buffer: &mut Self::ConcatBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self>;
+
+    fn pop_from_concat_buf(buffer: &mut Self::ConcatBuf) -> Option<Self>;
+
+    fn push_into_dedup_buf(
+        self,
+        buffer: &mut Self::DedupBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self>;
+
+    fn pop_from_dedup_buf(buffer: &mut Self::DedupBuf) -> Option<Self>;
+
+    fn extend_filter_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::FilterBuf) -> Option<Self>;
+
+    fn pop_from_filter_buf(buffer: &mut Self::FilterBuf) -> Option<Self>;
+
+    fn extend_flatten_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::FlattenBuf)
+        -> Option<Self>;
+
+    fn pop_from_flatten_buf(buffer: &mut Self::FlattenBuf) -> Option<Self>;
+
+    fn extend_group_by_buf(
+        diffs: Vec<VectorDiff<T>>,
+        buffer: &mut Self::GroupByBuf,
+    ) -> Option<Self>;
+
+    fn pop_from_group_by_buf(buffer: &mut Self::GroupByBuf) -> Option<Self>;
+
     fn push_into_head_buf(
         self,
         buffer: &mut Self::HeadBuf,
@@ -50,7 +91,27 @@ pub trait VectorDiffContainerOps<T>: Sized {
         map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
     ) -> Option<Self>;
 
+    fn extend_sort_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::SortBuf) -> Option<Self>;
+
     fn pop_from_sort_buf(buffer: &mut Self::SortBuf) -> Option<Self>;
+
+    fn push_into_reverse_buf(
+        self,
+        buffer: &mut Self::ReverseBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self>;
+
+    fn pop_from_reverse_buf(buffer: &mut Self::ReverseBuf) -> Option<Self>;
+
+    fn push_into_window_buf(
+        self,
+        buffer: &mut Self::WindowBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self>;
+
+    fn extend_window_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::WindowBuf) -> Option<Self>;
+
+    fn pop_from_window_buf(buffer: &mut Self::WindowBuf) -> Option<Self>;
 }
 
 #[allow(unreachable_pub)]
@@ -58,10 +119,17 @@ pub type VectorDiffContainerFamilyMember<F, U> = <F as VectorDiffContainerFamily
 
 impl<T> VectorDiffContainerOps<T> for VectorDiff<T> {
     type Family = VectorDiffFamily;
+    type ConcatBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type DedupBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type FilterBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type FlattenBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type GroupByBuf = SmallVec<[VectorDiff<T>; 2]>;
     type HeadBuf = Option<VectorDiff<T>>;
     type TailBuf = SmallVec<[VectorDiff<T>; 2]>;
     type SkipBuf = SmallVec<[VectorDiff<T>; 2]>;
     type SortBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type ReverseBuf = SmallVec<[VectorDiff<T>; 2]>;
+    type WindowBuf = SmallVec<[VectorDiff<T>; 2]>;
 
     fn from_item(vector_diff: VectorDiff<T>) -> Self {
         vector_diff
@@ -74,6 +142,88 @@ impl<T> VectorDiffContainerOps<T> for VectorDiff<T> {
         f(self)
     }
 
+    fn for_each(self, mut f: impl FnMut(VectorDiff<T>)) {
+        f(self);
+    }
+
+    fn push_into_concat_buf(
+        self,
+        buffer: &mut Self::ConcatBuf,
+        mut map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        assert!(buffer.is_empty(), "buffer must be empty when calling `push_into_concat_buf`");
+
+        // Store the diffs in reverse order to pop from the buffer.
+        *buffer = map_diffs(self);
+        buffer.reverse();
+
+        buffer.pop()
+    }
+
+    fn pop_from_concat_buf(buffer: &mut Self::ConcatBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
+    fn push_into_dedup_buf(
+        self,
+        buffer: &mut Self::DedupBuf,
+        mut map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        assert!(buffer.is_empty(), "buffer must be empty when calling `push_into_dedup_buf`");
+
+        // Store the diffs in reverse order to pop from the buffer.
+        *buffer = map_diffs(self);
+        buffer.reverse();
+
+        buffer.pop()
+    }
+
+    fn pop_from_dedup_buf(buffer: &mut Self::DedupBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
+    fn extend_filter_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::FilterBuf) -> Option<Self> {
+        // We cannot pop front on a `SmallVec`. We store all `diffs` in reverse order to
+        // pop from it.
+        buffer.insert_many(0, diffs.into_iter().rev());
+
+        buffer.pop()
+    }
+
+    fn pop_from_filter_buf(buffer: &mut Self::FilterBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
+    fn extend_flatten_buf(
+        diffs: Vec<VectorDiff<T>>,
+        buffer: &mut Self::FlattenBuf,
+    ) -> Option<Self> {
+        // We cannot pop front on a `SmallVec`. We store all `diffs` in reverse order to
+        // pop from it.
+        buffer.insert_many(0, diffs.into_iter().rev());
+
+        buffer.pop()
+    }
+
+    fn pop_from_flatten_buf(buffer: &mut Self::FlattenBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
+    fn extend_group_by_buf(
+        diffs: Vec<VectorDiff<T>>,
+        buffer: &mut Self::GroupByBuf,
+    ) -> Option<Self> {
+        // We cannot pop front on a `SmallVec`. We store all `diffs` in reverse order to
+        // pop from it.
+        buffer.insert_many(0, diffs.into_iter().rev());
+
+        buffer.pop()
+    }
+
+    fn pop_from_group_by_buf(buffer: &mut Self::GroupByBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
     fn push_into_head_buf(
         self,
         buffer: &mut Self::HeadBuf,
@@ -163,17 +313,76 @@ impl<T> VectorDiffContainerOps<T> for VectorDiff<T> {
         }
     }
 
+    fn extend_sort_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::SortBuf) -> Option<Self> {
+        // We cannot pop front on a `SmallVec`. We store all `diffs` in reverse order to
+        // pop from it.
+        buffer.insert_many(0, diffs.into_iter().rev());
+
+        buffer.pop()
+    }
+
     fn pop_from_sort_buf(buffer: &mut Self::SortBuf) -> Option<Self> {
         buffer.pop()
     }
+
+    fn push_into_reverse_buf(
+        self,
+        buffer: &mut Self::ReverseBuf,
+        mut map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        assert!(buffer.is_empty(), "buffer must be empty when calling `push_into_reverse_buf`");
+
+        // Store the diffs in reverse order to pop from the buffer.
+        *buffer = map_diffs(self);
+        buffer.reverse();
+
+        buffer.pop()
+    }
+
+    fn pop_from_reverse_buf(buffer: &mut Self::ReverseBuf) -> Option<Self> {
+        buffer.pop()
+    }
+
+    fn push_into_window_buf(
+        self,
+        buffer: &mut Self::WindowBuf,
+        mut map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        assert!(buffer.is_empty(), "buffer must be empty when calling `push_into_window_buf`");
+
+        // Store the diffs in reverse order to pop from the buffer.
+        *buffer = map_diffs(self);
+        buffer.reverse();
+
+        buffer.pop()
+    }
+
+    fn extend_window_buf(diffs: Vec<VectorDiff<T>>, buffer: &mut Self::WindowBuf) -> Option<Self> {
+        // We cannot pop front on a `SmallVec`. We store all `diffs` in reverse order to
+        // pop from it.
+        buffer.insert_many(0, diffs.into_iter().rev());
+
+        buffer.pop()
+    }
+
+    fn pop_from_window_buf(buffer: &mut Self::WindowBuf) -> Option<Self> {
+        buffer.pop()
+    }
 }
 
 impl<T> VectorDiffContainerOps<T> for Vec<VectorDiff<T>> {
     type Family = VecVectorDiffFamily;
+    type ConcatBuf = ();
+    type DedupBuf = ();
+    type FilterBuf = ();
+    type FlattenBuf = ();
+    type GroupByBuf = ();
     type HeadBuf = ();
     type TailBuf = ();
     type SkipBuf = ();
     type SortBuf = ();
+    type ReverseBuf = ();
+    type WindowBuf = ();
 
     fn from_item(vector_diff: VectorDiff<T>) -> Self {
         vec![vector_diff]
@@ -192,6 +401,88 @@ impl<T> VectorDiffContainerOps<T> for Vec<VectorDiff<T>> {
         }
     }
 
+    fn for_each(self, f: impl FnMut(VectorDiff<T>)) {
+        self.into_iter().for_each(f);
+    }
+
+    fn push_into_concat_buf(
+        self,
+        _buffer: &mut Self::ConcatBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        let res: Vec<_> = self.into_iter().flat_map(map_diffs).collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    fn pop_from_concat_buf(_buffer: &mut Self::ConcatBuf) -> Option<Self> {
+        None
+    }
+
+    fn push_into_dedup_buf(
+        self,
+        _buffer: &mut Self::DedupBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        let res: Vec<_> = self.into_iter().flat_map(map_diffs).collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    fn pop_from_dedup_buf(_buffer: &mut Self::DedupBuf) -> Option<Self> {
+        None
+    }
+
+    fn extend_filter_buf(diffs: Vec<VectorDiff<T>>, _buffer: &mut Self::FilterBuf) -> Option<Self> {
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(diffs)
+        }
+    }
+
+    fn pop_from_filter_buf(_buffer: &mut Self::FilterBuf) -> Option<Self> {
+        None
+    }
+
+    fn extend_flatten_buf(
+        diffs: Vec<VectorDiff<T>>,
+        _buffer: &mut Self::FlattenBuf,
+    ) -> Option<Self> {
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(diffs)
+        }
+    }
+
+    fn pop_from_flatten_buf(_buffer: &mut Self::FlattenBuf) -> Option<Self> {
+        None
+    }
+
+    fn extend_group_by_buf(
+        diffs: Vec<VectorDiff<T>>,
+        _buffer: &mut Self::GroupByBuf,
+    ) -> Option<Self> {
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(diffs)
+        }
+    }
+
+    fn pop_from_group_by_buf(_buffer: &mut Self::GroupByBuf) -> Option<Self> {
+        None
+    }
+
     fn push_into_head_buf(
         self,
         _buffer: &mut Self::HeadBuf,
@@ -276,9 +567,61 @@ impl<T> VectorDiffContainerOps<T> for Vec<VectorDiff<T>> {
         }
     }
 
+    fn extend_sort_buf(diffs: Vec<VectorDiff<T>>, _buffer: &mut Self::SortBuf) -> Option<Self> {
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(diffs)
+        }
+    }
+
     fn pop_from_sort_buf(_: &mut Self::HeadBuf) -> Option<Self> {
         None
     }
+
+    fn push_into_reverse_buf(
+        self,
+        _buffer: &mut Self::ReverseBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        let res: Vec<_> = self.into_iter().flat_map(map_diffs).collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    fn pop_from_reverse_buf(_buffer: &mut Self::ReverseBuf) -> Option<Self> {
+        None
+    }
+
+    fn push_into_window_buf(
+        self,
+        _buffer: &mut Self::WindowBuf,
+        map_diffs: impl FnMut(VectorDiff<T>) -> SmallVec<[VectorDiff<T>; 2]>,
+    ) -> Option<Self> {
+        let res: Vec<_> = self.into_iter().flat_map(map_diffs).collect();
+
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    fn extend_window_buf(diffs: Vec<VectorDiff<T>>, _buffer: &mut Self::WindowBuf) -> Option<Self> {
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(diffs)
+        }
+    }
+
+    fn pop_from_window_buf(_buffer: &mut Self::WindowBuf) -> Option<Self> {
+        None
+    }
 }
 
 #[allow(unreachable_pub)]
@@ -0,0 +1,235 @@
+use smallvec::{smallvec, SmallVec};
+use std::{
+    iter::repeat,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use super::{
+    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    VectorDiffContainerStreamReverseBuf,
+};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a reversed view of the
+    /// underlying [`ObservableVector`]s items.
+    ///
+    /// Every `VectorDiff` is translated into its mirrored form, e.g. a
+    /// `PushBack` becomes a `PushFront` and an `Insert` at index `i` of a
+    /// vector of length `len` becomes an `Insert` at index `len - i`.
+    ///
+    /// Some `VectorDiff`s have no single mirrored form and are translated into
+    /// multiple `VectorDiff`s: an `Append` becomes one `PushFront` per value
+    /// (unless the vector was empty), and a `Truncate` becomes one `PopFront`
+    /// per removed value (or a `Clear` if no value is left).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+    ///
+    /// let mut ob = ObservableVector::<char>::from(vector!['a', 'b']);
+    /// let (values, mut sub) = ob.subscribe().reverse();
+    ///
+    /// assert_eq!(values, vector!['b', 'a']);
+    /// assert_pending!(sub);
+    ///
+    /// ob.push_back('c');
+    /// assert_next_eq!(sub, VectorDiff::PushFront { value: 'c' });
+    ///
+    /// ob.insert(1, 'd');
+    /// // | `ob`  | a d b c |
+    /// // | `sub` | c b d a |
+    /// assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 'd' });
+    ///
+    /// ob.truncate(2);
+    /// // | `ob`  | a d |
+    /// // | `sub` | d a |
+    /// assert_next_eq!(sub, VectorDiff::PopFront);
+    /// assert_next_eq!(sub, VectorDiff::PopFront);
+    ///
+    /// assert_pending!(sub);
+    /// drop(ob);
+    /// assert_closed!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = ReverseProj]
+    pub struct Reverse<S>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+    {
+        #[pin]
+        inner_stream: S,
+
+        // The length of the underlying vector.
+        len: usize,
+
+        // This adapter can produce multiple items per item of the underlying
+        // stream.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-batched, can't
+        // just add diffs to a `poll_next` result), we need a buffer to store
+        // the possible extra items in.
+        ready_values: VectorDiffContainerStreamReverseBuf<S>,
+    }
+}
+
+impl<S> Reverse<S>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+{
+    /// Create a new [`Reverse`] with the given initial values and stream of
+    /// `VectorDiff` updates for those values.
+    ///
+    /// Returns the reversed initial values as well as a stream of updates
+    /// for them.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        let len = initial_values.len();
+        let stream = Self { inner_stream, len, ready_values: Default::default() };
+        (reversed(initial_values), stream)
+    }
+}
+
+impl<S> Stream for Reverse<S>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S> ReverseProj<'_, S>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_reverse_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll `VectorDiff`s from the `inner_stream`.
+            let Some(diffs) = ready!(self.inner_stream.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Consume and apply the diffs if possible.
+            let ready =
+                diffs.push_into_reverse_buf(self.ready_values, |diff| handle_diff(diff, self.len));
+
+            if let Some(diff) = ready {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Else loop and poll the streams again.
+        }
+    }
+}
+
+fn handle_diff<T: Clone>(diff: VectorDiff<T>, len: &mut usize) -> SmallVec<[VectorDiff<T>; 2]> {
+    let prev_len = *len;
+
+    match diff {
+        VectorDiff::Append { values } => {
+            *len += values.len();
+
+            if prev_len == 0 {
+                smallvec![VectorDiff::Append { values: reversed(values) }]
+            } else {
+                values.into_iter().map(|value| VectorDiff::PushFront { value }).collect()
+            }
+        }
+        VectorDiff::Clear => {
+            *len = 0;
+            smallvec![VectorDiff::Clear]
+        }
+        VectorDiff::PushFront { value } => {
+            *len += 1;
+            smallvec![VectorDiff::PushBack { value }]
+        }
+        VectorDiff::PushBack { value } => {
+            *len += 1;
+            smallvec![VectorDiff::PushFront { value }]
+        }
+        VectorDiff::PopFront => {
+            *len -= 1;
+            smallvec![VectorDiff::PopBack]
+        }
+        VectorDiff::PopBack => {
+            *len -= 1;
+            smallvec![VectorDiff::PopFront]
+        }
+        VectorDiff::Insert { index, value } => {
+            *len += 1;
+            smallvec![VectorDiff::Insert { index: prev_len - index, value }]
+        }
+        VectorDiff::Set { index, value } => {
+            smallvec![VectorDiff::Set { index: prev_len - 1 - index, value }]
+        }
+        VectorDiff::Remove { index } => {
+            *len -= 1;
+            smallvec![VectorDiff::Remove { index: prev_len - 1 - index }]
+        }
+        VectorDiff::Truncate { length } => {
+            *len = length;
+
+            if length == 0 {
+                smallvec![VectorDiff::Clear]
+            } else {
+                repeat(VectorDiff::PopFront).take(prev_len - length).collect()
+            }
+        }
+        VectorDiff::Reset { values } => {
+            *len = values.len();
+            smallvec![VectorDiff::Reset { values: reversed(values) }]
+        }
+        VectorDiff::Move { from, to } => {
+            smallvec![VectorDiff::Move { from: prev_len - 1 - from, to: prev_len - 1 - to }]
+        }
+        VectorDiff::Swap { first, second } => {
+            smallvec![VectorDiff::Swap {
+                first: prev_len - 1 - first,
+                second: prev_len - 1 - second
+            }]
+        }
+        VectorDiff::InsertMany { index, values } => {
+            *len += values.len();
+            smallvec![VectorDiff::InsertMany { index: prev_len - index, values: reversed(values) }]
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            *len -= end - start;
+            smallvec![VectorDiff::RemoveRange { start: prev_len - end, end: prev_len - start }]
+        }
+        VectorDiff::Splice { start, end, values } => {
+            *len = prev_len - (end - start) + values.len();
+            smallvec![VectorDiff::Splice {
+                start: prev_len - end,
+                end: prev_len - start,
+                values: reversed(values)
+            }]
+        }
+    }
+}
+
+fn reversed<T: Clone>(values: Vector<T>) -> Vector<T> {
+    values.into_iter().rev().collect()
+}
@@ -1,13 +1,13 @@
 use smallvec::SmallVec;
 use std::{
-    cmp::{min, Ordering},
+    cmp::{max, min, Ordering},
     iter::repeat,
     pin::Pin,
     task::{self, ready, Poll},
 };
 
 use super::{
-    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
+    concat::splice, VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
     VectorDiffContainerStreamSkipBuf, VectorObserver,
 };
 use eyeball_im::VectorDiff;
@@ -374,7 +374,7 @@ where
     }
 }
 
-fn handle_diff<T: Clone>(
+pub(super) fn handle_diff<T: Clone>(
     diff: VectorDiff<T>,
     count: usize,
     previous_length: usize,
@@ -499,6 +499,112 @@ fn handle_diff<T: Clone>(
         VectorDiff::Reset { values } => {
             res.push(VectorDiff::Reset { values: values.skeep(count) });
         }
+
+        VectorDiff::Move { from, to } => {
+            match (from >= count, to >= count) {
+                // The move happens after `count`, we need to re-map the indices.
+                (true, true) => {
+                    res.push(VectorDiff::Move { from: from - count, to: to - count });
+                }
+                // The value leaves the view, and the value at `count` enters
+                // it at the front.
+                (true, false) => {
+                    res.push(VectorDiff::Remove { index: from - count });
+                    res.push(VectorDiff::PushFront { value: buffered_vector[count].clone() });
+                }
+                // The value enters the view, and the first value leaves it.
+                (false, true) => {
+                    res.push(VectorDiff::PopFront);
+                    res.push(VectorDiff::Insert {
+                        index: to - count,
+                        value: buffered_vector[to].clone(),
+                    });
+                }
+                // The move happens before `count`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+
+        VectorDiff::Swap { first, second } => {
+            match (first >= count, second >= count) {
+                // The swap happens after `count`, we need to re-map the indices.
+                (true, true) => {
+                    res.push(VectorDiff::Swap { first: first - count, second: second - count });
+                }
+                // Only one of the values is after `count`, it's replaced.
+                (true, false) => {
+                    res.push(VectorDiff::Set {
+                        index: first - count,
+                        value: buffered_vector[first].clone(),
+                    });
+                }
+                (false, true) => {
+                    res.push(VectorDiff::Set {
+                        index: second - count,
+                        value: buffered_vector[second].clone(),
+                    });
+                }
+                // The swap happens before `count`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+
+        VectorDiff::InsertMany { index, values } => {
+            // The insert happens after `count`, we need to re-map `index`.
+            if index >= count {
+                res.push(VectorDiff::InsertMany { index: index - count, values });
+            }
+            // The insert happens before `count`, it shifts values into the
+            // view at the front.
+            else {
+                let values: Vector<_> =
+                    buffered_vector.iter().skip(count).take(values.len()).cloned().collect();
+
+                if !values.is_empty() {
+                    res.push(VectorDiff::InsertMany { index: 0, values });
+                }
+            }
+        }
+
+        VectorDiff::RemoveRange { start, end } => {
+            // The removal happens after `count`, we need to re-map the range.
+            if start >= count {
+                res.push(VectorDiff::RemoveRange { start: start - count, end: end - count });
+            }
+            // The removal starts before `count`, it shifts values out of the
+            // view at the front.
+            else {
+                let end = min(end - start, previous_length.saturating_sub(count));
+
+                if end > 0 {
+                    res.push(VectorDiff::RemoveRange { start: 0, end });
+                }
+            }
+        }
+
+        VectorDiff::Splice { start, end, values } => {
+            // The splice happens after `count`, we need to re-map the range.
+            if start >= count {
+                res.push(VectorDiff::Splice { start: start - count, end: end - count, values });
+            }
+            // The splice starts before `count`, the values after the spliced
+            // range that remain in the view are kept, the others are replaced.
+            else {
+                let kept = min(
+                    previous_length.saturating_sub(max(end, count)),
+                    buffered_vector.len().saturating_sub(count),
+                );
+                let end = previous_length.saturating_sub(count) - kept;
+                let values: Vector<_> = buffered_vector
+                    .iter()
+                    .skip(count)
+                    .take(buffered_vector.len().saturating_sub(count) - kept)
+                    .cloned()
+                    .collect();
+
+                res.extend(splice(0, end, values));
+            }
+        }
     }
 
     res
@@ -517,7 +623,7 @@ impl Stream for EmptyCountStream {
     }
 }
 
-trait Skeep {
+pub(super) trait Skeep {
     fn skeep(self, count: usize) -> Self;
 }
 
@@ -11,8 +11,8 @@ use pin_project_lite::pin_project;
 use smallvec::SmallVec;
 
 use super::{
-    VectorDiffContainer, VectorDiffContainerOps, VectorDiffContainerStreamElement,
-    VectorDiffContainerStreamSortBuf,
+    VectorDiffContainer, VectorDiffContainerDiff, VectorDiffContainerOps,
+    VectorDiffContainerStreamElement, VectorDiffContainerStreamSortBuf,
 };
 
 type UnsortedIndex = usize;
@@ -220,6 +220,297 @@ where
 }
 
 pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a sorted view of the
+    /// underlying [`ObservableVector`] items, with a comparison function that
+    /// can be replaced at runtime.
+    ///
+    /// New comparison functions are polled from a stream. Whenever a new one
+    /// is received, the items are sorted again and the items that changed
+    /// position are moved with a `VectorDiff::Remove` followed by a
+    /// `VectorDiff::Insert`. The items that keep their relative order (the
+    /// longest such sequence) are not touched. If more than half of the items
+    /// would need to move, a single `VectorDiff::Reset` is produced instead.
+    ///
+    /// Otherwise this adapter works exactly like [`Sort`], see that type's
+    /// documentation for details on how this adapter operates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::cmp::Ordering;
+    ///
+    /// use eyeball::Observable;
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use futures_util::StreamExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_next_eq, assert_pending};
+    ///
+    /// let ob = ObservableVector::<u32>::from(vector![3, 1, 4, 2]);
+    /// let mut reverse = Observable::new(false);
+    ///
+    /// // All comparison functions must have the same type, so create them the
+    /// // same way.
+    /// let make_compare = |reverse: bool| {
+    ///     move |a: &u32, b: &u32| if reverse { b.cmp(a) } else { a.cmp(b) }
+    /// };
+    /// let compare_stream = Observable::subscribe(&reverse).map(make_compare);
+    ///
+    /// let (values, mut sub) =
+    ///     ob.subscribe().dynamic_sort_by_with_initial_value(make_compare(false), compare_stream);
+    /// assert_eq!(values, vector![1, 2, 3, 4]);
+    ///
+    /// Observable::set(&mut reverse, true);
+    /// assert_next_eq!(sub, VectorDiff::Reset { values: vector![4, 3, 2, 1] });
+    /// assert_pending!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = DynamicSortByProj]
+    pub struct DynamicSortBy<S, C>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+        C: Stream,
+    {
+        #[pin]
+        inner: SortImpl<S>,
+
+        // The stream to poll new comparison functions from.
+        #[pin]
+        compare_stream: C,
+
+        // The current comparison function, `None` until the first one is
+        // received if there was no initial comparison function.
+        compare: Option<C::Item>,
+    }
+}
+
+impl<S, C> DynamicSortBy<S, C>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    C: Stream,
+    C::Item:
+        Fn(&VectorDiffContainerStreamElement<S>, &VectorDiffContainerStreamElement<S>) -> Ordering,
+{
+    /// Create a new `DynamicSortBy` with the given (unsorted) initial values,
+    /// stream of `VectorDiff` updates for those values, and stream of
+    /// comparison functions.
+    ///
+    /// Note that the returned `DynamicSortBy` won't produce anything until the
+    /// first comparison function is produced by the stream. Then, all items
+    /// are produced at once with a `VectorDiff::Append`.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        compare_stream: C,
+    ) -> Self {
+        let (_, inner) = SortImpl::new(initial_values, inner_stream, |_, _| Ordering::Equal);
+        Self { inner, compare_stream, compare: None }
+    }
+
+    /// Create a new `DynamicSortBy` with the given (unsorted) initial values,
+    /// stream of `VectorDiff` updates for those values, and an initial
+    /// comparison function as well as a stream of new comparison functions.
+    pub fn with_initial_compare(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        initial_compare: C::Item,
+        compare_stream: C,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        let (initial_sorted, inner) = SortImpl::new(initial_values, inner_stream, &initial_compare);
+        (initial_sorted, Self { inner, compare_stream, compare: Some(initial_compare) })
+    }
+}
+
+impl<S, C> Stream for DynamicSortBy<S, C>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    C: Stream,
+    C::Item:
+        Fn(&VectorDiffContainerStreamElement<S>, &VectorDiffContainerStreamElement<S>) -> Ordering,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, C> DynamicSortByProj<'_, S, C>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    C: Stream,
+    C::Item:
+        Fn(&VectorDiffContainerStreamElement<S>, &VectorDiffContainerStreamElement<S>) -> Ordering,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        loop {
+            let inner = self.inner.as_mut().project();
+
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_sort_buf(inner.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll a new comparison function from `compare_stream` before
+            // polling `inner_stream`.
+            while let Poll::Ready(Some(next_compare)) = self.compare_stream.as_mut().poll_next(cx) {
+                let diffs = self.update_compare(next_compare);
+                if !diffs.is_empty() {
+                    let ready_values = self.inner.as_mut().project().ready_values;
+                    return Poll::Ready(S::Item::extend_sort_buf(diffs, ready_values));
+                }
+
+                // If the order didn't change, poll the stream again.
+            }
+
+            let inner = self.inner.as_mut().project();
+
+            // Poll `VectorDiff`s from the `inner_stream`.
+            let Some(diffs) = ready!(inner.inner_stream.poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            let buffered_vector = inner.buffered_vector;
+            let ready = match &*self.compare {
+                Some(compare) => diffs.push_into_sort_buf(inner.ready_values, |diff| {
+                    handle_diff_and_update_buffered_vector(diff, compare, buffered_vector)
+                }),
+                // Without a comparison function, nothing is visible yet. Just
+                // keep the buffered vector up to date.
+                None => diffs.filter_map(|diff| {
+                    handle_diff_and_update_buffered_vector(
+                        diff,
+                        |_, _| Ordering::Equal,
+                        buffered_vector,
+                    );
+                    None::<VectorDiffContainerDiff<S>>
+                }),
+            };
+
+            if let Some(diff) = ready {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Else loop and poll the streams again.
+        }
+    }
+
+    /// Replace the comparison function, sort the buffered vector again and
+    /// compute the `VectorDiff`s that move the items to their new positions.
+    fn update_compare(&mut self, new_compare: C::Item) -> Vec<VectorDiffContainerDiff<S>> {
+        let buffered_vector = self.inner.as_mut().project().buffered_vector;
+        let old_sorted = buffered_vector.clone();
+        // Break ties by the unsorted index, like the initial sorting does.
+        buffered_vector.sort_by(|(left_index, left), (right_index, right)| {
+            new_compare(left, right).then_with(|| left_index.cmp(right_index))
+        });
+        let had_compare = self.compare.replace(new_compare).is_some();
+
+        if buffered_vector.is_empty() {
+            return Vec::new();
+        }
+        if !had_compare {
+            // Nothing was visible so far.
+            let values = buffered_vector.iter().map(|(_, value)| value.clone()).collect();
+            return vec![VectorDiff::Append { values }];
+        }
+
+        // The old position of every item, indexed by unsorted index.
+        let mut old_positions = vec![0; old_sorted.len()];
+        for (position, (unsorted_index, _)) in old_sorted.iter().enumerate() {
+            old_positions[*unsorted_index] = position;
+        }
+
+        // The old positions of the items, in their new order. The items in
+        // its longest increasing subsequence keep their relative order, so
+        // they don't need to be moved.
+        let old_positions_in_new_order: Vec<_> = buffered_vector
+            .iter()
+            .map(|(unsorted_index, _)| old_positions[*unsorted_index])
+            .collect();
+        let mut is_kept = vec![false; old_positions_in_new_order.len()];
+        for new_position in longest_increasing_subsequence(&old_positions_in_new_order) {
+            is_kept[new_position] = true;
+        }
+
+        let num_moves = is_kept.iter().filter(|&&kept| !kept).count();
+        if num_moves == 0 {
+            return Vec::new();
+        }
+        if num_moves > buffered_vector.len() / 2 {
+            let values = buffered_vector.iter().map(|(_, value)| value.clone()).collect();
+            return vec![VectorDiff::Reset { values }];
+        }
+
+        let mut is_moved_by_old_position = vec![false; old_sorted.len()];
+        for (new_position, &kept) in is_kept.iter().enumerate() {
+            if !kept {
+                is_moved_by_old_position[old_positions_in_new_order[new_position]] = true;
+            }
+        }
+
+        // First remove all of the moved items, from the back so that the
+        // indices of the remaining ones don't change…
+        let mut diffs: Vec<_> = is_moved_by_old_position
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, &moved)| moved)
+            .map(|(index, _)| VectorDiff::Remove { index })
+            .collect();
+
+        // … then insert them again at their new position. Since the inserts
+        // are done from the front, all of the items before the inserted one
+        // are already in place.
+        diffs.extend(
+            buffered_vector
+                .iter()
+                .zip(&is_kept)
+                .enumerate()
+                .filter(|(_, (_, &kept))| !kept)
+                .map(|(index, ((_, value), _))| VectorDiff::Insert { index, value: value.clone() }),
+        );
+
+        diffs
+    }
+}
+
+/// Get the indices of one of the longest strictly increasing subsequences of
+/// `values`, in ascending order.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // `tails[len]` is the index of the smallest value that ends an increasing
+    // subsequence of length `len + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    // The index of the previous value in the subsequence ending at each index.
+    let mut predecessors = vec![None; values.len()];
+
+    for (index, &value) in values.iter().enumerate() {
+        let len = tails.partition_point(|&tail| values[tail] < value);
+        predecessors[index] = len.checked_sub(1).map(|prev_len| tails[prev_len]);
+        if len == tails.len() {
+            tails.push(index);
+        } else {
+            tails[len] = index;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        result.push(index);
+        next = predecessors[index];
+    }
+    result.reverse();
+    result
+}
+
+pin_project! {
+    #[project = SortImplProj]
     pub struct SortImpl<S>
     where
         S: Stream,
@@ -323,7 +614,7 @@ fn handle_diff_and_update_buffered_vector<T, F>(
 ) -> SmallVec<[VectorDiff<T>; 2]>
 where
     T: Clone,
-    F: Fn(&T, &T) -> Ordering,
+    F: Fn(&T, &T) -> Ordering + Copy,
 {
     let mut result = SmallVec::new();
 
@@ -704,6 +995,63 @@ where
                 values: new_values.into_iter().map(|(_, value)| value).collect(),
             });
         }
+        VectorDiff::Move { from, to } => {
+            // The sorted values don't change, only their unsorted indices:
+            // the moved value goes to `to`, and the values between `from` and
+            // `to` are shifted towards `from`.
+            buffered_vector.iter_mut().for_each(|(unsorted_index, _)| {
+                if *unsorted_index == from {
+                    *unsorted_index = to;
+                } else if from < to && from < *unsorted_index && *unsorted_index <= to {
+                    *unsorted_index -= 1;
+                } else if to < from && to <= *unsorted_index && *unsorted_index < from {
+                    *unsorted_index += 1;
+                }
+            });
+        }
+        VectorDiff::Swap { first, second } => {
+            // The sorted values don't change, only their unsorted indices.
+            buffered_vector.iter_mut().for_each(|(unsorted_index, _)| {
+                if *unsorted_index == first {
+                    *unsorted_index = second;
+                } else if *unsorted_index == second {
+                    *unsorted_index = first;
+                }
+            });
+        }
+        VectorDiff::InsertMany { index, values } => {
+            // Insert the values one by one, each of them must be sorted.
+            for (offset, value) in values.into_iter().enumerate() {
+                result.extend(handle_diff_and_update_buffered_vector(
+                    VectorDiff::Insert { index: index + offset, value },
+                    compare,
+                    buffered_vector,
+                ));
+            }
+        }
+        VectorDiff::RemoveRange { start, end } => {
+            // Remove the values one by one, they can be anywhere in the
+            // sorted values.
+            for _ in start..end {
+                result.extend(handle_diff_and_update_buffered_vector(
+                    VectorDiff::Remove { index: start },
+                    compare,
+                    buffered_vector,
+                ));
+            }
+        }
+        VectorDiff::Splice { start, end, values } => {
+            result.extend(handle_diff_and_update_buffered_vector(
+                VectorDiff::RemoveRange { start, end },
+                compare,
+                buffered_vector,
+            ));
+            result.extend(handle_diff_and_update_buffered_vector(
+                VectorDiff::InsertMany { index: start, values },
+                compare,
+                buffered_vector,
+            ));
+        }
     }
 
     result
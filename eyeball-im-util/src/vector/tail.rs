@@ -1,6 +1,6 @@
 use smallvec::SmallVec;
 use std::{
-    cmp::{min, Ordering},
+    cmp::{max, min, Ordering},
     iter::repeat,
     mem,
     pin::Pin,
@@ -8,7 +8,7 @@ use std::{
 };
 
 use super::{
-    EmptyLimitStream, VectorDiffContainer, VectorDiffContainerOps,
+    concat::splice, EmptyLimitStream, VectorDiffContainer, VectorDiffContainerOps,
     VectorDiffContainerStreamElement, VectorDiffContainerStreamTailBuf, VectorObserver,
 };
 use eyeball_im::VectorDiff;
@@ -433,8 +433,9 @@ fn handle_diff<T: Clone>(
 
                 // There is space for this new item.
                 res.push(VectorDiff::Insert {
-                    // Subtract 1 because `insert` adds a value compared to `previous_length`.
-                    index: (index - index_of_limit).saturating_sub(1),
+                    // Subtract 1 if the vector is full because `insert` adds a value compared to
+                    // `previous_length`, shifting the view.
+                    index: if is_full { index - index_of_limit - 1 } else { index },
                     value,
                 });
             } else {
@@ -487,11 +488,109 @@ fn handle_diff<T: Clone>(
             // There is space for these new items.
             res.push(VectorDiff::Reset { values: new_values });
         }
+
+        VectorDiff::Move { from, to } => {
+            match (from >= index_of_limit, to >= index_of_limit) {
+                // Move inside `limit`.
+                (true, true) => {
+                    res.push(VectorDiff::Move {
+                        from: from - index_of_limit,
+                        to: to - index_of_limit,
+                    });
+                }
+                // The item leaves the view, and a previously-truncated item
+                // enters it.
+                (true, false) => {
+                    res.push(VectorDiff::Remove { index: from - index_of_limit });
+                    res.push(VectorDiff::PushFront {
+                        value: buffered_vector[index_of_limit].clone(),
+                    });
+                }
+                // The item enters the view, and the first item leaves it.
+                (false, true) => {
+                    res.push(VectorDiff::PopFront);
+                    res.push(VectorDiff::Insert {
+                        index: to - index_of_limit,
+                        value: buffered_vector[to].clone(),
+                    });
+                }
+                // Move before `limit`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+
+        VectorDiff::Swap { first, second } => {
+            match (first >= index_of_limit, second >= index_of_limit) {
+                // Swap inside `limit`.
+                (true, true) => {
+                    res.push(VectorDiff::Swap {
+                        first: first - index_of_limit,
+                        second: second - index_of_limit,
+                    });
+                }
+                // Only one of the items is inside `limit`, it's replaced.
+                (true, false) => {
+                    res.push(VectorDiff::Set {
+                        index: first - index_of_limit,
+                        value: buffered_vector[first].clone(),
+                    });
+                }
+                (false, true) => {
+                    res.push(VectorDiff::Set {
+                        index: second - index_of_limit,
+                        value: buffered_vector[second].clone(),
+                    });
+                }
+                // Swap before `limit`, ignore the diff.
+                (false, false) => {}
+            }
+        }
+
+        VectorDiff::InsertMany { .. }
+        | VectorDiff::RemoveRange { .. }
+        | VectorDiff::Splice { .. }
+            if previous_length <= limit && buffered_vector.len() <= limit =>
+        {
+            // All the values are inside `limit`, before and after the diff.
+            res.push(diff);
+        }
+
+        VectorDiff::InsertMany { index: end, .. }
+        | VectorDiff::RemoveRange { end, .. }
+        | VectorDiff::Splice { end, .. } => {
+            res.extend(handle_splice(end, limit, previous_length, buffered_vector));
+        }
     }
 
     res
 }
 
+/// Translate the replacement of a range of values ending at `end` into a
+/// `VectorDiff` replacing the values at the front of the view.
+///
+/// The values after `end` that are still inside `limit` are kept, the others
+/// are replaced by the values that are inside `limit` now.
+fn handle_splice<T: Clone>(
+    end: usize,
+    limit: usize,
+    previous_length: usize,
+    buffered_vector: &Vector<T>,
+) -> Option<VectorDiff<T>> {
+    let previous_view_length = min(previous_length, limit);
+    let view_length = min(buffered_vector.len(), limit);
+
+    let kept = min(previous_length - max(end, previous_length - previous_view_length), view_length);
+    let end = previous_view_length - kept;
+    let values: Vector<_> = buffered_vector
+        .iter()
+        .skip(buffered_vector.len() - view_length)
+        .take(view_length - kept)
+        .cloned()
+        .collect();
+
+    splice(0, end, values)
+}
+
 trait TruncateFromEnd {
     fn truncate_from_end(self, len: usize) -> Self;
 }
@@ -1,6 +1,10 @@
 //! Public traits.
 
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    hash::Hash,
+    ops::{AddAssign, Range, SubAssign},
+};
 
 use eyeball_im::{
     VectorDiff, VectorSubscriber, VectorSubscriberBatchedStream, VectorSubscriberStream,
@@ -12,8 +16,10 @@ use super::{
     ops::{
         VecVectorDiffFamily, VectorDiffContainerFamily, VectorDiffContainerOps, VectorDiffFamily,
     },
-    EmptyCountStream, EmptyLimitStream, Filter, FilterMap, Head, Skip, Sort, SortBy, SortByKey,
-    Tail,
+    Aggregate, Aggregator, Concat, CountWhere, DedupByKey, DynamicFilter, DynamicSortBy,
+    EmptyCountStream, EmptyLimitStream, EmptyRangeStream, Filter, FilterMap, Flatten, Fold, Group,
+    GroupBy, Head, Map, MaxByKey, MinByKey, Reverse, Skip, Sort, SortBy, SortByKey, SumBy, Tail,
+    Window,
 };
 
 /// Abstraction over stream items that the adapters in this module can deal
@@ -106,6 +112,31 @@ where
     T: Clone + 'static,
     <Self::Stream as Stream>::Item: VectorDiffContainer<Element = T>,
 {
+    /// Concatenate the observed values with the ones of another observer.
+    ///
+    /// See [`Concat`] for more details, and for concatenating more than two
+    /// observers.
+    fn chain<O>(self, other: O) -> (Vector<T>, Concat<Self::Stream>)
+    where
+        O: VectorObserver<T, Stream = Self::Stream>,
+        Self::Stream: Unpin,
+    {
+        Concat::new([self.into_parts(), other.into_parts()])
+    }
+
+    /// Hide the observed values whose key, determined by the given function,
+    /// is the same as the one of an earlier value.
+    ///
+    /// See [`DedupByKey`] for more details.
+    fn dedup_by_key<K, F>(self, key_fn: F) -> (Vector<T>, DedupByKey<Self::Stream, K, F>)
+    where
+        F: Fn(&T) -> K,
+        K: Hash + Eq,
+    {
+        let (items, stream) = self.into_parts();
+        DedupByKey::new(items, stream, key_fn)
+    }
+
     /// Filter the vector's values with the given function.
     fn filter<F>(self, f: F) -> (Vector<T>, Filter<Self::Stream, F>)
     where
@@ -115,6 +146,35 @@ where
         Filter::new(items, stream, f)
     }
 
+    /// Filter the vector's values with filters determined by the given stream.
+    ///
+    /// See [`DynamicFilter`] for more details.
+    fn dynamic_filter<P>(self, filter_stream: P) -> DynamicFilter<Self::Stream, P>
+    where
+        P: Stream,
+        P::Item: Fn(&T) -> bool,
+    {
+        let (items, stream) = self.into_parts();
+        DynamicFilter::new(items, stream, filter_stream)
+    }
+
+    /// Filter the vector's values with `initial_filter` initially, and update
+    /// the filter with the values from the given stream.
+    ///
+    /// See [`DynamicFilter`] for more details.
+    fn dynamic_filter_with_initial_value<P>(
+        self,
+        initial_filter: P::Item,
+        filter_stream: P,
+    ) -> (Vector<T>, DynamicFilter<Self::Stream, P>)
+    where
+        P: Stream,
+        P::Item: Fn(&T) -> bool,
+    {
+        let (items, stream) = self.into_parts();
+        DynamicFilter::with_initial_filter(items, stream, initial_filter, filter_stream)
+    }
+
     /// Filter and map the vector's values with the given function.
     fn filter_map<U, F>(self, f: F) -> (Vector<U>, FilterMap<Self::Stream, F>)
     where
@@ -125,6 +185,47 @@ where
         FilterMap::new(items, stream, f)
     }
 
+    /// Flatten the observed vector of vectors, using `subscribe` to obtain an
+    /// observer of the inner vector of each value.
+    ///
+    /// See [`Flatten`] for more details.
+    fn flatten<U, O, F>(self, subscribe: F) -> (Vector<U>, Flatten<Self::Stream, O::Stream, F>)
+    where
+        U: Clone + 'static,
+        F: Fn(&T) -> O,
+        O: VectorObserver<U>,
+        O::Stream: Unpin,
+        <O::Stream as Stream>::Item: VectorDiffContainer<Element = U>,
+    {
+        let (items, stream) = self.into_parts();
+        Flatten::new(items, stream, subscribe)
+    }
+
+    /// Group the observed values by the key determined by the given function.
+    ///
+    /// See [`GroupBy`] for more details.
+    #[allow(clippy::type_complexity)]
+    fn group_by<K, F>(self, key_fn: F) -> (Vector<Group<K, T>>, GroupBy<Self::Stream, K, F>)
+    where
+        K: Ord + Clone,
+        F: Fn(&T) -> K,
+    {
+        let (items, stream) = self.into_parts();
+        GroupBy::new(items, stream, key_fn)
+    }
+
+    /// Map the vector's values with the given function.
+    ///
+    /// See [`Map`] for more details.
+    fn map<U, F>(self, f: F) -> (Vector<U>, Map<Self::Stream, F>)
+    where
+        U: Clone,
+        F: Fn(T) -> U,
+    {
+        let (items, stream) = self.into_parts();
+        Map::new(items, stream, f)
+    }
+
     /// Limit the observed values to the first `limit` values.
     ///
     /// See [`Head`] for more details.
@@ -233,6 +334,136 @@ where
         Skip::dynamic_with_initial_count(items, stream, initial_count, count_stream)
     }
 
+    /// Limit the observed values to at most `limit` values, starting after
+    /// the first `offset` values.
+    ///
+    /// See [`Window`] for more details.
+    fn window(
+        self,
+        offset: usize,
+        limit: usize,
+    ) -> (Vector<T>, Window<Self::Stream, EmptyRangeStream>) {
+        let (items, stream) = self.into_parts();
+        Window::new(items, stream, offset, limit)
+    }
+
+    /// Limit the observed values to the range of indices determined by the
+    /// given stream.
+    ///
+    /// See [`Window`] for more details.
+    fn dynamic_window<R>(self, range_stream: R) -> Window<Self::Stream, R>
+    where
+        R: Stream<Item = Range<usize>>,
+    {
+        let (items, stream) = self.into_parts();
+        Window::dynamic(items, stream, range_stream)
+    }
+
+    /// Limit the observed values to the `initial_range` of indices initially,
+    /// and update the range with the values from the given stream.
+    ///
+    /// See [`Window`] for more details.
+    fn dynamic_window_with_initial_range<R>(
+        self,
+        initial_range: Range<usize>,
+        range_stream: R,
+    ) -> (Vector<T>, Window<Self::Stream, R>)
+    where
+        R: Stream<Item = Range<usize>>,
+    {
+        let (items, stream) = self.into_parts();
+        Window::dynamic_with_initial_range(items, stream, initial_range, range_stream)
+    }
+
+    /// Reverse the order of the observed values.
+    ///
+    /// See [`Reverse`] for more details.
+    fn reverse(self) -> (Vector<T>, Reverse<Self::Stream>) {
+        let (items, stream) = self.into_parts();
+        Reverse::new(items, stream)
+    }
+
+    /// Reduce the observed values to a single value with the given
+    /// aggregator, which is updated incrementally.
+    ///
+    /// See [`Aggregate`] for more details.
+    fn aggregate<A>(self, aggregator: A) -> (A::Output, Aggregate<Self::Stream, A>)
+    where
+        A: Aggregator<T>,
+        A::Output: Clone + PartialEq,
+    {
+        let (items, stream) = self.into_parts();
+        Aggregate::new(items, stream, aggregator)
+    }
+
+    /// Count the observed values for which the given predicate returns `true`.
+    ///
+    /// See [`Aggregate`] for more details.
+    fn count_where<F>(self, predicate: F) -> (usize, Aggregate<Self::Stream, CountWhere<F>>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.aggregate(CountWhere::new(predicate))
+    }
+
+    /// Sum the values returned by the given function for every observed value.
+    ///
+    /// See [`Aggregate`] for more details.
+    fn sum_by<U, F>(self, f: F) -> (U, Aggregate<Self::Stream, SumBy<U, F>>)
+    where
+        U: Clone + Default + PartialEq + AddAssign + SubAssign,
+        F: Fn(&T) -> U,
+    {
+        self.aggregate(SumBy::new(f))
+    }
+
+    /// Find the observed value with the smallest key, as returned by the given
+    /// function.
+    ///
+    /// See [`Aggregate`] and [`MinByKey`] for more details.
+    fn min_by_key<K, F>(self, key_fn: F) -> (Option<T>, Aggregate<Self::Stream, MinByKey<F>>)
+    where
+        T: PartialEq,
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        self.aggregate(MinByKey::new(key_fn))
+    }
+
+    /// Find the observed value with the largest key, as returned by the given
+    /// function.
+    ///
+    /// See [`Aggregate`] and [`MaxByKey`] for more details.
+    fn max_by_key<K, F>(self, key_fn: F) -> (Option<T>, Aggregate<Self::Stream, MaxByKey<F>>)
+    where
+        T: PartialEq,
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        self.aggregate(MaxByKey::new(key_fn))
+    }
+
+    /// Fold the observed values into an accumulator, starting from `init`.
+    ///
+    /// `add` is called when a value is inserted, and `remove` when a value is
+    /// removed. `remove` must undo what `add` did for the same value.
+    ///
+    /// See [`Aggregate`] and [`Fold`] for more details.
+    #[allow(clippy::type_complexity)]
+    fn fold<A, F, G>(
+        self,
+        init: A,
+        add: F,
+        remove: G,
+    ) -> (A, Aggregate<Self::Stream, Fold<A, F, G>>)
+    where
+        A: Clone + PartialEq,
+        F: FnMut(&mut A, &T),
+        G: FnMut(&mut A, &T),
+    {
+        self.aggregate(Fold::new(init, add, remove))
+    }
+
     /// Sort the observed values.
     ///
     /// See [`Sort`] for more details.
@@ -255,6 +486,36 @@ where
         SortBy::new(items, stream, compare)
     }
 
+    /// Sort the observed values with comparison functions determined by the
+    /// given stream.
+    ///
+    /// See [`DynamicSortBy`] for more details.
+    fn dynamic_sort_by<C>(self, compare_stream: C) -> DynamicSortBy<Self::Stream, C>
+    where
+        C: Stream,
+        C::Item: Fn(&T, &T) -> Ordering,
+    {
+        let (items, stream) = self.into_parts();
+        DynamicSortBy::new(items, stream, compare_stream)
+    }
+
+    /// Sort the observed values with `initial_compare` initially, and update
+    /// the comparison function with the values from the given stream.
+    ///
+    /// See [`DynamicSortBy`] for more details.
+    fn dynamic_sort_by_with_initial_value<C>(
+        self,
+        initial_compare: C::Item,
+        compare_stream: C,
+    ) -> (Vector<T>, DynamicSortBy<Self::Stream, C>)
+    where
+        C: Stream,
+        C::Item: Fn(&T, &T) -> Ordering,
+    {
+        let (items, stream) = self.into_parts();
+        DynamicSortBy::with_initial_compare(items, stream, initial_compare, compare_stream)
+    }
+
     /// Sort the observed values with the given key function.
     ///
     /// See [`SortBy`] for more details.
@@ -0,0 +1,440 @@
+use smallvec::SmallVec;
+use std::{
+    cmp::{max, min},
+    ops::Range,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use super::{
+    head, skip, skip::Skeep, VectorDiffContainer, VectorDiffContainerOps,
+    VectorDiffContainerStreamElement, VectorDiffContainerStreamWindowBuf, VectorObserver,
+};
+use eyeball_im::VectorDiff;
+use futures_core::Stream;
+use imbl::Vector;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A [`VectorDiff`] stream adapter that presents a window of the
+    /// underlying [`ObservableVector`]s items. The window is given by a range
+    /// of indices: it starts at `range.start` and contains at most
+    /// `range.end - range.start` values.
+    ///
+    /// This is equivalent to combining [`Skip`](super::Skip) and
+    /// [`Head`](super::Head), except that both bounds of the window are
+    /// controlled by a single stream of ranges. This is useful to paginate
+    /// through a vector: when the window is moved, only the values that leave
+    /// or enter the window produce `VectorDiff`s.
+    ///
+    /// An internal buffered vector is kept so that the adapter knows which
+    /// values can be added when the window moves, or when values are removed
+    /// and new values must be inserted. This fact is important if the items of
+    /// the `Vector` have a non-negligible size.
+    ///
+    /// It's okay to have a range that goes past the end of the observed
+    /// `Vector`. A range whose end is before its start represents an empty
+    /// window.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use eyeball::Observable;
+    /// use eyeball_im::{ObservableVector, VectorDiff};
+    /// use eyeball_im_util::vector::VectorObserverExt;
+    /// use imbl::vector;
+    /// use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+    ///
+    /// // Our vector.
+    /// let mut ob = ObservableVector::<char>::from(vector!['a', 'b', 'c', 'd', 'e', 'f']);
+    /// let mut range = Observable::new(1..3);
+    /// let (values, mut sub) = ob
+    ///     .subscribe()
+    ///     .dynamic_window_with_initial_range(1..3, Observable::subscribe(&range));
+    ///
+    /// assert_eq!(values, vector!['b', 'c']);
+    ///
+    /// // Let's recap what we have. `ob` is our `ObservableVector`,
+    /// // `sub` is the “window” of `ob`:
+    /// // | `ob`  | a b c d e f |
+    /// // | `sub` | _ b c _ _ _ |
+    /// // “_” means the item is outside of the window.
+    ///
+    /// // Move the window by one value.
+    /// Observable::set(&mut range, 2..4);
+    /// // `b` leaves the window, and `d` enters it.
+    /// assert_next_eq!(sub, VectorDiff::PopFront);
+    /// assert_next_eq!(sub, VectorDiff::Append { values: vector!['d'] });
+    ///
+    /// // Let's recap what we have:
+    /// // | `ob`  | a b c d e f |
+    /// // | `sub` | _ _ c d _ _ |
+    ///
+    /// // Insert a value before the window.
+    /// ob.push_front('z');
+    /// // Every value is shifted to the right, so `d` leaves the window, and
+    /// // `b` enters it.
+    /// assert_next_eq!(sub, VectorDiff::PopBack);
+    /// assert_next_eq!(sub, VectorDiff::PushFront { value: 'b' });
+    ///
+    /// // Let's recap what we have:
+    /// // | `ob`  | z a b c d e f |
+    /// // | `sub` | _ _ b c _ _ _ |
+    ///
+    /// assert_pending!(sub);
+    /// drop(ob);
+    /// assert_closed!(sub);
+    /// ```
+    ///
+    /// [`ObservableVector`]: eyeball_im::ObservableVector
+    #[project = WindowProj]
+    pub struct Window<S, R>
+    where
+        S: Stream,
+        S::Item: VectorDiffContainer,
+    {
+        // The main stream to poll items from.
+        #[pin]
+        inner_stream: S,
+
+        // The range stream to poll new range values from.
+        #[pin]
+        range_stream: R,
+
+        // The buffered vector that is updated with the main stream's items.
+        // It's used to provide missing items, e.g. when the window moves or
+        // when values must be filled.
+        buffered_vector: Vector<VectorDiffContainerStreamElement<S>>,
+
+        // The current range.
+        //
+        // This is an option because it can be uninitialized, like the count
+        // of [`Skip`].
+        range: Option<Range<usize>>,
+
+        // This adapter is not a basic filter: It can produce many items per
+        // item of the underlying stream.
+        //
+        // Thus, if the item type is just `VectorDiff<_>` (non-bached, can't
+        // just add diffs to a poll_next result), we need a buffer to store the
+        // possible extra item in.
+        ready_values: VectorDiffContainerStreamWindowBuf<S>,
+    }
+}
+
+impl<S> Window<S, EmptyRangeStream>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+{
+    /// Create a new [`Window`] with the given (unlimited) initial values,
+    /// stream of `VectorDiff` updates for those values, and a fixed window
+    /// of at most `limit` values starting at `offset`.
+    ///
+    /// Returns the initial values as well as a stream of updates that ensure
+    /// that the resulting vector only includes the values of the window.
+    pub fn new(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        offset: usize,
+        limit: usize,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        Self::dynamic_with_initial_range(
+            initial_values,
+            inner_stream,
+            offset..offset.saturating_add(limit),
+            EmptyRangeStream,
+        )
+    }
+}
+
+impl<S, R> Window<S, R>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    R: Stream<Item = Range<usize>>,
+{
+    /// Create a new [`Window`] with the given (unlimited) initial values,
+    /// stream of `VectorDiff` updates for those values, and a stream of
+    /// ranges.
+    ///
+    /// This is equivalent to `dynamic_with_initial_range` where the
+    /// `initial_range` is empty, except that it doesn't return the windowed
+    /// vector as it would be empty anyways.
+    ///
+    /// Note that the returned `Window` won't produce anything until the first
+    /// range is produced by the range stream.
+    pub fn dynamic(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        range_stream: R,
+    ) -> Self {
+        Self {
+            inner_stream,
+            range_stream,
+            buffered_vector: initial_values,
+            range: None,
+            ready_values: Default::default(),
+        }
+    }
+
+    /// Create a new [`Window`] with the given (unlimited) initial values,
+    /// stream of `VectorDiff` updates for those values, and an initial
+    /// range as well as a stream of new range values.
+    pub fn dynamic_with_initial_range(
+        initial_values: Vector<VectorDiffContainerStreamElement<S>>,
+        inner_stream: S,
+        initial_range: Range<usize>,
+        range_stream: R,
+    ) -> (Vector<VectorDiffContainerStreamElement<S>>, Self) {
+        let window = window_of(&initial_values, &initial_range);
+
+        let stream = Self {
+            inner_stream,
+            range_stream,
+            buffered_vector: initial_values,
+            range: Some(initial_range),
+            ready_values: Default::default(),
+        };
+
+        (window, stream)
+    }
+}
+
+impl<S, R> Stream for Window<S, R>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    R: Stream<Item = Range<usize>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().poll_next(cx)
+    }
+}
+
+impl<S, R> VectorObserver<VectorDiffContainerStreamElement<S>> for Window<S, R>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    R: Stream<Item = Range<usize>>,
+{
+    type Stream = Self;
+
+    fn into_parts(self) -> (Vector<VectorDiffContainerStreamElement<S>>, Self::Stream) {
+        (self.buffered_vector.clone(), self)
+    }
+}
+
+impl<S, R> WindowProj<'_, S, R>
+where
+    S: Stream,
+    S::Item: VectorDiffContainer,
+    R: Stream<Item = Range<usize>>,
+{
+    fn poll_next(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        loop {
+            // First off, if any values are ready, return them.
+            if let Some(value) = S::Item::pop_from_window_buf(self.ready_values) {
+                return Poll::Ready(Some(value));
+            }
+
+            // Poll a new range value from `range_stream` before polling `inner_stream`.
+            while let Poll::Ready(Some(next_range)) = self.range_stream.as_mut().poll_next(cx) {
+                // Update the range value and emit `VectorDiff`s accordingly.
+                if let Some(diffs) = self.update_range(next_range) {
+                    return Poll::Ready(S::Item::extend_window_buf(diffs, self.ready_values));
+                }
+
+                // If `update_range` returned `None`, poll the range stream
+                // again.
+            }
+
+            // Poll `VectorDiff`s from the `inner_stream`.
+            let Some(diffs) = ready!(self.inner_stream.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            // Consume and apply the diffs if possible.
+            let ready = diffs.push_into_window_buf(self.ready_values, |diff| {
+                let range = self.range.clone();
+
+                // The values after the start of the window, before `diff` is
+                // applied. Only needed if there is a range.
+                let skipped =
+                    range.as_ref().map(|range| self.buffered_vector.clone().skeep(range.start));
+                let previous_length = self.buffered_vector.len();
+
+                // Update the `buffered_vector`. It's a replica of the original observed
+                // `Vector`. We need to maintain it in order to be able to produce valid
+                // `VectorDiff`s when items are missing.
+                diff.clone().apply(self.buffered_vector);
+
+                // Handle the `diff` if and only if there is a range.
+                match (range, skipped) {
+                    (Some(range), Some(skipped)) => {
+                        handle_diff(diff, range, previous_length, skipped, self.buffered_vector)
+                    }
+                    _ => SmallVec::new(),
+                }
+            });
+
+            if let Some(diff) = ready {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Else loop and poll the streams again.
+        }
+    }
+
+    /// Update the range value if necessary.
+    ///
+    /// * If the buffered vector is empty, it returns `None`.
+    /// * If the old and new windows overlap, the values leaving the window are
+    ///   removed with a `VectorDiff::Truncate` and `VectorDiff::PopFront`s, and
+    ///   the values entering the window are added with `VectorDiff::PushFront`s
+    ///   and a `VectorDiff::Append`.
+    /// * Otherwise, the whole window is replaced.
+    ///
+    /// It's OK to have a `new_range` that goes past the end of the `Vector`.
+    /// The `new_range` won't be capped.
+    fn update_range(
+        &mut self,
+        new_range: Range<usize>,
+    ) -> Option<Vec<VectorDiff<VectorDiffContainerStreamElement<S>>>> {
+        // Let's update the range.
+        let old_range = self.range.replace(new_range.clone());
+
+        if self.buffered_vector.is_empty() {
+            // If empty, nothing to do.
+            return None;
+        }
+
+        let new_window = clamp(&new_range, self.buffered_vector.len());
+        let old_window = match old_range {
+            // First time `range` is initialized.
+            None => {
+                return (!new_window.is_empty()).then(|| {
+                    vec![VectorDiff::Append {
+                        values: window_of(self.buffered_vector, &new_window),
+                    }]
+                });
+            }
+
+            // Other updates of `range`.
+            Some(old_range) => clamp(&old_range, self.buffered_vector.len()),
+        };
+
+        if old_window == new_window {
+            // Nothing to do.
+            return None;
+        }
+
+        if old_window.is_empty() {
+            return (!new_window.is_empty()).then(|| {
+                vec![VectorDiff::Append { values: window_of(self.buffered_vector, &new_window) }]
+            });
+        }
+
+        if new_window.is_empty() {
+            return Some(vec![VectorDiff::Clear]);
+        }
+
+        // The windows don't overlap, replace all the values.
+        if new_window.end <= old_window.start || old_window.end <= new_window.start {
+            return Some(vec![VectorDiff::Reset {
+                values: window_of(self.buffered_vector, &new_window),
+            }]);
+        }
+
+        let mut diffs = Vec::new();
+
+        // Remove the values leaving the window, at the back first so that
+        // the length is expressed relative to the old window.
+        if new_window.end < old_window.end {
+            diffs.push(VectorDiff::Truncate { length: new_window.end - old_window.start });
+        }
+
+        if old_window.start < new_window.start {
+            diffs.extend((old_window.start..new_window.start).map(|_| VectorDiff::PopFront));
+        }
+
+        // Add the values entering the window.
+        if new_window.start < old_window.start {
+            diffs.extend(
+                self.buffered_vector
+                    .iter()
+                    .take(old_window.start)
+                    .skip(new_window.start)
+                    .rev()
+                    .map(|value| VectorDiff::PushFront { value: value.clone() }),
+            );
+        }
+
+        if old_window.end < new_window.end {
+            diffs.push(VectorDiff::Append {
+                values: window_of(self.buffered_vector, &(old_window.end..new_window.end)),
+            });
+        }
+
+        Some(diffs)
+    }
+}
+
+/// Translate a `VectorDiff` of the buffered vector into `VectorDiff`s of the
+/// window given by `range`.
+///
+/// `skipped` holds the values from the start of the window, before `diff` is
+/// applied, and `buffered_vector` holds all the values, after `diff` is
+/// applied.
+fn handle_diff<T: Clone>(
+    diff: VectorDiff<T>,
+    range: Range<usize>,
+    previous_length: usize,
+    mut skipped: Vector<T>,
+    buffered_vector: &Vector<T>,
+) -> SmallVec<[VectorDiff<T>; 2]> {
+    let limit = range.end.saturating_sub(range.start);
+
+    // Skip the values before the window, then keep the first `limit` values
+    // of the rest.
+    skip::handle_diff(diff, range.start, previous_length, buffered_vector)
+        .into_iter()
+        .flat_map(|diff| {
+            let previous_length = skipped.len();
+            diff.clone().apply(&mut skipped);
+            head::handle_diff(diff, limit, previous_length, &skipped)
+        })
+        .collect()
+}
+
+/// Clamp `range` to a vector of length `len`.
+///
+/// A range whose end is before its start is clamped to an empty range.
+fn clamp(range: &Range<usize>, len: usize) -> Range<usize> {
+    let start = min(range.start, len);
+    let end = min(max(range.end, range.start), len);
+
+    start..end
+}
+
+/// Get the values of `values` that are inside `range`.
+fn window_of<T: Clone>(values: &Vector<T>, range: &Range<usize>) -> Vector<T> {
+    let range = clamp(range, values.len());
+
+    values.clone().skeep(range.start).take(range.end - range.start)
+}
+
+/// An empty stream with an item type of `Range<usize>`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct EmptyRangeStream;
+
+impl Stream for EmptyRangeStream {
+    type Item = Range<usize>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(None)
+    }
+}
@@ -0,0 +1,285 @@
+use eyeball_im::ObservableVector;
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::vector;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+#[test]
+fn count_where() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4]);
+    let (count, mut sub) = ob.subscribe().count_where(|value| value % 2 == 0);
+    assert_eq!(count, 2);
+
+    ob.push_back(6);
+    assert_next_eq!(sub, 3);
+
+    // Odd values don't change the count.
+    ob.push_front(7);
+    ob.insert(2, 9);
+    assert_pending!(sub);
+
+    ob.set(0, 8);
+    assert_next_eq!(sub, 4);
+
+    // Replacing an even value by an even value doesn't change the count.
+    ob.set(0, 10);
+    assert_pending!(sub);
+
+    // [10, 1, 9, 2, 3, 4, 6]
+    ob.truncate(4);
+    assert_next_eq!(sub, 2);
+
+    ob.remove(1);
+    assert_pending!(sub);
+
+    ob.pop_front();
+    assert_next_eq!(sub, 1);
+
+    ob.clear();
+    assert_next_eq!(sub, 0);
+
+    // Clearing an empty vector.
+    ob.clear();
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn sum_by() {
+    let mut ob = ObservableVector::<(char, i32)>::from(vector![('a', 1), ('b', 0), ('c', 5)]);
+    let (sum, mut sub) = ob.subscribe().sum_by(|(_, count)| *count);
+    assert_eq!(sum, 6);
+
+    ob.push_back(('d', 2));
+    assert_next_eq!(sub, 8);
+
+    // Zeros don't change the sum.
+    ob.push_back(('e', 0));
+    ob.pop_back();
+    assert_pending!(sub);
+
+    ob.pop_front();
+    assert_next_eq!(sub, 7);
+
+    // Values that cancel each other out don't change the sum.
+    ob.append(vector![('e', 3), ('f', -3)]);
+    assert_pending!(sub);
+
+    ob.truncate(1);
+    assert_next_eq!(sub, 0);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn min_by_key() {
+    let mut ob = ObservableVector::<(char, u32)>::from(vector![('a', 3), ('b', 1), ('c', 1)]);
+    let (min, mut sub) = ob.subscribe().min_by_key(|(_, key)| *key);
+    // The first value is returned amongst equal keys.
+    assert_eq!(min, Some(('b', 1)));
+
+    // A larger value doesn't change the minimum.
+    ob.push_front(('d', 5));
+    assert_pending!(sub);
+
+    // An equal value before the minimum becomes the minimum.
+    ob.insert(1, ('e', 1));
+    assert_next_eq!(sub, Some(('e', 1)));
+
+    // An equal value after the minimum doesn't change the minimum.
+    ob.push_back(('f', 1));
+    assert_pending!(sub);
+
+    ob.push_back(('g', 0));
+    assert_next_eq!(sub, Some(('g', 0)));
+
+    // [d5, e1, a3, b1, c1, f1, g0]
+    ob.pop_back();
+    assert_next_eq!(sub, Some(('e', 1)));
+
+    ob.set(1, ('h', 4));
+    assert_next_eq!(sub, Some(('b', 1)));
+
+    // Replacing the minimum by an equal value doesn't produce a result.
+    ob.set(3, ('b', 1));
+    assert_pending!(sub);
+
+    // [d5, h4, a3, b1, c1, f1]
+    ob.truncate(4);
+    assert_pending!(sub);
+    ob.truncate(3);
+    assert_next_eq!(sub, Some(('a', 3)));
+
+    ob.clear();
+    assert_next_eq!(sub, None);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn max_by_key() {
+    let mut ob = ObservableVector::<(char, u32)>::from(vector![('a', 3), ('b', 1), ('c', 3)]);
+    let (max, mut sub) = ob.subscribe().max_by_key(|(_, key)| *key);
+    // The last value is returned amongst equal keys.
+    assert_eq!(max, Some(('c', 3)));
+
+    // An equal value before the maximum doesn't change the maximum.
+    ob.push_front(('d', 3));
+    assert_pending!(sub);
+
+    // An equal value after the maximum becomes the maximum.
+    ob.push_back(('e', 3));
+    assert_next_eq!(sub, Some(('e', 3)));
+
+    ob.remove(4);
+    assert_next_eq!(sub, Some(('c', 3)));
+
+    ob.truncate(2);
+    assert_next_eq!(sub, Some(('a', 3)));
+
+    ob.pop_front();
+    ob.pop_front();
+    assert_next_eq!(sub, None);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn fold() {
+    let mut ob = ObservableVector::<&str>::from(vector!["a", "bb"]);
+    let (lengths, mut sub) = ob.subscribe().fold(
+        (0, 0),
+        |(count, len), value| {
+            *count += 1;
+            *len += value.len();
+        },
+        |(count, len), value| {
+            *count -= 1;
+            *len -= value.len();
+        },
+    );
+    assert_eq!(lengths, (2, 3));
+
+    ob.push_back("ccc");
+    assert_next_eq!(sub, (3, 6));
+
+    ob.set(0, "dddd");
+    assert_next_eq!(sub, (3, 9));
+
+    // Replacing a value by one with the same length doesn't change the result.
+    ob.set(0, "eeee");
+    assert_pending!(sub);
+
+    ob.clear();
+    assert_next_eq!(sub, (0, 0));
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3]);
+    let (sum, mut sub) = ob.subscribe().batched().sum_by(|value| *value);
+    assert_eq!(sum, 6);
+
+    // A single value is produced per batch.
+    let mut txn = ob.transaction();
+    txn.push_back(4);
+    txn.push_front(5);
+    txn.remove(1);
+    txn.commit();
+    assert_next_eq!(sub, 14);
+    assert_pending!(sub);
+}
+
+#[test]
+fn reset() {
+    let mut ob = ObservableVector::<u32>::with_capacity(1);
+    let (min, mut sub) = ob.subscribe().min_by_key(|value| *value);
+    assert_eq!(min, None);
+
+    // Lagging behind makes the subscriber receive a reset.
+    ob.append(vector![3, 1, 2]);
+    ob.push_back(0);
+    assert_next_eq!(sub, Some(0));
+    assert_pending!(sub);
+}
+
+/// A deterministic pseudo-random number generator.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % bound
+    }
+}
+
+#[test]
+fn random() {
+    let mut rng = Rng(0x2545_f491_u32);
+    let mut ob = ObservableVector::<(usize, usize)>::new();
+    let (mut count, mut count_sub) = ob.subscribe().count_where(|(_, key)| key % 3 == 0);
+    let (mut sum, mut sum_sub) = ob.subscribe().sum_by(|(_, key)| *key);
+    let (mut min, mut min_sub) = ob.subscribe().min_by_key(|(_, key)| *key);
+    let (mut max, mut max_sub) = ob.subscribe().max_by_key(|(_, key)| *key);
+
+    for id in 0..2000 {
+        let len = ob.len();
+        let value = (id, rng.next(10));
+
+        match rng.next(11) {
+            0 => ob.push_front(value),
+            1 => ob.push_back(value),
+            2 if len > 0 => {
+                ob.pop_front();
+            }
+            3 if len > 0 => {
+                ob.pop_back();
+            }
+            4 => ob.insert(rng.next(len + 1), value),
+            5 if len > 0 => {
+                ob.remove(rng.next(len));
+            }
+            6 if len > 0 => {
+                ob.set(rng.next(len), value);
+            }
+            7 => ob.append((0..rng.next(4)).map(|i| (id, (value.1 + i) % 10)).collect()),
+            8 => ob.truncate(rng.next(len + 1)),
+            9 if rng.next(10) == 0 => ob.clear(),
+            _ => {
+                let values = (0..rng.next(15)).map(|i| (id, (value.1 + i) % 10)).collect();
+                let mut txn = ob.transaction();
+                txn.clear();
+                txn.append(values);
+                txn.commit();
+            }
+        }
+
+        while let Some(Some(value)) = count_sub.next().now_or_never() {
+            count = value;
+        }
+        while let Some(Some(value)) = sum_sub.next().now_or_never() {
+            sum = value;
+        }
+        while let Some(Some(value)) = min_sub.next().now_or_never() {
+            min = value;
+        }
+        while let Some(Some(value)) = max_sub.next().now_or_never() {
+            max = value;
+        }
+
+        assert_eq!(count, ob.iter().filter(|(_, key)| key % 3 == 0).count(), "after step {id}");
+        assert_eq!(sum, ob.iter().map(|(_, key)| key).sum::<usize>(), "after step {id}");
+        assert_eq!(min, ob.iter().min_by_key(|(_, key)| *key).copied(), "after step {id}");
+        assert_eq!(max, ob.iter().max_by_key(|(_, key)| *key).copied(), "after step {id}");
+    }
+}
@@ -0,0 +1,173 @@
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{Concat, VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+#[test]
+fn chain() {
+    let mut a = ObservableVector::<u32>::from(vector![1, 2]);
+    let mut b = ObservableVector::<u32>::from(vector![10]);
+    let (values, mut sub) = a.subscribe().chain(b.subscribe());
+    assert_eq!(values, vector![1, 2, 10]);
+
+    a.push_front(0);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 0 });
+    a.push_back(3);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 3, value: 3 });
+    b.push_front(9);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 4, value: 9 });
+    b.push_back(11);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 11 });
+    // [0, 1, 2, 3, 9, 10, 11]
+
+    a.pop_back();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 3 });
+    b.pop_front();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 3 });
+    a.pop_front();
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    b.pop_back();
+    assert_next_eq!(sub, VectorDiff::PopBack);
+    // [1, 2, 10]
+
+    a.insert(1, 5);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 5 });
+    b.set(0, 20);
+    assert_next_eq!(sub, VectorDiff::Set { index: 3, value: 20 });
+    b.remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 3 });
+    // [1, 5, 2]
+
+    // `b` is empty, so `a` is at the end now.
+    a.push_back(6);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 6 });
+    a.append(vector![7, 8]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![7, 8] });
+    a.truncate(2);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 2 });
+    assert_pending!(sub);
+
+    drop(a);
+    assert_pending!(sub);
+    drop(b);
+    assert_closed!(sub);
+}
+
+#[test]
+fn append_truncate_clear_in_the_middle() {
+    let mut a = ObservableVector::<u32>::from(vector![1]);
+    let b = ObservableVector::<u32>::from(vector![10]);
+    let (_, mut sub) = Concat::new([a.subscribe(), b.subscribe()]);
+
+    a.append(vector![2, 3]);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 2 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 3 });
+
+    a.truncate(1);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+
+    a.clear();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn clear_and_reset() {
+    let mut a = ObservableVector::<u32>::with_capacity(1);
+    let mut b = ObservableVector::<u32>::from(vector![10, 11]);
+    let mut c = ObservableVector::<u32>::new();
+    let (_, mut sub) = Concat::new([a.subscribe(), b.subscribe(), c.subscribe()]);
+
+    // Only `b` has items.
+    b.clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+    b.append(vector![12, 13]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![12, 13] });
+
+    c.push_back(20);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 20 });
+
+    // Lagging behind makes `a` produce a reset.
+    a.push_back(1);
+    a.push_back(2);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 1 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 2 });
+
+    // [1, 2, 12, 13, 20]
+    b.clear();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+
+    // `c` is at the end.
+    c.clear();
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 2 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn reset_in_the_middle() {
+    let mut a = ObservableVector::<u32>::from(vector![1]);
+    let mut b = ObservableVector::<u32>::with_capacity(1);
+    let c = ObservableVector::<u32>::from(vector![20]);
+    let (mut values, mut sub) = Concat::new([a.subscribe(), b.subscribe(), c.subscribe()]);
+
+    b.push_back(10);
+    b.push_back(11);
+    let expected =
+        [VectorDiff::Insert { index: 1, value: 10 }, VectorDiff::Insert { index: 2, value: 11 }];
+    for diff in expected {
+        assert_next_eq!(sub, diff.clone());
+        diff.apply(&mut values);
+    }
+
+    b.push_back(12);
+    b.pop_front();
+    let expected = [
+        VectorDiff::Remove { index: 1 },
+        VectorDiff::Remove { index: 1 },
+        VectorDiff::Insert { index: 1, value: 11 },
+        VectorDiff::Insert { index: 2, value: 12 },
+    ];
+    for diff in expected {
+        assert_next_eq!(sub, diff.clone());
+        diff.apply(&mut values);
+    }
+
+    a.push_back(2);
+    let diff = sub.next().now_or_never().unwrap().unwrap();
+    assert_eq!(diff, VectorDiff::Insert { index: 1, value: 2 });
+    diff.apply(&mut values);
+    assert_pending!(sub);
+
+    assert_eq!(values, vector![1, 2, 11, 12, 20]);
+}
+
+#[test]
+fn batched() {
+    let mut a = ObservableVector::<u32>::from(vector![1, 2]);
+    let mut b = ObservableVector::<u32>::from(vector![10]);
+    let (mut values, mut sub) = a.subscribe().batched().chain(b.subscribe().batched());
+
+    let mut txn = a.transaction();
+    txn.push_back(3);
+    txn.clear();
+    txn.append(vector![4, 5]);
+    txn.commit();
+
+    let mut txn = b.transaction();
+    txn.push_front(9);
+    txn.truncate(1);
+    txn.commit();
+
+    while let Some(Some(diffs)) = sub.next().now_or_never() {
+        for diff in diffs {
+            diff.apply(&mut values);
+        }
+    }
+
+    let expected: Vector<_> = a.iter().chain(b.iter()).copied().collect();
+    assert_eq!(values, expected);
+    assert_eq!(values, vector![4, 5, 9]);
+}
@@ -0,0 +1,198 @@
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+/// Values are `(key, payload)` pairs.
+type Value = (u8, u8);
+
+fn key(&(key, _): &Value) -> u8 {
+    key
+}
+
+/// The expected deduplicated values of `ob`.
+fn expected(ob: &ObservableVector<Value>) -> Vector<Value> {
+    let mut keys = Vec::new();
+    let mut values = Vector::new();
+    for value in ob.iter() {
+        if !keys.contains(&key(value)) {
+            keys.push(key(value));
+            values.push_back(*value);
+        }
+    }
+    values
+}
+
+#[test]
+fn push_and_pop() {
+    let mut ob = ObservableVector::<Value>::from(vector![(1, 0), (1, 1)]);
+    let (values, mut sub) = ob.subscribe().dedup_by_key(key);
+    assert_eq!(values, vector![(1, 0)]);
+
+    ob.push_back((2, 2));
+    assert_next_eq!(sub, VectorDiff::PushBack { value: (2, 2) });
+    ob.push_back((2, 3));
+    assert_pending!(sub);
+    ob.push_front((3, 4));
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: (3, 4) });
+    // [3/4, 1/0, 1/1, 2/2, 2/3] => [3/4, 1/0, 2/2]
+
+    ob.push_front((2, 5));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: (2, 5) });
+    // [2/5, 3/4, 1/0, 1/1, 2/2, 2/3] => [2/5, 3/4, 1/0]
+
+    ob.pop_back();
+    assert_pending!(sub);
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: (2, 2) });
+    // [3/4, 1/0, 1/1, 2/2] => [3/4, 1/0, 2/2]
+
+    ob.pop_back();
+    assert_next_eq!(sub, VectorDiff::PopBack);
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::Set { index: 0, value: (1, 1) });
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn insert_set_remove() {
+    let mut ob = ObservableVector::<Value>::from(vector![(1, 0), (2, 1), (1, 2)]);
+    let (_, mut sub) = ob.subscribe().dedup_by_key(key);
+
+    // Hidden.
+    ob.insert(1, (1, 3));
+    assert_pending!(sub);
+    // Before the visible value with the same key, at the same position.
+    ob.insert(0, (1, 4));
+    assert_next_eq!(sub, VectorDiff::Set { index: 0, value: (1, 4) });
+    // [1/4, 1/0, 1/3, 2/1, 1/2] => [1/4, 2/1]
+
+    // Same key, visible.
+    ob.set(3, (2, 5));
+    assert_next_eq!(sub, VectorDiff::Set { index: 1, value: (2, 5) });
+    // Same key, hidden.
+    ob.set(4, (1, 6));
+    assert_pending!(sub);
+    // New key, hidden value becomes visible.
+    ob.set(2, (3, 7));
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: (3, 7) });
+    // [1/4, 1/0, 3/7, 2/5, 1/6] => [1/4, 3/7, 2/5]
+
+    // New key, visible value is replaced by the next one with its old key.
+    ob.set(0, (4, 8));
+    assert_next_eq!(sub, VectorDiff::Set { index: 0, value: (1, 0) });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: (4, 8) });
+    // [4/8, 1/0, 3/7, 2/5, 1/6] => [4/8, 1/0, 3/7, 2/5]
+
+    // Existing key, visible value is hidden.
+    ob.set(2, (2, 9));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+    assert_next_eq!(sub, VectorDiff::Set { index: 2, value: (2, 9) });
+    // [4/8, 1/0, 2/9, 2/5, 1/6] => [4/8, 1/0, 2/9]
+
+    ob.remove(2);
+    assert_next_eq!(sub, VectorDiff::Set { index: 2, value: (2, 5) });
+    ob.remove(3);
+    assert_pending!(sub);
+    ob.remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_pending!(sub);
+    assert_eq!(expected(&ob), vector![(1, 0), (2, 5)]);
+}
+
+#[test]
+fn append_truncate_clear_reset() {
+    let mut ob = ObservableVector::<Value>::with_capacity(1);
+    let (_, mut sub) = ob.subscribe().dedup_by_key(key);
+
+    ob.append(vector![(1, 0), (2, 1), (1, 2)]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![(1, 0), (2, 1)] });
+    ob.append(vector![(2, 3), (3, 4), (3, 5)]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![(3, 4)] });
+    ob.append(vector![(1, 6)]);
+    assert_pending!(sub);
+
+    ob.truncate(5);
+    assert_pending!(sub);
+    ob.truncate(3);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 2 });
+
+    ob.clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+    ob.clear();
+    assert_pending!(sub);
+
+    ob.push_back((1, 7));
+    ob.push_back((1, 8));
+    ob.push_back((2, 9));
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![(1, 7), (2, 9)] });
+    ob.remove(0);
+    assert_next_eq!(sub, VectorDiff::Set { index: 0, value: (1, 8) });
+    assert_pending!(sub);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::<Value>::from(vector![(1, 0)]);
+    let (mut values, mut sub) = ob.subscribe().batched().dedup_by_key(key);
+
+    let mut txn = ob.transaction();
+    txn.push_back((1, 1));
+    txn.push_back((2, 2));
+    txn.remove(0);
+    txn.push_back((2, 3));
+    txn.commit();
+
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    assert_eq!(
+        diffs,
+        vec![VectorDiff::PushBack { value: (2, 2) }, VectorDiff::Set { index: 0, value: (1, 1) }]
+    );
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, expected(&ob));
+    assert_pending!(sub);
+}
+
+#[test]
+fn random() {
+    // Simple deterministic pseudo-random generator (xorshift).
+    let mut state = 0x2545_f491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let mut ob = ObservableVector::<Value>::new();
+    let (mut values, mut sub) = ob.subscribe().dedup_by_key(key);
+
+    for i in 0..2000 {
+        let value = ((next() % 4) as u8, (i % 256) as u8);
+        let len = ob.len();
+        match next() % 8 {
+            0 | 1 => ob.insert(next() as usize % (len + 1), value),
+            2 if len > 0 => _ = ob.set(next() as usize % len, value),
+            3 if len > 0 => _ = ob.remove(next() as usize % len),
+            4 => ob.push_front(value),
+            5 => ob.push_back(value),
+            6 => _ = ob.pop_front(),
+            _ => _ = ob.pop_back(),
+        }
+
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        assert_eq!(values, expected(&ob));
+    }
+}
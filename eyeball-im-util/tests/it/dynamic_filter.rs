@@ -0,0 +1,156 @@
+use eyeball::Observable;
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+// All filters of a stream need to have the same type. This returns a filter
+// for values that are multiples of `modulo` and less than `max`.
+fn multiple_below(modulo: u32, max: u32) -> impl Fn(&u32) -> bool + Clone {
+    move |&i| i % modulo == 0 && i < max
+}
+
+#[test]
+fn nothing_until_first_filter() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3]);
+    let mut filter = Observable::new(multiple_below(1, 3));
+    let mut sub = ob.subscribe().dynamic_filter(Observable::subscribe(&filter));
+
+    ob.push_back(0);
+    // The initial value of the filter observable is not yielded by the
+    // subscriber, only updates are.
+    assert_pending!(sub);
+
+    _ = Observable::set(&mut filter, multiple_below(2, u32::MAX));
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![2, 0] });
+    assert_pending!(sub);
+
+    ob.push_front(4);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 4 });
+    ob.push_back(5);
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn update_filter() {
+    let ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5, 6]);
+    let mut filter = Observable::new(multiple_below(1, 4));
+    let (values, mut sub) = ob
+        .subscribe()
+        .dynamic_filter_with_initial_value(multiple_below(1, 4), Observable::subscribe(&filter));
+    assert_eq!(values, vector![1, 2, 3]);
+
+    // [1, 2, 3] -> [2, 4, 6]
+    _ = Observable::set(&mut filter, multiple_below(2, u32::MAX));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 4 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 6 });
+    assert_pending!(sub);
+
+    // Nothing visible anymore.
+    _ = Observable::set(&mut filter, multiple_below(1, 1));
+    assert_next_eq!(sub, VectorDiff::Clear);
+
+    // Still nothing visible, no update.
+    _ = Observable::set(&mut filter, multiple_below(1, 0));
+    assert_pending!(sub);
+
+    // Everything visible.
+    _ = Observable::set(&mut filter, multiple_below(1, 10));
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![1, 2, 3, 4, 5, 6] });
+
+    // Same items visible, no update.
+    _ = Observable::set(&mut filter, multiple_below(1, 20));
+    assert_pending!(sub);
+}
+
+#[test]
+fn updates_use_latest_filter() {
+    let mut ob = ObservableVector::<u32>::new();
+    let mut filter = Observable::new(multiple_below(1, 10));
+    let (_, mut sub) = ob
+        .subscribe()
+        .dynamic_filter_with_initial_value(multiple_below(1, 10), Observable::subscribe(&filter));
+
+    ob.append(vector![1, 2, 20]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![1, 2] });
+
+    _ = Observable::set(&mut filter, multiple_below(2, u32::MAX));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 20 });
+
+    ob.set(0, 6);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 6 });
+    ob.remove(1);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4]);
+    let mut filter = Observable::new(multiple_below(1, 3));
+    let (mut values, mut sub) = ob
+        .subscribe()
+        .batched()
+        .dynamic_filter_with_initial_value(multiple_below(1, 3), Observable::subscribe(&filter));
+
+    _ = Observable::set(&mut filter, multiple_below(2, u32::MAX));
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    assert_eq!(
+        diffs,
+        vec![VectorDiff::Remove { index: 0 }, VectorDiff::Insert { index: 1, value: 4 }]
+    );
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, vector![2, 4]);
+
+    let mut txn = ob.transaction();
+    txn.push_back(6);
+    txn.push_back(7);
+    txn.insert(0, 8);
+    txn.commit();
+    assert_next_eq!(
+        sub,
+        vec![VectorDiff::PushBack { value: 6 }, VectorDiff::Insert { index: 0, value: 8 }]
+    );
+    assert_pending!(sub);
+}
+
+#[test]
+fn mirrors_the_vector() {
+    let mut ob = ObservableVector::<u32>::from((0..20).collect::<Vector<_>>());
+    let mut filter_ob = Observable::new(multiple_below(1, 5));
+    let (mut values, mut sub) = ob
+        .subscribe()
+        .dynamic_filter_with_initial_value(multiple_below(1, 5), Observable::subscribe(&filter_ob));
+
+    let check = |values: &Vector<u32>, ob: &ObservableVector<u32>, f: &dyn Fn(&u32) -> bool| {
+        let expected: Vector<_> = ob.iter().copied().filter(|i| f(i)).collect();
+        assert_eq!(*values, expected);
+    };
+
+    let filters = [(2, 20), (3, 20), (1, 12)];
+    for (modulo, max) in filters {
+        let f = multiple_below(modulo, max);
+        _ = Observable::set(&mut filter_ob, f.clone());
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        check(&values, &ob, &f);
+
+        ob.remove(3);
+        ob.insert(7, 30);
+        ob.set(0, 42);
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        check(&values, &ob, &f);
+    }
+}
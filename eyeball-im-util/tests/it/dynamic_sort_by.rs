@@ -0,0 +1,150 @@
+use std::cmp::Ordering;
+
+use eyeball::Observable;
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+// All comparison functions of a stream need to have the same type. This
+// returns a comparison function that sorts `first` before all other values,
+// and the other values in ascending or descending order.
+fn first_then(first: u32, descending: bool) -> impl Fn(&u32, &u32) -> Ordering + Clone {
+    move |a, b| {
+        let ord = if descending { b.cmp(a) } else { a.cmp(b) };
+        (*a != first).cmp(&(*b != first)).then(ord)
+    }
+}
+
+#[test]
+fn nothing_until_first_compare() {
+    let mut ob = ObservableVector::<u32>::from(vector![3, 1, 2]);
+    let mut compare = Observable::new(first_then(0, false));
+    let mut sub = ob.subscribe().dynamic_sort_by(Observable::subscribe(&compare));
+
+    ob.push_back(0);
+    ob.remove(1);
+    assert_pending!(sub);
+
+    _ = Observable::set(&mut compare, first_then(0, true));
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![0, 3, 2] });
+    assert_pending!(sub);
+
+    ob.push_back(5);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 5 });
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn moves() {
+    let mut ob = ObservableVector::<u32>::from(vector![5, 1, 4, 2, 3]);
+    let mut compare = Observable::new(first_then(u32::MAX, false));
+    let (values, mut sub) = ob.subscribe().dynamic_sort_by_with_initial_value(
+        first_then(u32::MAX, false),
+        Observable::subscribe(&compare),
+    );
+    assert_eq!(values, vector![1, 2, 3, 4, 5]);
+
+    // Only 3 moves.
+    _ = Observable::set(&mut compare, first_then(3, false));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 2 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 3 });
+
+    // Two moves.
+    _ = Observable::set(&mut compare, first_then(5, false));
+    assert_next_eq!(sub, VectorDiff::Remove { index: 4 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 5 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 3, value: 3 });
+    assert_pending!(sub);
+
+    // Same order, nothing to do.
+    _ = Observable::set(&mut compare, first_then(5, false));
+    assert_pending!(sub);
+
+    // Updates use the latest comparison function.
+    ob.push_back(0);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 0 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn reset_if_most_items_move() {
+    let ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4]);
+    let mut compare = Observable::new(first_then(u32::MAX, false));
+    let (_, mut sub) = ob.subscribe().dynamic_sort_by_with_initial_value(
+        first_then(u32::MAX, false),
+        Observable::subscribe(&compare),
+    );
+
+    _ = Observable::set(&mut compare, first_then(u32::MAX, true));
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![4, 3, 2, 1] });
+    assert_pending!(sub);
+}
+
+#[test]
+fn empty() {
+    let mut ob = ObservableVector::<u32>::new();
+    let mut compare = Observable::new(first_then(u32::MAX, false));
+    let mut sub = ob.subscribe().dynamic_sort_by(Observable::subscribe(&compare));
+
+    _ = Observable::set(&mut compare, first_then(u32::MAX, true));
+    assert_pending!(sub);
+
+    ob.append(vector![1, 3, 2]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![3, 2, 1] });
+    assert_pending!(sub);
+}
+
+#[test]
+fn batched() {
+    let ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let mut compare = Observable::new(first_then(u32::MAX, false));
+    let (_, mut sub) = ob.subscribe().batched().dynamic_sort_by_with_initial_value(
+        first_then(u32::MAX, false),
+        Observable::subscribe(&compare),
+    );
+
+    _ = Observable::set(&mut compare, first_then(4, false));
+    assert_next_eq!(
+        sub,
+        vec![VectorDiff::Remove { index: 3 }, VectorDiff::Insert { index: 0, value: 4 }]
+    );
+    assert_pending!(sub);
+}
+
+#[test]
+fn mirrors_the_vector() {
+    let mut ob =
+        ObservableVector::<u32>::from((0..20).map(|i| (i * 7) % 20).collect::<Vector<_>>());
+    let mut compare = Observable::new(first_then(u32::MAX, false));
+    let (mut values, mut sub) = ob.subscribe().dynamic_sort_by_with_initial_value(
+        first_then(u32::MAX, false),
+        Observable::subscribe(&compare),
+    );
+
+    for (first, descending) in [(3, false), (7, false), (7, true), (0, true), (12, false)] {
+        let f = first_then(first, descending);
+        _ = Observable::set(&mut compare, f.clone());
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        let mut expected = ob.clone();
+        expected.sort_by(&f);
+        assert_eq!(values, expected);
+
+        ob.remove(2);
+        ob.push_back(first + 30);
+        ob.set(5, first + 40);
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        let mut expected = ob.clone();
+        expected.sort_by(&f);
+        assert_eq!(values, expected);
+    }
+}
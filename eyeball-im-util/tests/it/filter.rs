@@ -319,3 +319,22 @@ fn reset() {
     ob.remove(0);
     assert_pending!(sub);
 }
+
+#[test]
+fn reset_without_matches() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2]);
+    let (values, mut sub) = ob.subscribe().filter(|&i| i % 2 == 0);
+    assert_eq!(values, vector![2]);
+
+    // The compacted transaction is a single reset without matching values.
+    let mut txn = ob.transaction();
+    txn.clear();
+    txn.push_back(3);
+    txn.commit_compacted();
+
+    assert_next_eq!(sub, VectorDiff::Clear);
+    assert_pending!(sub);
+
+    ob.push_back(4);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 4 });
+}
@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex};
+
+use eyeball_im::{ObservableVector, VectorDiff, VectorSubscriber};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+type Section = Arc<Mutex<ObservableVector<u32>>>;
+
+fn section(values: Vector<u32>) -> Section {
+    Arc::new(Mutex::new(ObservableVector::from(values)))
+}
+
+fn subscribe(section: &Section) -> VectorSubscriber<u32> {
+    section.lock().unwrap().subscribe()
+}
+
+fn flattened(sections: &ObservableVector<Section>) -> Vector<u32> {
+    sections.iter().flat_map(|section| section.lock().unwrap().clone()).collect()
+}
+
+#[test]
+fn outer_changes() {
+    let a = section(vector![1, 2]);
+    let b = section(vector![]);
+    let c = section(vector![3]);
+
+    let mut ob = ObservableVector::<Section>::new();
+    let (values, mut sub) = ob.subscribe().flatten(subscribe);
+    assert!(values.is_empty());
+
+    ob.push_back(a.clone());
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![1, 2] });
+    ob.push_back(b.clone());
+    assert_pending!(sub);
+    ob.push_front(c.clone());
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 3 });
+    // [c, a, b] => [3, 1, 2]
+
+    ob.insert(2, c.clone());
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![3] });
+    // [c, a, c, b] => [3, 1, 2, 3]
+
+    ob.set(1, b.clone());
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    // [c, b, c, b] => [3, 3]
+
+    ob.set(1, a.clone());
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 1 });
+    assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 2 });
+    // [c, a, c, b] => [3, 1, 2, 3]
+
+    ob.pop_back();
+    assert_pending!(sub);
+    ob.pop_back();
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 3 });
+    ob.remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    // [a] => [1, 2]
+
+    ob.append(vector![b.clone(), c.clone(), a.clone()]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![3, 1, 2] });
+    // [a, b, c, a] => [1, 2, 3, 1, 2]
+
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    // [b, c, a] => [3, 1, 2]
+
+    ob.truncate(2);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 1 });
+    ob.truncate(1);
+    assert_next_eq!(sub, VectorDiff::Clear);
+    ob.clear();
+    assert_pending!(sub);
+}
+
+#[test]
+fn inner_changes() {
+    let a = section(vector![1]);
+    let b = section(vector![]);
+    let c = section(vector![10]);
+
+    let ob = ObservableVector::from(vector![a.clone(), b.clone(), c.clone()]);
+    let (values, mut sub) = ob.subscribe().flatten(subscribe);
+    assert_eq!(values, vector![1, 10]);
+
+    b.lock().unwrap().push_back(5);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 5 });
+    a.lock().unwrap().push_front(0);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 0 });
+    c.lock().unwrap().push_back(11);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 11 });
+    // [0, 1, 5, 10, 11]
+
+    b.lock().unwrap().set(0, 6);
+    assert_next_eq!(sub, VectorDiff::Set { index: 2, value: 6 });
+    c.lock().unwrap().remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 3 });
+    a.lock().unwrap().clear();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+    c.lock().unwrap().clear();
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 1 });
+    b.lock().unwrap().clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+    assert_pending!(sub);
+
+    assert_eq!(flattened(&ob), vector![]);
+}
+
+#[test]
+fn same_section_twice() {
+    let a = section(vector![1]);
+
+    let mut ob = ObservableVector::from(vector![a.clone()]);
+    let (mut values, mut sub) = ob.subscribe().flatten(subscribe);
+
+    ob.push_back(a.clone());
+    a.lock().unwrap().push_back(2);
+
+    while let Some(Some(diff)) = sub.next().now_or_never() {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, flattened(&ob));
+    assert_eq!(values, vector![1, 2, 1, 2]);
+}
+
+#[test]
+fn reset() {
+    let a = section(vector![1]);
+    let b = section(vector![2, 3]);
+
+    let mut ob = ObservableVector::with_capacity(1);
+    let (_, mut sub) = ob.subscribe().flatten(subscribe);
+
+    ob.push_back(a.clone());
+    ob.push_back(b.clone());
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![1, 2, 3] });
+
+    // The sections have been subscribed to again.
+    b.lock().unwrap().pop_front();
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn batched() {
+    let a = section(vector![1]);
+    let b = section(vector![10]);
+
+    let mut ob = ObservableVector::from(vector![a.clone()]);
+    let (mut values, mut sub) = ob
+        .subscribe()
+        .batched()
+        .flatten(|section: &Section| section.lock().unwrap().subscribe().batched());
+
+    let mut txn = ob.transaction();
+    txn.push_front(b.clone());
+    txn.push_back(b.clone());
+    txn.remove(1);
+    txn.commit();
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    assert_eq!(
+        diffs,
+        vec![
+            VectorDiff::Insert { index: 0, value: 10 },
+            VectorDiff::Append { values: vector![10] },
+            VectorDiff::Remove { index: 1 },
+        ]
+    );
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+
+    {
+        let mut b = b.lock().unwrap();
+        let mut txn = b.transaction();
+        txn.push_back(11);
+        txn.push_front(9);
+        txn.commit();
+    }
+
+    while let Some(Some(diffs)) = sub.next().now_or_never() {
+        for diff in diffs {
+            diff.apply(&mut values);
+        }
+    }
+    assert_eq!(values, flattened(&ob));
+    assert_eq!(values, vector![9, 10, 11, 9, 10, 11]);
+}
+
+#[test]
+fn end() {
+    let a = section(vector![1]);
+
+    let ob = ObservableVector::from(vector![a.clone()]);
+    let (_, mut sub) = ob.subscribe().flatten(subscribe);
+
+    drop(ob);
+    assert_pending!(sub);
+
+    // The inner vectors can still be changed after the outer one is gone.
+    a.lock().unwrap().push_back(2);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 2 });
+
+    drop(a);
+    assert_closed!(sub);
+}
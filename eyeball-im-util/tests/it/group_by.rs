@@ -0,0 +1,233 @@
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{Group, VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, Stream, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_pending};
+
+fn tens(value: &u32) -> u32 {
+    value / 10
+}
+
+/// The keys and values of the given groups.
+fn contents(groups: &Vector<Group<u32, u32>>) -> Vec<(u32, Vector<u32>)> {
+    groups.iter().map(|group| (*group.key(), group.values())).collect()
+}
+
+/// The expected keys and values of the groups of `ob`.
+fn expected(ob: &ObservableVector<u32>) -> Vec<(u32, Vector<u32>)> {
+    let mut groups: Vec<(u32, Vector<u32>)> = Vec::new();
+    for &value in ob.iter() {
+        match groups.iter_mut().find(|(key, _)| *key == tens(&value)) {
+            Some((_, values)) => values.push_back(value),
+            None => groups.push((tens(&value), vector![value])),
+        }
+    }
+    groups.sort_by_key(|(key, _)| *key);
+    groups
+}
+
+fn next_diff<S>(sub: &mut S) -> VectorDiff<(u32, Vector<u32>)>
+where
+    S: Stream<Item = VectorDiff<Group<u32, u32>>> + Unpin,
+{
+    let diff = sub.next().now_or_never().unwrap().unwrap();
+    diff.map(|group| (*group.key(), group.values()))
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut ob = ObservableVector::<u32>::from(vector![12, 21, 15]);
+    let (groups, mut sub) = ob.subscribe().group_by(tens);
+    assert_eq!(contents(&groups), expected(&ob));
+    assert_eq!(contents(&groups), vec![(1, vector![12, 15]), (2, vector![21])]);
+
+    let mut ones = groups[0].subscribe().into_stream();
+
+    ob.push_back(3);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Insert { index: 0, value: (0, vector![3]) });
+    ob.push_front(11);
+    assert_pending!(sub);
+    assert_eq!(ones.next().now_or_never(), Some(Some(VectorDiff::PushFront { value: 11 })));
+    ob.insert(2, 13);
+    assert_pending!(sub);
+    assert_eq!(ones.next().now_or_never(), Some(Some(VectorDiff::Insert { index: 2, value: 13 })));
+    // [11, 12, 13, 21, 15, 3]
+
+    ob.remove(3);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Remove { index: 2 });
+    ob.pop_back();
+    assert_eq!(next_diff(&mut sub), VectorDiff::Remove { index: 0 });
+    ob.pop_front();
+    assert_pending!(sub);
+    assert_eq!(ones.next().now_or_never(), Some(Some(VectorDiff::PopFront)));
+    // [12, 13, 15]
+
+    ob.append(vector![31, 14]);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Insert { index: 1, value: (3, vector![31]) });
+    assert_pending!(sub);
+    assert_eq!(ones.next().now_or_never(), Some(Some(VectorDiff::PushBack { value: 14 })));
+
+    // Groups that were removed are empty.
+    assert_eq!(contents(&groups), vec![(1, vector![12, 13, 15, 14]), (2, vector![])]);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn set() {
+    let mut ob = ObservableVector::<u32>::from(vector![10, 20, 11]);
+    let (groups, mut sub) = ob.subscribe().group_by(tens);
+    let ones = groups[0].clone();
+
+    // Same group.
+    ob.set(2, 12);
+    assert_pending!(sub);
+    assert_eq!(ones.values(), vector![10, 12]);
+
+    // Existing group.
+    ob.set(0, 21);
+    assert_pending!(sub);
+    assert_eq!(ones.values(), vector![12]);
+
+    // New group.
+    ob.set(1, 30);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Insert { index: 2, value: (3, vector![30]) });
+    assert_pending!(sub);
+
+    // Last value of the group moves to a new group.
+    ob.set(1, 0);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Remove { index: 2 });
+    assert_eq!(next_diff(&mut sub), VectorDiff::Insert { index: 0, value: (0, vector![0]) });
+    assert_pending!(sub);
+    // [21, 0, 12]
+
+    assert_eq!(ones.values(), vector![12]);
+    assert_eq!(
+        contents(&groups.into_iter().skip(1).collect()),
+        vec![(2, vector![21])],
+        "the initial groups are kept up to date"
+    );
+}
+
+#[test]
+fn move_and_swap() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 10, 2, 11, 20]);
+    let (groups, mut sub) = ob.subscribe().group_by(tens);
+    let (zeros, ones) = (groups[0].clone(), groups[1].clone());
+
+    ob.move_item(0, 3);
+    assert_pending!(sub);
+    assert_eq!(zeros.values(), vector![2, 1]);
+    assert_eq!(contents(&groups), expected(&ob));
+
+    ob.swap(0, 4);
+    assert_pending!(sub);
+    assert_eq!(ones.values(), vector![11, 10]);
+    // [20, 2, 11, 1, 10]
+
+    // The values are still found in their groups after moving around.
+    ob.set(3, 3);
+    ob.remove(0);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Remove { index: 2 });
+    ob.set(2, 12);
+    assert_pending!(sub);
+    assert_eq!(zeros.values(), vector![2]);
+    assert_eq!(ones.values(), vector![11, 12, 10]);
+    assert_eq!(contents(&groups.into_iter().take(2).collect()), expected(&ob));
+}
+
+#[test]
+fn clear_truncate_reset() {
+    let mut ob = ObservableVector::<u32>::with_capacity(1);
+    let (mut groups, mut sub) = ob.subscribe().group_by(tens);
+
+    ob.append(vector![1, 12, 2, 23]);
+    while let Some(Some(diff)) = sub.next().now_or_never() {
+        diff.apply(&mut groups);
+    }
+    assert_eq!(contents(&groups), expected(&ob));
+    let zeros = groups[0].clone();
+
+    ob.truncate(2);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Remove { index: 2 });
+    assert_pending!(sub);
+    assert_eq!(zeros.values(), vector![1]);
+
+    ob.truncate(0);
+    assert_eq!(next_diff(&mut sub), VectorDiff::Clear);
+    assert_pending!(sub);
+
+    ob.append(vector![3, 14]);
+    ob.clear();
+    ob.append(vector![4, 35]);
+    // The subscriber lagged behind, so it gets a `Reset`.
+    let diff = sub.next().now_or_never().unwrap().unwrap();
+    let VectorDiff::Reset { values } = diff else { panic!("expected a reset, got {diff:?}") };
+    assert_eq!(contents(&values), vec![(0, vector![4]), (3, vector![35])]);
+    assert_pending!(sub);
+}
+
+#[test]
+fn reset_keeps_groups() {
+    let mut ob = ObservableVector::<u32>::with_capacity(1);
+    ob.append(vector![1, 10]);
+    let (groups, mut sub) = ob.subscribe().group_by(tens);
+    let (zeros, ones) = (groups[0].clone(), groups[1].clone());
+
+    ob.push_back(2);
+    ob.set(1, 20);
+    let diff = sub.next().now_or_never().unwrap().unwrap();
+    let VectorDiff::Reset { values } = diff else { panic!("expected a reset, got {diff:?}") };
+    assert_eq!(contents(&values), vec![(0, vector![1, 2]), (2, vector![20])]);
+
+    // The group with a key that is still in use has been updated, the other
+    // one has been emptied.
+    assert_eq!(zeros.values(), vector![1, 2]);
+    assert!(ones.values().is_empty());
+
+    ob.push_back(3);
+    assert_pending!(sub);
+    assert_eq!(zeros.values(), vector![1, 2, 3]);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::<u32>::new();
+    let (mut groups, mut sub) = ob.subscribe().batched().group_by(tens);
+
+    let mut txn = ob.transaction();
+    txn.append(vector![5, 25, 15]);
+    txn.push_front(26);
+    txn.remove(1);
+    txn.set(0, 6);
+    txn.commit();
+
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    assert_eq!(diffs.len(), 5);
+    for diff in diffs {
+        diff.apply(&mut groups);
+    }
+    assert_pending!(sub);
+
+    assert_eq!(contents(&groups), expected(&ob));
+    assert_eq!(contents(&groups), vec![(0, vector![6]), (1, vector![15]), (2, vector![25])]);
+}
+
+#[test]
+fn flattened() {
+    let mut ob = ObservableVector::<u32>::from(vector![21, 3, 12]);
+    let (groups, sub) = ob.subscribe().group_by(tens);
+    let (mut values, mut sub) = (groups, sub).flatten(Group::subscribe);
+    assert_eq!(values, vector![3, 12, 21]);
+
+    ob.push_back(4);
+    ob.set(0, 13);
+    ob.remove(1);
+    ob.push_front(41);
+
+    while let Some(Some(diff)) = sub.next().now_or_never() {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, vector![4, 13, 12, 41]);
+}
@@ -1,10 +1,22 @@
 #![allow(missing_docs)]
 
+mod aggregate;
+mod concat;
+mod dedup_by_key;
+mod dynamic_filter;
+mod dynamic_sort_by;
 mod filter;
 mod filter_map;
+mod flatten;
+mod group_by;
 mod head;
+mod map;
+mod move_and_swap;
+mod range_operations;
+mod reverse;
 mod skip;
 mod sort;
 mod sort_by;
 mod sort_by_key;
 mod tail;
+mod window;
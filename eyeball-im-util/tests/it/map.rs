@@ -0,0 +1,61 @@
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use imbl::vector;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+#[test]
+fn map() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2]);
+    let (values, mut sub) = ob.subscribe().map(|i| i.to_string());
+    assert_eq!(values, vector!["1".to_owned(), "2".to_owned()]);
+
+    ob.append(vector![3, 4]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector!["3".to_owned(), "4".to_owned()] });
+
+    ob.insert(1, 5);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: "5".to_owned() });
+
+    ob.remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 0 });
+
+    ob.clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn map_batched() {
+    let mut ob: ObservableVector<i32> = ObservableVector::new();
+    let (values, mut sub) = ob.subscribe().batched().map(|i| i * 2);
+    assert!(values.is_empty());
+
+    let mut txn = ob.transaction();
+    txn.push_back(1);
+    txn.push_front(2);
+    txn.set(1, 3);
+    txn.commit();
+
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::PushBack { value: 2 },
+            VectorDiff::PushFront { value: 4 },
+            VectorDiff::Set { index: 1, value: 6 },
+        ]
+    );
+    assert_pending!(sub);
+}
+
+#[test]
+fn map_reset() {
+    let mut ob: ObservableVector<i32> = ObservableVector::with_capacity(1);
+    let (_, mut sub) = ob.subscribe().map(|i| i + 1);
+
+    ob.push_back(1);
+    ob.push_back(2);
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![2, 3] });
+    assert_pending!(sub);
+}
@@ -0,0 +1,339 @@
+use std::sync::{Arc, Mutex};
+
+use eyeball_im::{ObservableVector, VectorDiff, VectorSubscriber};
+use eyeball_im_util::vector::{Group, VectorObserverExt};
+use futures_util::{FutureExt, Stream, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_next_eq, assert_pending};
+
+#[test]
+fn head() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().head(3);
+
+    // Both indices inside the limit.
+    ob.move_item(0, 2);
+    assert_next_eq!(sub, VectorDiff::Move { from: 0, to: 2 });
+    // [2, 3, 1, 4, 5]
+
+    // A value leaves the limit.
+    ob.move_item(1, 4);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 4 });
+    // [2, 1, 4, 5, 3]
+
+    // A value enters the limit.
+    ob.move_item(3, 0);
+    assert_next_eq!(sub, VectorDiff::PopBack);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 5 });
+    // [5, 2, 1, 4, 3]
+
+    // Both indices outside the limit.
+    ob.move_item(4, 3);
+    ob.swap(3, 4);
+    assert_pending!(sub);
+
+    ob.swap(0, 2);
+    assert_next_eq!(sub, VectorDiff::Swap { first: 0, second: 2 });
+    // [1, 2, 5, 4, 3]
+
+    ob.swap(1, 4);
+    assert_next_eq!(sub, VectorDiff::Set { index: 1, value: 3 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn tail() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().tail(3);
+
+    ob.move_item(4, 2);
+    assert_next_eq!(sub, VectorDiff::Move { from: 2, to: 0 });
+    // [1, 2, 5, 3, 4]
+
+    ob.move_item(3, 0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 2 });
+    // [3, 1, 2, 5, 4]
+
+    ob.move_item(1, 4);
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 2, value: 1 });
+    // [3, 2, 5, 4, 1]
+
+    ob.swap(0, 1);
+    assert_pending!(sub);
+
+    ob.swap(0, 3);
+    assert_next_eq!(sub, VectorDiff::Set { index: 1, value: 2 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn skip() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().skip(2);
+
+    ob.move_item(4, 2);
+    assert_next_eq!(sub, VectorDiff::Move { from: 2, to: 0 });
+    // [1, 2, 5, 3, 4]
+
+    ob.move_item(3, 0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 2 });
+    // [3, 1, 2, 5, 4]
+
+    ob.swap(0, 1);
+    assert_pending!(sub);
+
+    ob.swap(2, 4);
+    assert_next_eq!(sub, VectorDiff::Swap { first: 0, second: 2 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn filter() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5, 6]);
+    let (_, mut sub) = ob.subscribe().filter(|value| value % 2 == 0);
+
+    ob.move_item(1, 4);
+    assert_next_eq!(sub, VectorDiff::Move { from: 0, to: 1 });
+    // [1, 3, 4, 5, 2, 6]
+
+    // Moving a value that doesn't match.
+    ob.move_item(0, 5);
+    assert_pending!(sub);
+    // [3, 4, 5, 2, 6, 1]
+
+    // Moving a value without changing its position amongst filtered values.
+    ob.move_item(1, 0);
+    assert_pending!(sub);
+    // [4, 3, 5, 2, 6, 1]
+
+    ob.swap(0, 4);
+    assert_next_eq!(sub, VectorDiff::Swap { first: 0, second: 2 });
+    // [6, 3, 5, 2, 4, 1]
+
+    // Swapping a value that matches with one that doesn't.
+    ob.swap(2, 4);
+    assert_next_eq!(sub, VectorDiff::Move { from: 2, to: 1 });
+    // [6, 3, 4, 2, 5, 1]
+
+    ob.swap(0, 1);
+    assert_pending!(sub);
+}
+
+#[test]
+fn sort() {
+    let mut ob = ObservableVector::<u32>::from(vector![3, 1, 2]);
+    let (values, mut sub) = ob.subscribe().sort();
+    assert_eq!(values, vector![1, 2, 3]);
+
+    // Reordering doesn't change the sorted values.
+    ob.move_item(0, 2);
+    ob.swap(0, 1);
+    assert_pending!(sub);
+
+    // … but the adapter keeps track of the new positions.
+    // [2, 1, 3]
+    ob.remove(0);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 1 });
+    assert_pending!(sub);
+}
+
+type Section = Arc<Mutex<ObservableVector<u32>>>;
+
+fn section(values: Vector<u32>) -> Section {
+    Arc::new(Mutex::new(ObservableVector::from(values)))
+}
+
+fn subscribe(section: &Section) -> VectorSubscriber<u32> {
+    section.lock().unwrap().subscribe()
+}
+
+fn flattened(sections: &ObservableVector<Section>) -> Vector<u32> {
+    sections.iter().flat_map(|section| section.lock().unwrap().clone()).collect()
+}
+
+#[test]
+fn flatten() {
+    let a = section(vector![1, 2]);
+    let b = section(vector![]);
+    let c = section(vector![3, 4, 5]);
+
+    let mut ob = ObservableVector::from(vector![a.clone(), b.clone(), c.clone()]);
+    let (mut values, mut sub) = ob.subscribe().flatten(subscribe);
+
+    let mut check = |ob: &ObservableVector<Section>| {
+        while let Some(Some(diff)) = sub.next().now_or_never() {
+            diff.apply(&mut values);
+        }
+        assert_eq!(values, flattened(ob));
+    };
+
+    ob.move_item(0, 2);
+    check(&ob);
+    ob.move_item(2, 0);
+    check(&ob);
+    ob.swap(0, 2);
+    check(&ob);
+    ob.swap(1, 2);
+    check(&ob);
+
+    // Sections keep forwarding their own updates at their new position.
+    a.lock().unwrap().push_front(0);
+    c.lock().unwrap().move_item(0, 2);
+    check(&ob);
+    b.lock().unwrap().push_back(6);
+    check(&ob);
+}
+
+fn tens(value: &u32) -> u32 {
+    value / 10
+}
+
+fn contents(groups: &Vector<Group<u32, u32>>) -> Vec<(u32, Vector<u32>)> {
+    groups.iter().map(|group| (*group.key(), group.values())).collect()
+}
+
+#[test]
+fn group_by() {
+    let mut ob = ObservableVector::<u32>::from(vector![10, 20, 11, 21, 12]);
+    let (groups, mut sub) = ob.subscribe().group_by(tens);
+    let mut ones = groups[0].subscribe().into_stream();
+
+    // Moves don't change the groups themselves…
+    ob.move_item(0, 4);
+    assert_pending!(sub);
+    assert_eq!(contents(&groups), vec![(1, vector![11, 12, 10]), (2, vector![20, 21])]);
+    assert_eq!(ones.next().now_or_never(), Some(Some(VectorDiff::Move { from: 0, to: 2 })));
+
+    // … only the order of values in them.
+    ob.swap(0, 1);
+    assert_pending!(sub);
+    assert_eq!(contents(&groups), vec![(1, vector![11, 12, 10]), (2, vector![20, 21])]);
+    assert_pending!(ones);
+
+    ob.swap(1, 3);
+    assert_pending!(sub);
+    assert_eq!(contents(&groups), vec![(1, vector![11, 12, 10]), (2, vector![21, 20])]);
+}
+
+/// A deterministic pseudo-random number generator.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % bound
+    }
+}
+
+fn drain<S, T>(sub: &mut S, values: &mut Vector<T>)
+where
+    S: Stream<Item = VectorDiff<T>> + Unpin,
+    T: Clone,
+{
+    while let Some(Some(diff)) = sub.next().now_or_never() {
+        diff.apply(values);
+    }
+}
+
+#[test]
+fn random() {
+    let mut rng = Rng(0x2545_f491_u32);
+    let mut ob = ObservableVector::<usize>::new();
+    let mut other = ObservableVector::<usize>::new();
+
+    let (mut head, mut head_sub) = ob.subscribe().head(5);
+    let (mut tail, mut tail_sub) = ob.subscribe().tail(5);
+    let (mut skip, mut skip_sub) = ob.subscribe().skip(3);
+    let (mut window, mut window_sub) = ob.subscribe().window(2, 4);
+    let (mut filter, mut filter_sub) = ob.subscribe().filter(|value| value % 3 != 0);
+    let (mut sort, mut sort_sub) = ob.subscribe().sort();
+    let (mut reverse, mut reverse_sub) = ob.subscribe().reverse();
+    let (mut dedup, mut dedup_sub) = ob.subscribe().dedup_by_key(|value| value % 5);
+    let (mut chain, mut chain_sub) = ob.subscribe().chain(other.subscribe());
+    let (groups, mut groups_sub) = ob.subscribe().group_by(|value| value % 4);
+    let mut groups = groups.into_iter().map(|group| (*group.key(), group)).collect::<Vector<_>>();
+    let (mut sum, mut sum_sub) = ob.subscribe().sum_by(|value| *value);
+
+    for value in 0..2000 {
+        let target = if rng.next(4) == 0 { &mut other } else { &mut ob };
+        let len = target.len();
+
+        match rng.next(9) {
+            0 => target.push_front(value),
+            1 => target.push_back(value),
+            2 => target.insert(rng.next(len + 1), value),
+            3 if len > 0 => {
+                target.remove(rng.next(len));
+            }
+            4 if len > 0 => {
+                target.set(rng.next(len), value);
+            }
+            5 | 6 if len > 0 => target.move_item(rng.next(len), rng.next(len)),
+            7 | 8 if len > 0 => target.swap(rng.next(len), rng.next(len)),
+            _ if len > 20 => target.truncate(rng.next(len)),
+            _ => {}
+        }
+
+        drain(&mut head_sub, &mut head);
+        drain(&mut tail_sub, &mut tail);
+        drain(&mut skip_sub, &mut skip);
+        drain(&mut window_sub, &mut window);
+        drain(&mut filter_sub, &mut filter);
+        drain(&mut sort_sub, &mut sort);
+        drain(&mut reverse_sub, &mut reverse);
+        drain(&mut dedup_sub, &mut dedup);
+        drain(&mut chain_sub, &mut chain);
+        while let Some(Some(diff)) = groups_sub.next().now_or_never() {
+            diff.map(|group| (*group.key(), group)).apply(&mut groups);
+        }
+        while let Some(Some(value)) = sum_sub.next().now_or_never() {
+            sum = value;
+        }
+
+        let len = ob.len();
+        let values: Vector<_> = ob.iter().copied().collect();
+        let other_values: Vector<_> = other.iter().copied().collect();
+        let mut expected_sort = values.clone();
+        expected_sort.sort();
+        let mut keys = Vec::new();
+        let expected_dedup: Vector<_> = values
+            .iter()
+            .copied()
+            .filter(|value| {
+                let is_new = !keys.contains(&(value % 5));
+                keys.push(value % 5);
+                is_new
+            })
+            .collect();
+        let mut expected_groups: Vec<(usize, Vector<usize>)> = Vec::new();
+        for &value in &values {
+            match expected_groups.iter_mut().find(|(key, _)| *key == value % 4) {
+                Some((_, values)) => values.push_back(value),
+                None => expected_groups.push((value % 4, vector![value])),
+            }
+        }
+        expected_groups.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(head, values.iter().copied().take(5).collect(), "after step {value}");
+        assert_eq!(tail, values.iter().copied().skip(len.saturating_sub(5)).collect());
+        assert_eq!(skip, values.iter().copied().skip(3).collect(), "after step {value}");
+        assert_eq!(window, values.iter().copied().skip(2).take(4).collect());
+        assert_eq!(filter, values.iter().copied().filter(|value| value % 3 != 0).collect());
+        assert_eq!(sort, expected_sort, "after step {value}");
+        assert_eq!(reverse, values.iter().copied().rev().collect(), "after step {value}");
+        assert_eq!(dedup, expected_dedup, "after step {value}");
+        assert_eq!(chain, values.clone() + other_values, "after step {value}");
+        assert_eq!(
+            groups.iter().map(|(key, group)| (*key, group.values())).collect::<Vec<_>>(),
+            expected_groups,
+            "after step {value}"
+        );
+        assert_eq!(sum, values.iter().sum::<usize>(), "after step {value}");
+    }
+}
@@ -0,0 +1,295 @@
+use std::sync::{Arc, Mutex};
+
+use eyeball_im::{ObservableVector, VectorDiff, VectorSubscriber};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, Stream, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_next_eq, assert_pending};
+
+#[test]
+fn head() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().head(3);
+
+    // Values pushed out of the view are truncated first.
+    ob.insert_many(1, vector![6, 7]);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 1 });
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 1, values: vector![6, 7] });
+    // [1, 6, 7, 2, 3, 4, 5]
+
+    ob.insert_many(3, vector![8]);
+    assert_pending!(sub);
+    // [1, 6, 7, 8, 2, 3, 4, 5]
+
+    // Values entering the view are appended.
+    ob.remove_range(0..2);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 0, end: 2 });
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![8, 2] });
+    // [7, 8, 2, 3, 4, 5]
+
+    ob.splice(1..5, vector![9]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 1, end: 3, values: vector![9] });
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![5] });
+    assert_pending!(sub);
+}
+
+#[test]
+fn tail() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().tail(3);
+
+    ob.insert_many(4, vector![6, 7]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 0, end: 2, values: vector![6, 7] });
+    // [1, 2, 3, 4, 6, 7, 5]
+
+    // Before the view.
+    ob.insert_many(0, vector![8]);
+    ob.remove_range(1..3);
+    assert_pending!(sub);
+    // [8, 3, 4, 6, 7, 5]
+
+    ob.remove_range(3..5);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 0, end: 2, values: vector![3, 4] });
+    // [8, 3, 4, 5]
+
+    // Fewer values than `limit` remain.
+    ob.splice(2..4, vector![]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 0, end: 3, values: vector![8, 3] });
+    assert_pending!(sub);
+}
+
+#[test]
+fn skip() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5]);
+    let (_, mut sub) = ob.subscribe().skip(2);
+
+    ob.insert_many(3, vector![6, 7]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 1, values: vector![6, 7] });
+    // [1, 2, 3, 6, 7, 4, 5]
+
+    // Values before `count` shift values into the view.
+    ob.insert_many(0, vector![8, 9]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![1, 2] });
+    // [8, 9, 1, 2, 3, 6, 7, 4, 5]
+
+    ob.remove_range(1..4);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 0, end: 3 });
+    // [8, 3, 6, 7, 4, 5]
+
+    ob.splice(1..3, vector![10]);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 0, end: 1 });
+    // [8, 10, 7, 4, 5]
+
+    ob.splice(3.., vector![11, 12, 13]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 1, end: 3, values: vector![11, 12, 13] });
+    assert_pending!(sub);
+}
+
+#[test]
+fn filter() {
+    let mut ob = ObservableVector::<u32>::from(vector![1, 2, 3, 4, 5, 6]);
+    let (_, mut sub) = ob.subscribe().filter(|value| value % 2 == 0);
+
+    ob.insert_many(1, vector![8, 9, 10]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![8, 10] });
+    // [1, 8, 9, 10, 2, 3, 4, 5, 6]
+
+    ob.remove_range(3..6);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 1, end: 3 });
+    // [1, 8, 9, 4, 5, 6]
+
+    ob.splice(0..2, vector![7]);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 0, end: 1 });
+    // [7, 9, 4, 5, 6]
+
+    ob.splice(1..2, vector![12, 13]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![12] });
+    // [7, 12, 13, 4, 5, 6]
+
+    // Values that don't match.
+    ob.splice(2..4, vector![15]);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 1, end: 2 });
+    ob.insert_many(0, vector![17, 19]);
+    assert_pending!(sub);
+}
+
+type Section = Arc<Mutex<ObservableVector<u32>>>;
+
+fn section(values: Vector<u32>) -> Section {
+    Arc::new(Mutex::new(ObservableVector::from(values)))
+}
+
+fn subscribe(section: &Section) -> VectorSubscriber<u32> {
+    section.lock().unwrap().subscribe()
+}
+
+#[test]
+fn flatten() {
+    let a = section(vector![1, 2]);
+    let b = section(vector![]);
+    let c = section(vector![3]);
+
+    let mut ob = ObservableVector::from(vector![a.clone()]);
+    let (_, mut sub) = ob.subscribe().flatten(subscribe);
+
+    ob.insert_many(0, vector![b.clone(), c.clone()]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![3] });
+    ob.insert_many(3, vector![c.clone()]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![3] });
+    // [b, c, a, c] => [3, 1, 2, 3]
+
+    ob.remove_range(1..3);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 0, end: 3 });
+    // [b, c] => [3]
+
+    ob.splice(0..1, vector![a.clone()]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![1, 2] });
+    // [a, c] => [1, 2, 3]
+
+    // Sections keep forwarding their own updates.
+    a.lock().unwrap().insert_many(1, vector![4, 5]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 1, values: vector![4, 5] });
+    c.lock().unwrap().splice(.., vector![6]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 4, end: 5, values: vector![6] });
+
+    ob.remove_range(..);
+    assert_next_eq!(sub, VectorDiff::Clear);
+    assert_pending!(sub);
+}
+
+/// A deterministic pseudo-random number generator.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % bound
+    }
+
+    fn range(&mut self, len: usize) -> (usize, usize) {
+        let start = self.next(len + 1);
+        (start, start + self.next(len - start + 1))
+    }
+}
+
+fn drain<S, T>(sub: &mut S, values: &mut Vector<T>)
+where
+    S: Stream<Item = VectorDiff<T>> + Unpin,
+    T: Clone,
+{
+    while let Some(Some(diff)) = sub.next().now_or_never() {
+        diff.apply(values);
+    }
+}
+
+#[test]
+fn random() {
+    let mut rng = Rng(0x2545_f491_u32);
+    let mut ob = ObservableVector::<usize>::new();
+    let mut other = ObservableVector::<usize>::new();
+
+    let (mut head, mut head_sub) = ob.subscribe().head(5);
+    let (mut tail, mut tail_sub) = ob.subscribe().tail(5);
+    let (mut skip, mut skip_sub) = ob.subscribe().skip(3);
+    let (mut window, mut window_sub) = ob.subscribe().batched().window(2, 4);
+    let (mut filter, mut filter_sub) = ob.subscribe().filter(|value| value % 3 != 0);
+    let (mut sort, mut sort_sub) = ob.subscribe().sort();
+    let (mut reverse, mut reverse_sub) = ob.subscribe().reverse();
+    let (mut dedup, mut dedup_sub) = ob.subscribe().dedup_by_key(|value| value % 5);
+    let (mut chain, mut chain_sub) = ob.subscribe().chain(other.subscribe());
+    let (groups, mut groups_sub) = ob.subscribe().group_by(|value| value % 4);
+    let mut groups = groups.into_iter().map(|group| (*group.key(), group)).collect::<Vector<_>>();
+    let (mut sum, mut sum_sub) = ob.subscribe().sum_by(|value| *value);
+
+    for value in 0..2000 {
+        let target = if rng.next(4) == 0 { &mut other } else { &mut ob };
+        let len = target.len();
+        let values: Vector<_> = (0..rng.next(6)).map(|i| value * 10 + i).collect();
+
+        match rng.next(9) {
+            0 => target.push_back(value),
+            1 => target.insert(rng.next(len + 1), value),
+            2 if len > 0 => {
+                target.remove(rng.next(len));
+            }
+            3 | 4 => target.insert_many(rng.next(len + 1), values),
+            5 => {
+                let (start, end) = rng.range(len);
+                target.remove_range(start..end);
+            }
+            6 | 7 => {
+                let (start, end) = rng.range(len);
+                target.splice(start..end, values);
+            }
+            _ => {
+                let mut txn = target.transaction();
+                txn.insert_many(rng.next(len + 1), values);
+                let (start, end) = rng.range(txn.len());
+                txn.remove_range(start..end);
+                txn.commit();
+            }
+        }
+
+        drain(&mut head_sub, &mut head);
+        drain(&mut tail_sub, &mut tail);
+        drain(&mut skip_sub, &mut skip);
+        while let Some(Some(diffs)) = window_sub.next().now_or_never() {
+            for diff in diffs {
+                diff.apply(&mut window);
+            }
+        }
+        drain(&mut filter_sub, &mut filter);
+        drain(&mut sort_sub, &mut sort);
+        drain(&mut reverse_sub, &mut reverse);
+        drain(&mut dedup_sub, &mut dedup);
+        drain(&mut chain_sub, &mut chain);
+        while let Some(Some(diff)) = groups_sub.next().now_or_never() {
+            diff.map(|group| (*group.key(), group)).apply(&mut groups);
+        }
+        while let Some(Some(value)) = sum_sub.next().now_or_never() {
+            sum = value;
+        }
+
+        let len = ob.len();
+        let values: Vector<_> = ob.iter().copied().collect();
+        let other_values: Vector<_> = other.iter().copied().collect();
+        let mut expected_sort = values.clone();
+        expected_sort.sort();
+        let mut keys = Vec::new();
+        let expected_dedup: Vector<_> = values
+            .iter()
+            .copied()
+            .filter(|value| {
+                let is_new = !keys.contains(&(value % 5));
+                keys.push(value % 5);
+                is_new
+            })
+            .collect();
+        let mut expected_groups: Vec<(usize, Vector<usize>)> = Vec::new();
+        for &value in &values {
+            match expected_groups.iter_mut().find(|(key, _)| *key == value % 4) {
+                Some((_, values)) => values.push_back(value),
+                None => expected_groups.push((value % 4, vector![value])),
+            }
+        }
+        expected_groups.sort_by_key(|(key, _)| *key);
+
+        assert_eq!(head, values.iter().copied().take(5).collect(), "after step {value}");
+        assert_eq!(tail, values.iter().copied().skip(len.saturating_sub(5)).collect());
+        assert_eq!(skip, values.iter().copied().skip(3).collect(), "after step {value}");
+        assert_eq!(window, values.iter().copied().skip(2).take(4).collect());
+        assert_eq!(filter, values.iter().copied().filter(|value| value % 3 != 0).collect());
+        assert_eq!(sort, expected_sort, "after step {value}");
+        assert_eq!(reverse, values.iter().copied().rev().collect(), "after step {value}");
+        assert_eq!(dedup, expected_dedup, "after step {value}");
+        assert_eq!(chain, values.clone() + other_values, "after step {value}");
+        assert_eq!(
+            groups.iter().map(|(key, group)| (*key, group.values())).collect::<Vec<_>>(),
+            expected_groups,
+            "after step {value}"
+        );
+        assert_eq!(sum, values.iter().sum::<usize>(), "after step {value}");
+    }
+}
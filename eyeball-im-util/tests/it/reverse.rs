@@ -0,0 +1,153 @@
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+fn reversed(values: &Vector<i32>) -> Vector<i32> {
+    values.iter().rev().copied().collect()
+}
+
+#[test]
+fn initial_values() {
+    let ob = ObservableVector::from(vector![1, 2, 3]);
+    let (values, mut sub) = ob.subscribe().reverse();
+
+    assert_eq!(values, vector![3, 2, 1]);
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn append() {
+    let mut ob = ObservableVector::new();
+    let (_, mut sub) = ob.subscribe().reverse();
+
+    ob.append(vector![1, 2]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![2, 1] });
+
+    ob.append(vector![3, 4]);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 3 });
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 4 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn push_and_pop() {
+    let mut ob = ObservableVector::new();
+    let (_, mut sub) = ob.subscribe().reverse();
+
+    ob.push_back(1);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 1 });
+    ob.push_front(0);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 0 });
+    ob.pop_back();
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::PopBack);
+    assert_pending!(sub);
+}
+
+#[test]
+fn insert_set_remove() {
+    let mut ob = ObservableVector::from(vector![0, 1, 2]);
+    let (_, mut sub) = ob.subscribe().reverse();
+
+    // [0, 1, 10, 2] reversed is [2, 10, 1, 0]
+    ob.insert(2, 10);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 10 });
+
+    // Inserting at the end of the vector inserts at the start of the view.
+    ob.insert(4, 20);
+    assert_next_eq!(sub, VectorDiff::Insert { index: 0, value: 20 });
+
+    // [0, 1, 10, 2, 20] reversed is [20, 2, 10, 1, 0]
+    ob.set(0, 30);
+    assert_next_eq!(sub, VectorDiff::Set { index: 4, value: 30 });
+
+    ob.remove(1);
+    assert_next_eq!(sub, VectorDiff::Remove { index: 3 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn truncate_clear_reset() {
+    let mut ob: ObservableVector<i32> = ObservableVector::with_capacity(1);
+    let (_, mut sub) = ob.subscribe().reverse();
+
+    ob.append(vector![1, 2, 3, 4]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![4, 3, 2, 1] });
+
+    ob.truncate(2);
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_next_eq!(sub, VectorDiff::PopFront);
+
+    ob.truncate(0);
+    assert_next_eq!(sub, VectorDiff::Clear);
+
+    ob.push_back(5);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 5 });
+
+    ob.clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+
+    // Lagging behind produces a reset.
+    ob.append(vector![6, 7]);
+    ob.push_back(8);
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![8, 7, 6] });
+
+    ob.push_front(9);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 9 });
+    assert_pending!(sub);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::from(vector![1, 2]);
+    let (values, mut sub) = ob.subscribe().batched().reverse();
+    assert_eq!(values, vector![2, 1]);
+
+    let mut txn = ob.transaction();
+    txn.append(vector![3, 4]);
+    txn.truncate(1);
+    txn.insert(0, 0);
+    txn.commit();
+
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::PushFront { value: 3 },
+            VectorDiff::PushFront { value: 4 },
+            VectorDiff::PopFront,
+            VectorDiff::PopFront,
+            VectorDiff::PopFront,
+            VectorDiff::Insert { index: 1, value: 0 },
+        ]
+    );
+    assert_pending!(sub);
+}
+
+#[test]
+fn mirrors_the_vector() {
+    let mut ob = ObservableVector::new();
+    let (mut values, mut sub) = ob.subscribe().batched().reverse();
+
+    let mut txn = ob.transaction();
+    txn.append(vector![1, 2, 3, 4, 5]);
+    txn.insert(3, 6);
+    txn.set(5, 7);
+    txn.remove(0);
+    txn.push_front(8);
+    txn.pop_back();
+    txn.push_back(9);
+    txn.truncate(4);
+    txn.commit();
+
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, reversed(&ob));
+}
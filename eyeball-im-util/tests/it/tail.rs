@@ -763,6 +763,18 @@ fn insert() {
         assert_next_eq!(sub, VectorDiff::Append { values: expected });
     }
 
+    // Insert inside a view that isn't full.
+    {
+        ob.insert(1, 17);
+
+        assert_next_eq!(sub, VectorDiff::Insert { index: 1, value: 17 });
+
+        // State of:
+        //
+        // - the vector: [ 12, 17, 13, 14, 11, 16, 15, 10 ]
+        // - the “view”: [ 12, 17, 13, 14, 11, 16, 15, 10 ]
+    }
+
     assert_pending!(sub);
     drop(ob);
     assert_closed!(sub);
@@ -0,0 +1,238 @@
+use std::ops::Range;
+
+use eyeball::Observable;
+use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im_util::vector::{VectorObserverExt, VectorSubscriberExt};
+use futures_util::{FutureExt, StreamExt};
+use imbl::{vector, Vector};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+#[test]
+fn static_window() {
+    let mut ob = ObservableVector::<usize>::from(vector![10, 11, 12, 13, 14]);
+    let (initial_values, mut sub) = ob.subscribe().window(1, 2);
+    assert_eq!(initial_values, vector![11, 12]);
+    assert_pending!(sub);
+
+    // 10 is removed, 11 leaves the window and 13 enters it.
+    ob.pop_front();
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 13 });
+
+    // 15 is added after the window.
+    ob.push_back(15);
+    assert_pending!(sub);
+
+    // 12 is replaced.
+    ob.set(1, 20);
+    assert_next_eq!(sub, VectorDiff::Set { index: 0, value: 20 });
+
+    // The vector is cleared.
+    ob.clear();
+    assert_next_eq!(sub, VectorDiff::Clear);
+
+    // Values are appended, only the ones inside the window are kept.
+    ob.append(vector![1, 2, 3, 4]);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![2, 3] });
+
+    assert_pending!(sub);
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn pending_until_range_emits_a_value() {
+    let mut ob = ObservableVector::<usize>::new();
+    let mut range = Observable::new(0..0);
+    let mut sub = ob.subscribe().dynamic_window(Observable::subscribe(&range));
+
+    // Append new values…
+    ob.append(vector![10, 11, 12, 13, 14, 15]);
+
+    // … but it's still pending…
+    assert_pending!(sub);
+
+    // … because the `range` stream didn't produce any value yet.
+    // Let's change that.
+    Observable::set(&mut range, 2..4);
+
+    // Here we are.
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![12, 13] });
+
+    assert_pending!(sub);
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn scroll() {
+    let ob = ObservableVector::<usize>::from(vector![10, 11, 12, 13, 14, 15, 16, 17]);
+    let mut range = Observable::new(2..5);
+    let (initial_values, mut sub) =
+        ob.subscribe().dynamic_window_with_initial_range(2..5, Observable::subscribe(&range));
+    assert_eq!(initial_values, vector![12, 13, 14]);
+
+    // Scroll forward by two values.
+    Observable::set(&mut range, 4..7);
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![15, 16] });
+    assert_pending!(sub);
+
+    // Scroll backward by one value.
+    Observable::set(&mut range, 3..6);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 2 });
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 13 });
+    assert_pending!(sub);
+
+    // Grow the window on both sides.
+    Observable::set(&mut range, 1..7);
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 12 });
+    assert_next_eq!(sub, VectorDiff::PushFront { value: 11 });
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![16] });
+    assert_pending!(sub);
+
+    // Shrink the window on both sides.
+    Observable::set(&mut range, 2..4);
+    assert_next_eq!(sub, VectorDiff::Truncate { length: 3 });
+    assert_next_eq!(sub, VectorDiff::PopFront);
+    assert_pending!(sub);
+
+    // Jump to a window that doesn't overlap.
+    Observable::set(&mut range, 6..10);
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![16, 17] });
+    assert_pending!(sub);
+
+    // Jump past the end of the vector.
+    Observable::set(&mut range, 10..12);
+    assert_next_eq!(sub, VectorDiff::Clear);
+    assert_pending!(sub);
+
+    // Still past the end of the vector.
+    Observable::set(&mut range, 8..12);
+    assert_pending!(sub);
+
+    // An empty range.
+    Observable::set(&mut range, Range { start: 7, end: 3 });
+    assert_pending!(sub);
+
+    // Come back.
+    Observable::set(&mut range, 0..2);
+    assert_next_eq!(sub, VectorDiff::Append { values: vector![10, 11] });
+    assert_pending!(sub);
+
+    // The same window.
+    Observable::set(&mut range, 0..2);
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn update_range_and_vector_before_polling_sub() {
+    let mut ob = ObservableVector::<usize>::from(vector![10, 11, 12, 13]);
+    let mut range = Observable::new(0..2);
+    let (mut values, mut sub) =
+        ob.subscribe().dynamic_window_with_initial_range(0..2, Observable::subscribe(&range));
+    assert_eq!(values, vector![10, 11]);
+
+    ob.push_front(9);
+    Observable::set(&mut range, 1..3);
+    ob.remove(2);
+
+    while let Some(diff) = sub.next().now_or_never().flatten() {
+        diff.apply(&mut values);
+    }
+
+    assert_eq!(values, vector![10, 12]);
+}
+
+#[test]
+fn batched() {
+    let mut ob = ObservableVector::<usize>::from(vector![10, 11, 12, 13, 14]);
+    let (mut values, mut sub) = ob.subscribe().batched().window(1, 3);
+    assert_eq!(values, vector![11, 12, 13]);
+
+    let mut txn = ob.transaction();
+    txn.push_front(9);
+    txn.remove(3);
+    txn.push_back(15);
+    txn.commit();
+
+    let diffs = sub.next().now_or_never().unwrap().unwrap();
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_pending!(sub);
+
+    assert_eq!(values, vector![10, 11, 13]);
+}
+
+/// A deterministic pseudo-random number generator.
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % bound
+    }
+}
+
+#[test]
+fn random() {
+    let mut rng = Rng(0x2545_f491_u32);
+    let mut ob = ObservableVector::<usize>::new();
+    let mut range = Observable::new(0..0);
+    let (mut values, mut sub) =
+        ob.subscribe().dynamic_window_with_initial_range(0..0, Observable::subscribe(&range));
+
+    let window = |ob: &ObservableVector<usize>, range: &Range<usize>| -> Vector<usize> {
+        ob.iter().skip(range.start).take(range.end.saturating_sub(range.start)).copied().collect()
+    };
+
+    for value in 0..2000 {
+        let len = ob.len();
+
+        match rng.next(13) {
+            0 => ob.push_front(value),
+            1 => ob.push_back(value),
+            2 if len > 0 => {
+                ob.pop_front();
+            }
+            3 if len > 0 => {
+                ob.pop_back();
+            }
+            4 => ob.insert(rng.next(len + 1), value),
+            5 if len > 0 => {
+                ob.remove(rng.next(len));
+            }
+            6 if len > 0 => {
+                ob.set(rng.next(len), value);
+            }
+            7 => ob.append((0..rng.next(4)).map(|i| value + i).collect()),
+            8 => ob.truncate(rng.next(len + 1)),
+            9 if rng.next(10) == 0 => ob.clear(),
+            10 => {
+                let values: Vector<_> = (0..rng.next(15)).map(|i| value + i).collect();
+                let mut txn = ob.transaction();
+                txn.clear();
+                txn.append(values);
+                txn.commit();
+            }
+            _ => {
+                let start = rng.next(15);
+                let end = rng.next(20);
+                Observable::set(&mut range, start..end);
+            }
+        }
+
+        while let Some(diff) = sub.next().now_or_never().flatten() {
+            diff.apply(&mut values);
+        }
+
+        assert_eq!(values, window(&ob, Observable::get(&range)), "after step {value}");
+    }
+}
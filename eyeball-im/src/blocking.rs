@@ -0,0 +1,115 @@
+//! Blocking (non-async) waiting for updates.
+
+use std::{
+    error::Error,
+    fmt,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::{VectorSubscriberBatchedStream, VectorSubscriberStream};
+
+/// Error returned by the `next_blocking_timeout` methods of subscriber streams
+/// if no update happened within the given timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// Blocking iterator returned by the `blocking_iter` methods of subscriber
+/// streams.
+///
+/// Every call to `next` blocks the current thread until there is an update,
+/// and iteration ends once the observable collection is dropped.
+#[must_use]
+#[derive(Debug)]
+pub struct BlockingIter<'a, S> {
+    stream: &'a mut S,
+}
+
+impl<'a, S> BlockingIter<'a, S> {
+    pub(crate) fn new(stream: &'a mut S) -> Self {
+        Self { stream }
+    }
+}
+
+impl<T: Clone + 'static> Iterator for BlockingIter<'_, VectorSubscriberStream<T>> {
+    type Item = crate::VectorDiff<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.next_blocking()
+    }
+}
+
+impl<T: Clone + 'static> Iterator for BlockingIter<'_, VectorSubscriberBatchedStream<T>> {
+    type Item = Vec<crate::VectorDiff<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.next_blocking()
+    }
+}
+
+/// Call `poll` until it is ready, parking the current thread in between.
+pub(crate) fn block_on<T>(poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+    match block_on_until(None, poll) {
+        Ok(value) => value,
+        Err(Elapsed(())) => unreachable!("no deadline was set"),
+    }
+}
+
+/// Call `poll` until it is ready or the timeout elapses, parking the current
+/// thread in between.
+pub(crate) fn block_on_timeout<T>(
+    timeout: Duration,
+    poll: impl FnMut(&mut Context<'_>) -> Poll<T>,
+) -> Result<T, Elapsed> {
+    // If the deadline is too far in the future to be represented, wait forever
+    block_on_until(Instant::now().checked_add(timeout), poll)
+}
+
+fn block_on_until<T>(
+    deadline: Option<Instant>,
+    mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>,
+) -> Result<T, Elapsed> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(value) = poll(&mut cx) {
+            return Ok(value);
+        }
+
+        // Parking can wake up spuriously, so the loop re-polls in any case.
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(Elapsed(()));
+                }
+                thread::park_timeout(deadline - now);
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+/// A waker that unparks the thread that created it.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,425 @@
+//! Broadcasting of diffs to subscribers, shared by all observable collections.
+
+use std::{
+    error::Error,
+    fmt,
+    sync::Mutex,
+    task::{ready, Context, Poll},
+    vec,
+};
+
+use tokio::sync::{
+    broadcast::{
+        self,
+        error::{RecvError, TryRecvError},
+        Receiver, Sender,
+    },
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+#[cfg(feature = "tracing")]
+use tracing::info;
+
+use crate::reusable_box::ReusableBoxFuture;
+
+/// A change to one of the observable collections.
+pub(crate) trait Diff: Clone + 'static {
+    /// The full state of the collection.
+    type Values: Clone + 'static;
+
+    /// Create the diff that is yielded to subscribers that lagged behind.
+    fn reset(values: Self::Values) -> Self;
+}
+
+#[derive(Clone)]
+pub(crate) struct BroadcastMessage<D, V> {
+    pub(crate) diffs: OneOrManyDiffs<D>,
+    pub(crate) state: V,
+}
+
+pub(crate) type DiffMessage<D> = BroadcastMessage<D, <D as Diff>::Values>;
+
+#[derive(Clone)]
+pub(crate) enum OneOrManyDiffs<D> {
+    One(D),
+    Many(Vec<D>),
+}
+
+impl<D> OneOrManyDiffs<D> {
+    pub(crate) fn into_vec(self) -> Vec<D> {
+        match self {
+            OneOrManyDiffs::One(diff) => vec![diff],
+            OneOrManyDiffs::Many(diffs) => diffs,
+        }
+    }
+}
+
+/// What happens when a subscriber falls behind by more than the capacity of
+/// an observable collection's internal buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// The subscriber receives a single diff that resets its state to the
+    /// collection's latest values, such as [`VectorDiff::Reset`].
+    ///
+    /// This is the policy of subscribers created by `subscribe()`.
+    ///
+    /// [`VectorDiff::Reset`]: crate::VectorDiff::Reset
+    #[default]
+    Reset,
+    /// The subscriber's fallible streams yield a [`Lagged`] error and end.
+    ///
+    /// Infallible streams can't report the error, so they fall back to
+    /// [`LagPolicy::Reset`]. With the `tracing` feature, a warning is logged
+    /// when such a subscriber is turned into an infallible stream.
+    Error,
+    /// The subscriber gets an unbounded queue of its own, such that it never
+    /// lags behind.
+    ///
+    /// Diffs that have not been received yet are retained for as long as the
+    /// subscriber is alive, so a subscriber that isn't polled can use up an
+    /// arbitrary amount of memory.
+    Unbounded,
+}
+
+/// Error yielded by fallible subscriber streams when the subscriber lagged
+/// too far behind, with [`LagPolicy::Error`].
+///
+/// The stream ends after yielding this error, as diffs were lost. Subscribe
+/// again to get back in sync with the collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lagged(());
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("subscriber lagged too far behind")
+    }
+}
+
+impl Error for Lagged {}
+
+/// The sending side of a collection's subscriptions: a broadcast channel for
+/// regular subscribers, and one unbounded channel per subscriber with
+/// [`LagPolicy::Unbounded`].
+pub(crate) struct DiffSender<D, V> {
+    broadcast: Sender<BroadcastMessage<D, V>>,
+    unbounded: Mutex<Vec<UnboundedSender<BroadcastMessage<D, V>>>>,
+}
+
+impl<D: Clone, V: Clone> DiffSender<D, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (broadcast, _) = broadcast::channel(capacity);
+        Self { broadcast, unbounded: Mutex::new(Vec::new()) }
+    }
+
+    pub(crate) fn subscribe(&self, lag_policy: LagPolicy) -> DiffReceiver<D, V> {
+        match lag_policy {
+            LagPolicy::Reset | LagPolicy::Error => {
+                DiffReceiver::Broadcast(self.broadcast.subscribe())
+            }
+            LagPolicy::Unbounded => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                self.unbounded.lock().unwrap().push(tx);
+                DiffReceiver::Unbounded(rx)
+            }
+        }
+    }
+
+    pub(crate) fn receiver_count(&self) -> usize {
+        let unbounded = self.unbounded.lock().unwrap();
+        let num_unbounded = unbounded.iter().filter(|tx| !tx.is_closed()).count();
+        self.broadcast.receiver_count() + num_unbounded
+    }
+
+    /// Send the message to all subscribers, returning the number of
+    /// subscribers it was sent to.
+    pub(crate) fn send(&self, msg: BroadcastMessage<D, V>) -> usize {
+        let mut unbounded = self.unbounded.lock().unwrap();
+        unbounded.retain(|tx| tx.send(msg.clone()).is_ok());
+        let num_unbounded = unbounded.len();
+        drop(unbounded);
+
+        self.broadcast.send(msg).unwrap_or(0) + num_unbounded
+    }
+}
+
+/// The receiving side of a subscription, before it is turned into a stream.
+#[derive(Debug)]
+pub(crate) enum DiffReceiver<D, V> {
+    Broadcast(Receiver<BroadcastMessage<D, V>>),
+    Unbounded(UnboundedReceiver<BroadcastMessage<D, V>>),
+}
+
+impl<D, V> From<Receiver<BroadcastMessage<D, V>>> for DiffReceiver<D, V> {
+    fn from(rx: Receiver<BroadcastMessage<D, V>>) -> Self {
+        Self::Broadcast(rx)
+    }
+}
+
+/// The result of polling a [`RecvState`].
+enum Received<D: Diff> {
+    Diffs(OneOrManyDiffs<D>),
+    /// The subscriber lagged behind, contains the latest values.
+    Lagged(D::Values),
+    Closed,
+}
+
+/// The receiving side of a subscription, in a pollable form.
+#[derive(Debug)]
+enum RecvState<D, V> {
+    Broadcast(ReusableBoxRecvFuture<BroadcastMessage<D, V>>),
+    Unbounded(UnboundedReceiver<BroadcastMessage<D, V>>),
+}
+
+impl<D: Diff> RecvState<D, D::Values> {
+    fn new(rx: DiffReceiver<D, D::Values>) -> Self {
+        match rx {
+            DiffReceiver::Broadcast(rx) => Self::Broadcast(ReusableBoxRecvFuture::new(rx)),
+            DiffReceiver::Unbounded(rx) => Self::Unbounded(rx),
+        }
+    }
+
+    /// Wait for the next message. If `batch` is `true`, also take all of the
+    /// messages that are already available after that one.
+    fn poll_recv(&mut self, cx: &mut Context<'_>, batch: bool) -> Poll<Received<D>> {
+        match self {
+            Self::Broadcast(inner) => {
+                let (result, mut rx) = ready!(inner.poll(cx));
+
+                let received = match result {
+                    Ok(msg) if batch => {
+                        let mut diffs = msg.diffs.into_vec();
+                        loop {
+                            match rx.try_recv() {
+                                Ok(msg) => append(&mut diffs, msg.diffs),
+                                Err(TryRecvError::Empty | TryRecvError::Closed) => {
+                                    break Received::Diffs(OneOrManyDiffs::Many(diffs));
+                                }
+                                Err(TryRecvError::Lagged(_)) => break lagged(&mut rx),
+                            }
+                        }
+                    }
+                    Ok(msg) => Received::Diffs(msg.diffs),
+                    Err(RecvError::Closed) => Received::Closed,
+                    Err(RecvError::Lagged(_)) => lagged(&mut rx),
+                };
+
+                inner.set(rx);
+                Poll::Ready(received)
+            }
+            Self::Unbounded(rx) => {
+                let msg = match ready!(rx.poll_recv(cx)) {
+                    Some(msg) => msg,
+                    None => return Poll::Ready(Received::Closed),
+                };
+
+                if !batch {
+                    return Poll::Ready(Received::Diffs(msg.diffs));
+                }
+
+                let mut diffs = msg.diffs.into_vec();
+                while let Ok(msg) = rx.try_recv() {
+                    append(&mut diffs, msg.diffs);
+                }
+                Poll::Ready(Received::Diffs(OneOrManyDiffs::Many(diffs)))
+            }
+        }
+    }
+}
+
+fn append<D>(target: &mut Vec<D>, source: OneOrManyDiffs<D>) {
+    match source {
+        OneOrManyDiffs::One(diff) => target.push(diff),
+        OneOrManyDiffs::Many(mut diffs) => target.append(&mut diffs),
+    }
+}
+
+fn lagged<D: Diff>(rx: &mut Receiver<DiffMessage<D>>) -> Received<D> {
+    match handle_lag(rx) {
+        Some(values) => Received::Lagged(values),
+        None => Received::Closed,
+    }
+}
+
+/// A stream of diffs, received from a broadcast or unbounded channel.
+#[derive(Debug)]
+pub(crate) struct DiffStream<D, V> {
+    rx: RecvState<D, V>,
+    /// The remaining diffs of the last message with multiple diffs.
+    batch: vec::IntoIter<D>,
+    /// Whether the stream has ended early because of a lag.
+    terminated: bool,
+}
+
+impl<D: Diff> DiffStream<D, D::Values> {
+    pub(crate) fn new(rx: impl Into<DiffReceiver<D, D::Values>>) -> Self {
+        Self { rx: RecvState::new(rx.into()), batch: Vec::new().into_iter(), terminated: false }
+    }
+
+    /// Poll for the next diff, yielding a reset diff if the subscriber lagged.
+    pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<D>> {
+        let result = ready!(self.poll_next_inner(cx));
+        Poll::Ready(result.map(|res| res.unwrap_or_else(D::reset)))
+    }
+
+    /// Poll for the next diff, yielding an error and ending the stream if the
+    /// subscriber lagged.
+    pub(crate) fn poll_try_next(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<D, Lagged>>> {
+        let result = ready!(self.poll_next_inner(cx));
+        Poll::Ready(result.map(|res| {
+            res.map_err(|_| {
+                self.terminated = true;
+                Lagged(())
+            })
+        }))
+    }
+
+    fn poll_next_inner(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<D, D::Values>>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+        if let Some(diff) = self.batch.next() {
+            return Poll::Ready(Some(Ok(diff)));
+        }
+
+        let poll = match ready!(self.rx.poll_recv(cx, false)) {
+            Received::Diffs(OneOrManyDiffs::One(diff)) => Some(Ok(diff)),
+            Received::Diffs(OneOrManyDiffs::Many(diffs)) => {
+                self.batch = diffs.into_iter();
+                let diff = self.batch.next().expect("transactions never send empty diffs");
+                Some(Ok(diff))
+            }
+            Received::Lagged(values) => Some(Err(values)),
+            Received::Closed => None,
+        };
+        Poll::Ready(poll)
+    }
+}
+
+// The diffs are never pinned, so this is fine regardless of `D`
+impl<D, V> Unpin for DiffStream<D, V> {}
+
+/// A batched stream of diffs, received from a broadcast or unbounded channel.
+#[derive(Debug)]
+pub(crate) struct BatchedDiffStream<D, V> {
+    rx: RecvState<D, V>,
+    /// Whether the stream has ended early because of a lag.
+    terminated: bool,
+}
+
+impl<D: Diff> BatchedDiffStream<D, D::Values> {
+    pub(crate) fn new(rx: impl Into<DiffReceiver<D, D::Values>>) -> Self {
+        Self { rx: RecvState::new(rx.into()), terminated: false }
+    }
+
+    /// Poll for the next batch of diffs, yielding a reset diff if the
+    /// subscriber lagged.
+    pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Vec<D>>> {
+        let result = ready!(self.poll_next_inner(cx));
+        Poll::Ready(result.map(|res| res.unwrap_or_else(|values| vec![D::reset(values)])))
+    }
+
+    /// Poll for the next batch of diffs, yielding an error and ending the
+    /// stream if the subscriber lagged.
+    pub(crate) fn poll_try_next(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Vec<D>, Lagged>>> {
+        let result = ready!(self.poll_next_inner(cx));
+        Poll::Ready(result.map(|res| {
+            res.map_err(|_| {
+                self.terminated = true;
+                Lagged(())
+            })
+        }))
+    }
+
+    fn poll_next_inner(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Vec<D>, D::Values>>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+
+        let poll = match ready!(self.rx.poll_recv(cx, true)) {
+            Received::Diffs(diffs) => Some(Ok(diffs.into_vec())),
+            Received::Lagged(values) => Some(Err(values)),
+            Received::Closed => None,
+        };
+        Poll::Ready(poll)
+    }
+}
+
+fn handle_lag<D: Diff>(rx: &mut Receiver<DiffMessage<D>>) -> Option<D::Values> {
+    let mut msg = None;
+    loop {
+        match rx.try_recv() {
+            // There's a newer message in the receiver's buffer, use that for reset.
+            Ok(m) => {
+                msg = Some(m);
+            }
+            // Ideally we'd return a reset with the last state before the
+            // channel was closed here, but we have no way of obtaining the last state.
+            Err(TryRecvError::Closed) => {
+                #[cfg(feature = "tracing")]
+                info!("Channel closed after lag, can't return last state");
+                return None;
+            }
+            // Lagged twice in a row, is this possible? If it is, it's fine to just
+            // loop again and look at the next try_recv result.
+            Err(TryRecvError::Lagged(_)) => {}
+            Err(TryRecvError::Empty) => match msg {
+                // We exhausted the internal buffer using try_recv, msg contains the
+                // last message from it, which we use for the reset.
+                Some(msg) => return Some(msg.state),
+                // We exhausted the internal buffer using try_recv but there was no
+                // message in it, even though we got TryRecvError::Lagged(_) before.
+                None => unreachable!("got no new message via try_recv after lag"),
+            },
+        }
+    }
+}
+
+type SubscriberFutureReturn<T> = (Result<T, RecvError>, Receiver<T>);
+
+struct ReusableBoxRecvFuture<T> {
+    inner: ReusableBoxFuture<'static, SubscriberFutureReturn<T>>,
+}
+
+async fn make_recv_future<T: Clone>(mut rx: Receiver<T>) -> SubscriberFutureReturn<T> {
+    let result = rx.recv().await;
+    (result, rx)
+}
+
+impl<T: Clone + 'static> ReusableBoxRecvFuture<T> {
+    fn new(rx: Receiver<T>) -> Self {
+        Self { inner: ReusableBoxFuture::new(make_recv_future(rx)) }
+    }
+
+    fn set(&mut self, rx: Receiver<T>) {
+        self.inner.set(make_recv_future(rx));
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<SubscriberFutureReturn<T>> {
+        self.inner.poll(cx)
+    }
+}
+
+fn assert_send<T: Send>(_val: T) {}
+#[allow(unused)]
+fn assert_make_future_send() {
+    #[derive(Clone)]
+    struct IsSend(*mut ());
+    unsafe impl Send for IsSend {}
+
+    let (_sender, receiver): (_, Receiver<IsSend>) = broadcast::channel(1);
+
+    assert_send(make_recv_future(receiver));
+}
+// SAFETY: make_future is Send if T is, as proven by assert_make_future_send.
+unsafe impl<T: Send> Send for ReusableBoxRecvFuture<T> {}
+
+impl<T> fmt::Debug for ReusableBoxRecvFuture<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReusableBoxRecvFuture").finish()
+    }
+}
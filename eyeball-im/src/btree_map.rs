@@ -0,0 +1,384 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    ops::{self, RangeBounds},
+};
+
+use imbl::{OrdMap, Vector};
+use tokio::sync::broadcast::{self, Sender};
+
+use crate::broadcast::{BroadcastMessage, Diff, OneOrManyDiffs};
+
+mod subscriber;
+
+pub use self::subscriber::{
+    BTreeMapSubscriber, BTreeMapSubscriberBatchedStream, BTreeMapSubscriberStream,
+};
+
+/// A map of keys to values, ordered by key, that broadcasts any changes made
+/// to it.
+///
+/// Changes are broadcast as [`BTreeMapDiff`]s, which contain both the key and
+/// the position of the affected entry, such that they can be used to keep a
+/// sorted list in sync with the map.
+pub struct ObservableBTreeMap<K, V> {
+    values: OrdMap<K, V>,
+    /// The keys of `values` in order, for finding the position of an entry in
+    /// logarithmic time.
+    keys: Vector<K>,
+    sender: Sender<BroadcastMessage<BTreeMapDiff<K, V>, OrdMap<K, V>>>,
+}
+
+impl<K, V> ObservableBTreeMap<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    /// Create a new `ObservableBTreeMap`.
+    ///
+    /// As of the time of writing, this is equivalent to
+    /// `ObservableBTreeMap::with_capacity(16)`, but the internal buffer
+    /// capacity is subject to change in non-breaking releases.
+    ///
+    /// See [`with_capacity`][Self::with_capacity] for details about the buffer
+    /// capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(16)
+    }
+
+    /// Create a new `ObservableBTreeMap` with the given capacity for the inner
+    /// buffer.
+    ///
+    /// Up to `capacity` updates that have not been received by all of the
+    /// subscribers yet will be retained in the inner buffer. If an update
+    /// happens while the buffer is at capacity, the oldest update is discarded
+    /// from it and all subscribers that have not yet received it will instead
+    /// see [`BTreeMapDiff::Reset`] as the next update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { values: OrdMap::new(), keys: Vector::new(), sender }
+    }
+
+    /// Turn the `ObservableBTreeMap` back into a regular `OrdMap`.
+    pub fn into_inner(self) -> OrdMap<K, V> {
+        self.values
+    }
+
+    /// Obtain a new subscriber for the whole map.
+    ///
+    /// If you put the `ObservableBTreeMap` behind a lock, it is highly
+    /// recommended to make access of the elements and subscribing one
+    /// operation. Otherwise, the values could be altered in between the
+    /// reading of the values and subscribing to changes.
+    pub fn subscribe(&self) -> BTreeMapSubscriber<K, V> {
+        self.subscribe_range(..)
+    }
+
+    /// Obtain a new subscriber for the entries with keys in the given range.
+    ///
+    /// The subscriber's initial values only contain the entries within the
+    /// range, and diffs for entries outside of the range are not forwarded to
+    /// it. Indices of the diffs it yields are relative to the start of the
+    /// range, i.e. index `0` refers to the entry with the smallest key within
+    /// the range.
+    pub fn subscribe_range<R: RangeBounds<K>>(&self, range: R) -> BTreeMapSubscriber<K, V> {
+        let rx = self.sender.subscribe();
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        BTreeMapSubscriber::new(&self.values, range, rx)
+    }
+
+    /// Insert a key-value pair into the map, notify subscribers and return the
+    /// previous value for that key, if any.
+    ///
+    /// Subscribers see [`BTreeMapDiff::Insert`] if the key was not present in
+    /// the map before, and [`BTreeMapDiff::Update`] otherwise.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old_value = self.values.insert(key.clone(), value.clone());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::btree_map::update",
+            update = old_value.is_some().then_some(true),
+            "insert"
+        );
+
+        let index = self.index_of(&key);
+        if old_value.is_none() {
+            self.keys.insert(index, key.clone());
+        }
+
+        self.broadcast_diff(|| match old_value {
+            Some(_) => BTreeMapDiff::Update { index, key, value },
+            None => BTreeMapDiff::Insert { index, key, value },
+        });
+        old_value
+    }
+
+    /// Remove the entry for the given key, notify subscribers and return the
+    /// removed value.
+    ///
+    /// If there is no entry for the given key, subscribers will not be notified
+    /// and this method will return `None`.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        let (key, value) = self.values.remove_with_key(key)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::btree_map::update", "remove");
+
+        let index = self.index_of(&key);
+        self.keys.remove(index);
+
+        self.broadcast_diff(|| BTreeMapDiff::Remove { index, key });
+        Some(value)
+    }
+
+    /// Clear out all of the entries in this `OrdMap` and notify subscribers.
+    pub fn clear(&mut self) {
+        let already_empty = self.values.is_empty();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::btree_map::update",
+            nop = already_empty.then_some(true),
+            "clear"
+        );
+
+        if !already_empty {
+            self.values.clear();
+            self.keys.clear();
+            self.broadcast_diff(|| BTreeMapDiff::Clear);
+        }
+    }
+
+    /// Get the position the given key has, or would have, in the map.
+    fn index_of(&self, key: &K) -> usize {
+        match self.keys.binary_search(key) {
+            Ok(index) | Err(index) => index,
+        }
+    }
+
+    fn broadcast_diff(&self, make_diff: impl FnOnce() -> BTreeMapDiff<K, V>) {
+        // Creating the diff clones the entry, so skip it if nobody listens.
+        if self.sender.receiver_count() != 0 {
+            let msg = BroadcastMessage {
+                diffs: OneOrManyDiffs::One(make_diff()),
+                state: self.values.clone(),
+            };
+            let _num_receivers = self.sender.send(msg).unwrap_or(0);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::btree_map::broadcast",
+                "New observable value broadcast to {_num_receivers} receivers"
+            );
+        }
+    }
+}
+
+impl<K, V> Default for ObservableBTreeMap<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableBTreeMap<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableBTreeMap").field("values", &self.values).finish_non_exhaustive()
+    }
+}
+
+// Note: No DerefMut because all mutating must go through inherent methods that
+// notify subscribers
+impl<K, V> ops::Deref for ObservableBTreeMap<K, V> {
+    type Target = OrdMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<K, V> From<OrdMap<K, V>> for ObservableBTreeMap<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn from(values: OrdMap<K, V>) -> Self {
+        let mut this = Self::new();
+        this.keys = values.keys().cloned().collect();
+        this.values = values;
+        this
+    }
+}
+
+/// A change to an [`ObservableBTreeMap`].
+///
+/// Indices refer to the position of the entry in the map (or the subscribed
+/// range of it), in key order.
+#[derive(Clone)]
+pub enum BTreeMapDiff<K, V> {
+    /// A new entry was inserted.
+    Insert {
+        /// The position of the new entry.
+        index: usize,
+        /// The key of the new entry.
+        key: K,
+        /// The value of the new entry.
+        value: V,
+    },
+    /// The value of an existing entry was replaced.
+    Update {
+        /// The position of the updated entry.
+        index: usize,
+        /// The key of the updated entry.
+        key: K,
+        /// The new value.
+        value: V,
+    },
+    /// An entry was removed.
+    Remove {
+        /// The position the removed entry had.
+        index: usize,
+        /// The key of the removed entry.
+        key: K,
+    },
+    /// The map was cleared.
+    Clear,
+    /// The subscriber lagged too far behind, and the next update that should
+    /// have been received has already been discarded from the internal buffer.
+    Reset {
+        /// The full map.
+        values: OrdMap<K, V>,
+    },
+}
+
+impl<K, V> BTreeMapDiff<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    /// Transform `BTreeMapDiff<K, V>` into `BTreeMapDiff<K, U>` by applying the
+    /// given function to any contained values.
+    pub fn map<U: Clone>(self, mut f: impl FnMut(V) -> U) -> BTreeMapDiff<K, U> {
+        match self {
+            BTreeMapDiff::Insert { index, key, value } => {
+                BTreeMapDiff::Insert { index, key, value: f(value) }
+            }
+            BTreeMapDiff::Update { index, key, value } => {
+                BTreeMapDiff::Update { index, key, value: f(value) }
+            }
+            BTreeMapDiff::Remove { index, key } => BTreeMapDiff::Remove { index, key },
+            BTreeMapDiff::Clear => BTreeMapDiff::Clear,
+            BTreeMapDiff::Reset { values } => BTreeMapDiff::Reset {
+                values: values.into_iter().map(|(key, value)| (key, f(value))).collect(),
+            },
+        }
+    }
+
+    /// Applies this [`BTreeMapDiff`] to a map.
+    ///
+    /// This is useful to keep two maps in sync, with potentially one
+    /// containing data [`map`](Self::map)ped from the other.
+    pub fn apply(self, map: &mut OrdMap<K, V>) {
+        match self {
+            BTreeMapDiff::Insert { key, value, .. } | BTreeMapDiff::Update { key, value, .. } => {
+                map.insert(key, value);
+            }
+            BTreeMapDiff::Remove { key, .. } => {
+                map.remove(&key);
+            }
+            BTreeMapDiff::Clear => {
+                map.clear();
+            }
+            BTreeMapDiff::Reset { values } => {
+                *map = values;
+            }
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for BTreeMapDiff<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insert { index, key, value } => f
+                .debug_struct("Insert")
+                .field("index", index)
+                .field("key", key)
+                .field("value", value)
+                .finish(),
+            Self::Update { index, key, value } => f
+                .debug_struct("Update")
+                .field("index", index)
+                .field("key", key)
+                .field("value", value)
+                .finish(),
+            Self::Remove { index, key } => {
+                f.debug_struct("Remove").field("index", index).field("key", key).finish()
+            }
+            Self::Clear => f.write_str("Clear"),
+            Self::Reset { values } => f.debug_struct("Reset").field("values", values).finish(),
+        }
+    }
+}
+
+impl<K, V> PartialEq for BTreeMapDiff<K, V>
+where
+    K: Ord + Clone,
+    V: PartialEq + Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Insert { index: i1, key: k1, value: v1 },
+                Self::Insert { index: i2, key: k2, value: v2 },
+            )
+            | (
+                Self::Update { index: i1, key: k1, value: v1 },
+                Self::Update { index: i2, key: k2, value: v2 },
+            ) => i1 == i2 && k1 == k2 && v1 == v2,
+            (Self::Remove { index: i1, key: k1 }, Self::Remove { index: i2, key: k2 }) => {
+                i1 == i2 && k1 == k2
+            }
+            (Self::Clear, Self::Clear) => true,
+            (Self::Reset { values: v1 }, Self::Reset { values: v2 }) => v1 == v2,
+            _ => false,
+        }
+    }
+}
+
+impl<K, V> Eq for BTreeMapDiff<K, V>
+where
+    K: Ord + Clone,
+    V: Eq + Clone,
+{
+}
+
+impl<K, V> Diff for BTreeMapDiff<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    type Values = OrdMap<K, V>;
+
+    fn reset(values: Self::Values) -> Self {
+        BTreeMapDiff::Reset { values }
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    fmt,
+    ops::{Bound, RangeBounds},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use imbl::OrdMap;
+use tokio::sync::broadcast::Receiver;
+
+use super::BTreeMapDiff;
+use crate::broadcast::{BatchedDiffStream, BroadcastMessage, DiffStream};
+
+/// A subscriber for updates of an [`OrdMap`], or a range of it.
+pub struct BTreeMapSubscriber<K, V> {
+    values: OrdMap<K, V>,
+    filter: RangeFilter<K>,
+    rx: Receiver<BroadcastMessage<BTreeMapDiff<K, V>, OrdMap<K, V>>>,
+}
+
+impl<K, V> BTreeMapSubscriber<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    pub(super) fn new(
+        values: &OrdMap<K, V>,
+        range: (Bound<K>, Bound<K>),
+        rx: Receiver<BroadcastMessage<BTreeMapDiff<K, V>, OrdMap<K, V>>>,
+    ) -> Self {
+        let mut filter = RangeFilter { range, offset: 0, len: 0 };
+        let values = filter.reset(values);
+        Self { values, filter, rx }
+    }
+
+    /// Get the entries (within the subscribed range) the
+    /// [`ObservableBTreeMap`][super::ObservableBTreeMap] contained when this
+    /// subscriber was created.
+    pub fn values(&self) -> OrdMap<K, V> {
+        self.values.clone()
+    }
+
+    /// Turn this `BTreeMapSubscriber` into a stream of `BTreeMapDiff`s.
+    pub fn into_stream(self) -> BTreeMapSubscriberStream<K, V> {
+        BTreeMapSubscriberStream::new(self.rx, self.filter)
+    }
+
+    /// Turn this `BTreeMapSubscriber` into a stream of `Vec<BTreeMapDiff>`s.
+    pub fn into_batched_stream(self) -> BTreeMapSubscriberBatchedStream<K, V> {
+        BTreeMapSubscriberBatchedStream::new(self.rx, self.filter)
+    }
+
+    /// Destructure this `BTreeMapSubscriber` into the initial values and a
+    /// stream of `BTreeMapDiff`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and `.into_stream()`
+    /// separately, but guarantees that the values are not unnecessarily cloned.
+    pub fn into_values_and_stream(self) -> (OrdMap<K, V>, BTreeMapSubscriberStream<K, V>) {
+        let Self { values, filter, rx } = self;
+        (values, BTreeMapSubscriberStream::new(rx, filter))
+    }
+
+    /// Destructure this `BTreeMapSubscriber` into the initial values and a
+    /// stream of `Vec<BTreeMapDiff>`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and
+    /// `.into_batched_stream()` separately, but guarantees that the values
+    /// are not unnecessarily cloned.
+    pub fn into_values_and_batched_stream(
+        self,
+    ) -> (OrdMap<K, V>, BTreeMapSubscriberBatchedStream<K, V>) {
+        let Self { values, filter, rx } = self;
+        (values, BTreeMapSubscriberBatchedStream::new(rx, filter))
+    }
+}
+
+impl<K, V> fmt::Debug for BTreeMapSubscriber<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BTreeMapSubscriber")
+            .field("values", &self.values)
+            .field("range", &self.filter.range)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A stream of `BTreeMapDiff`s created from a [`BTreeMapSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+pub struct BTreeMapSubscriberStream<K, V> {
+    inner: DiffStream<BTreeMapDiff<K, V>, OrdMap<K, V>>,
+    filter: RangeFilter<K>,
+}
+
+impl<K, V> BTreeMapSubscriberStream<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn new(
+        rx: Receiver<BroadcastMessage<BTreeMapDiff<K, V>, OrdMap<K, V>>>,
+        filter: RangeFilter<K>,
+    ) -> Self {
+        Self { inner: DiffStream::new(rx), filter }
+    }
+}
+
+impl<K, V> Stream for BTreeMapSubscriberStream<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    type Item = BTreeMapDiff<K, V>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match ready!(this.inner.poll_next(cx)) {
+                Some(diff) => {
+                    if let Some(diff) = this.filter.handle_diff(diff) {
+                        return Poll::Ready(Some(diff));
+                    }
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for BTreeMapSubscriberStream<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BTreeMapSubscriberStream")
+            .field("range", &self.filter.range)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A batched stream of `BTreeMapDiff`s created from a [`BTreeMapSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+pub struct BTreeMapSubscriberBatchedStream<K, V> {
+    inner: BatchedDiffStream<BTreeMapDiff<K, V>, OrdMap<K, V>>,
+    filter: RangeFilter<K>,
+}
+
+impl<K, V> BTreeMapSubscriberBatchedStream<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn new(
+        rx: Receiver<BroadcastMessage<BTreeMapDiff<K, V>, OrdMap<K, V>>>,
+        filter: RangeFilter<K>,
+    ) -> Self {
+        Self { inner: BatchedDiffStream::new(rx), filter }
+    }
+}
+
+impl<K, V> Stream for BTreeMapSubscriberBatchedStream<K, V>
+where
+    K: Ord + Clone + 'static,
+    V: Clone + 'static,
+{
+    type Item = Vec<BTreeMapDiff<K, V>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let diffs = match ready!(this.inner.poll_next(cx)) {
+                Some(diffs) => diffs,
+                None => return Poll::Ready(None),
+            };
+
+            let diffs: Vec<_> =
+                diffs.into_iter().filter_map(|diff| this.filter.handle_diff(diff)).collect();
+            if !diffs.is_empty() {
+                return Poll::Ready(Some(diffs));
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for BTreeMapSubscriberBatchedStream<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BTreeMapSubscriberBatchedStream")
+            .field("range", &self.filter.range)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Restricts the diffs of the whole map to a range of keys, re-basing their
+/// indices on the start of the range.
+#[derive(Debug)]
+struct RangeFilter<K> {
+    range: (Bound<K>, Bound<K>),
+    /// The number of entries with keys below the range.
+    offset: usize,
+    /// The number of entries with keys within the range.
+    len: usize,
+}
+
+// The filter is never pinned, so this is fine regardless of `K`
+impl<K> Unpin for RangeFilter<K> {}
+
+impl<K: Ord + Clone> RangeFilter<K> {
+    /// Restrict the given full map to the range, resetting the entry counts.
+    fn reset<V: Clone>(&mut self, values: &OrdMap<K, V>) -> OrdMap<K, V> {
+        self.offset = match &self.range.0 {
+            Bound::Included(start) => values.range(..start.clone()).count(),
+            Bound::Excluded(start) => values.range(..=start.clone()).count(),
+            Bound::Unbounded => 0,
+        };
+
+        if let (Bound::Unbounded, Bound::Unbounded) = &self.range {
+            self.len = values.len();
+            return values.clone();
+        }
+
+        let values: OrdMap<_, _> = values
+            .range(self.range.clone())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        self.len = values.len();
+        values
+    }
+
+    fn is_below(&self, key: &K) -> bool {
+        match &self.range.0 {
+            Bound::Included(start) => key < start,
+            Bound::Excluded(start) => key <= start,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn handle_diff<V: Clone>(&mut self, diff: BTreeMapDiff<K, V>) -> Option<BTreeMapDiff<K, V>> {
+        match diff {
+            BTreeMapDiff::Insert { index, key, value } => {
+                if self.is_below(&key) {
+                    self.offset += 1;
+                    None
+                } else if self.range.contains(&key) {
+                    self.len += 1;
+                    Some(BTreeMapDiff::Insert { index: index - self.offset, key, value })
+                } else {
+                    None
+                }
+            }
+            BTreeMapDiff::Update { index, key, value } => self
+                .range
+                .contains(&key)
+                .then(|| BTreeMapDiff::Update { index: index - self.offset, key, value }),
+            BTreeMapDiff::Remove { index, key } => {
+                if self.is_below(&key) {
+                    self.offset -= 1;
+                    None
+                } else if self.range.contains(&key) {
+                    self.len -= 1;
+                    Some(BTreeMapDiff::Remove { index: index - self.offset, key })
+                } else {
+                    None
+                }
+            }
+            BTreeMapDiff::Clear => {
+                let was_empty = self.len == 0;
+                self.offset = 0;
+                self.len = 0;
+                (!was_empty).then_some(BTreeMapDiff::Clear)
+            }
+            BTreeMapDiff::Reset { values } => {
+                Some(BTreeMapDiff::Reset { values: self.reset(&values) })
+            }
+        }
+    }
+}
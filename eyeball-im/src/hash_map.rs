@@ -0,0 +1,303 @@
+use std::{borrow::Borrow, fmt, hash::Hash, ops};
+
+use imbl::HashMap;
+use tokio::sync::broadcast::{self, Sender};
+
+use crate::broadcast::{BroadcastMessage, Diff, OneOrManyDiffs};
+
+mod subscriber;
+mod transaction;
+
+pub use self::{
+    subscriber::{HashMapSubscriber, HashMapSubscriberBatchedStream, HashMapSubscriberStream},
+    transaction::ObservableHashMapTransaction,
+};
+
+/// An unordered map of keys to values that broadcasts any changes made to it.
+pub struct ObservableHashMap<K, V> {
+    values: HashMap<K, V>,
+    sender: Sender<BroadcastMessage<MapDiff<K, V>, HashMap<K, V>>>,
+}
+
+impl<K, V> ObservableHashMap<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    /// Create a new `ObservableHashMap`.
+    ///
+    /// As of the time of writing, this is equivalent to
+    /// `ObservableHashMap::with_capacity(16)`, but the internal buffer capacity
+    /// is subject to change in non-breaking releases.
+    ///
+    /// See [`with_capacity`][Self::with_capacity] for details about the buffer
+    /// capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(16)
+    }
+
+    /// Create a new `ObservableHashMap` with the given capacity for the inner
+    /// buffer.
+    ///
+    /// Up to `capacity` updates that have not been received by all of the
+    /// subscribers yet will be retained in the inner buffer. If an update
+    /// happens while the buffer is at capacity, the oldest update is discarded
+    /// from it and all subscribers that have not yet received it will instead
+    /// see [`MapDiff::Reset`] as the next update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { values: HashMap::new(), sender }
+    }
+
+    /// Turn the `ObservableHashMap` back into a regular `HashMap`.
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.values
+    }
+
+    /// Obtain a new subscriber.
+    ///
+    /// If you put the `ObservableHashMap` behind a lock, it is highly
+    /// recommended to make access of the elements and subscribing one
+    /// operation. Otherwise, the values could be altered in between the
+    /// reading of the values and subscribing to changes.
+    pub fn subscribe(&self) -> HashMapSubscriber<K, V> {
+        let rx = self.sender.subscribe();
+        HashMapSubscriber::new(self.values.clone(), rx)
+    }
+
+    /// Insert a key-value pair into the map, notify subscribers and return the
+    /// previous value for that key, if any.
+    ///
+    /// Subscribers see [`MapDiff::Insert`] if the key was not present in the
+    /// map before, and [`MapDiff::Update`] otherwise.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old_value = self.values.insert(key.clone(), value.clone());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::hash_map::update",
+            update = old_value.is_some().then_some(true),
+            "insert"
+        );
+
+        let diff = match old_value {
+            Some(_) => MapDiff::Update { key, value },
+            None => MapDiff::Insert { key, value },
+        };
+        self.broadcast_diff(diff);
+        old_value
+    }
+
+    /// Remove the entry for the given key, notify subscribers and return the
+    /// removed value.
+    ///
+    /// If there is no entry for the given key, subscribers will not be notified
+    /// and this method will return `None`.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Hash + Eq + ?Sized,
+        K: Borrow<Q>,
+    {
+        let (key, value) = self.values.remove_with_key(key)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::hash_map::update", "remove");
+
+        self.broadcast_diff(MapDiff::Remove { key });
+        Some(value)
+    }
+
+    /// Clear out all of the entries in this `HashMap` and notify subscribers.
+    pub fn clear(&mut self) {
+        let already_empty = self.values.is_empty();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::hash_map::update",
+            nop = already_empty.then_some(true),
+            "clear"
+        );
+
+        if !already_empty {
+            self.values.clear();
+            self.broadcast_diff(MapDiff::Clear);
+        }
+    }
+
+    /// Start a new transaction to make multiple updates as one unit.
+    ///
+    /// See [`ObservableHashMapTransaction`]s documentation for more details.
+    pub fn transaction(&mut self) -> ObservableHashMapTransaction<'_, K, V> {
+        ObservableHashMapTransaction::new(self)
+    }
+
+    fn broadcast_diff(&self, diff: MapDiff<K, V>) {
+        if self.sender.receiver_count() != 0 {
+            let msg =
+                BroadcastMessage { diffs: OneOrManyDiffs::One(diff), state: self.values.clone() };
+            let _num_receivers = self.sender.send(msg).unwrap_or(0);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::hash_map::broadcast",
+                "New observable value broadcast to {_num_receivers} receivers"
+            );
+        }
+    }
+}
+
+impl<K, V> Default for ObservableHashMap<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableHashMap<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableHashMap").field("values", &self.values).finish_non_exhaustive()
+    }
+}
+
+// Note: No DerefMut because all mutating must go through inherent methods that
+// notify subscribers
+impl<K, V> ops::Deref for ObservableHashMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<K, V> From<HashMap<K, V>> for ObservableHashMap<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn from(values: HashMap<K, V>) -> Self {
+        let mut this = Self::new();
+        this.values = values;
+        this
+    }
+}
+
+/// A change to an [`ObservableHashMap`].
+#[derive(Clone, Debug)]
+pub enum MapDiff<K, V> {
+    /// A new entry was inserted.
+    Insert {
+        /// The key of the new entry.
+        key: K,
+        /// The value of the new entry.
+        value: V,
+    },
+    /// The value of an existing entry was replaced.
+    Update {
+        /// The key of the updated entry.
+        key: K,
+        /// The new value.
+        value: V,
+    },
+    /// An entry was removed.
+    Remove {
+        /// The key of the removed entry.
+        key: K,
+    },
+    /// The map was cleared.
+    Clear,
+    /// The subscriber lagged too far behind, and the next update that should
+    /// have been received has already been discarded from the internal buffer.
+    Reset {
+        /// The full map.
+        values: HashMap<K, V>,
+    },
+}
+
+impl<K, V> MapDiff<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Transform `MapDiff<K, V>` into `MapDiff<K, U>` by applying the given
+    /// function to any contained values.
+    pub fn map<U: Clone>(self, mut f: impl FnMut(V) -> U) -> MapDiff<K, U> {
+        match self {
+            MapDiff::Insert { key, value } => MapDiff::Insert { key, value: f(value) },
+            MapDiff::Update { key, value } => MapDiff::Update { key, value: f(value) },
+            MapDiff::Remove { key } => MapDiff::Remove { key },
+            MapDiff::Clear => MapDiff::Clear,
+            MapDiff::Reset { values } => MapDiff::Reset {
+                values: values.into_iter().map(|(key, value)| (key, f(value))).collect(),
+            },
+        }
+    }
+
+    /// Applies this [`MapDiff`] to a map.
+    ///
+    /// This is useful to keep two maps in sync, with potentially one
+    /// containing data [`map`](Self::map)ped from the other.
+    pub fn apply(self, map: &mut HashMap<K, V>) {
+        match self {
+            MapDiff::Insert { key, value } | MapDiff::Update { key, value } => {
+                map.insert(key, value);
+            }
+            MapDiff::Remove { key } => {
+                map.remove(&key);
+            }
+            MapDiff::Clear => {
+                map.clear();
+            }
+            MapDiff::Reset { values } => {
+                *map = values;
+            }
+        }
+    }
+}
+
+impl<K, V> PartialEq for MapDiff<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: PartialEq + Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Insert { key: k1, value: v1 }, Self::Insert { key: k2, value: v2 })
+            | (Self::Update { key: k1, value: v1 }, Self::Update { key: k2, value: v2 }) => {
+                k1 == k2 && v1 == v2
+            }
+            (Self::Remove { key: k1 }, Self::Remove { key: k2 }) => k1 == k2,
+            (Self::Clear, Self::Clear) => true,
+            (Self::Reset { values: v1 }, Self::Reset { values: v2 }) => v1 == v2,
+            _ => false,
+        }
+    }
+}
+
+impl<K, V> Eq for MapDiff<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Eq + Clone,
+{
+}
+
+impl<K, V> Diff for MapDiff<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    type Values = HashMap<K, V>;
+
+    fn reset(values: Self::Values) -> Self {
+        MapDiff::Reset { values }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    hash::Hash,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use imbl::HashMap;
+use tokio::sync::broadcast::Receiver;
+
+use super::MapDiff;
+use crate::broadcast::{BatchedDiffStream, BroadcastMessage, DiffStream};
+
+/// A subscriber for updates of a [`HashMap`].
+#[derive(Debug)]
+pub struct HashMapSubscriber<K, V> {
+    values: HashMap<K, V>,
+    rx: Receiver<BroadcastMessage<MapDiff<K, V>, HashMap<K, V>>>,
+}
+
+impl<K, V> HashMapSubscriber<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    pub(super) fn new(
+        values: HashMap<K, V>,
+        rx: Receiver<BroadcastMessage<MapDiff<K, V>, HashMap<K, V>>>,
+    ) -> Self {
+        Self { values, rx }
+    }
+
+    /// Get the entries the [`ObservableHashMap`][super::ObservableHashMap]
+    /// contained when this subscriber was created.
+    pub fn values(&self) -> HashMap<K, V> {
+        self.values.clone()
+    }
+
+    /// Turn this `HashMapSubscriber` into a stream of `MapDiff`s.
+    pub fn into_stream(self) -> HashMapSubscriberStream<K, V> {
+        HashMapSubscriberStream::new(self.rx)
+    }
+
+    /// Turn this `HashMapSubscriber` into a stream of `Vec<MapDiff>`s.
+    pub fn into_batched_stream(self) -> HashMapSubscriberBatchedStream<K, V> {
+        HashMapSubscriberBatchedStream::new(self.rx)
+    }
+
+    /// Destructure this `HashMapSubscriber` into the initial values and a
+    /// stream of `MapDiff`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and `.into_stream()`
+    /// separately, but guarantees that the values are not unnecessarily cloned.
+    pub fn into_values_and_stream(self) -> (HashMap<K, V>, HashMapSubscriberStream<K, V>) {
+        let Self { values, rx } = self;
+        (values, HashMapSubscriberStream::new(rx))
+    }
+
+    /// Destructure this `HashMapSubscriber` into the initial values and a
+    /// stream of `Vec<MapDiff>`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and
+    /// `.into_batched_stream()` separately, but guarantees that the values
+    /// are not unnecessarily cloned.
+    pub fn into_values_and_batched_stream(
+        self,
+    ) -> (HashMap<K, V>, HashMapSubscriberBatchedStream<K, V>) {
+        let Self { values, rx } = self;
+        (values, HashMapSubscriberBatchedStream::new(rx))
+    }
+}
+
+/// A stream of `MapDiff`s created from a [`HashMapSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct HashMapSubscriberStream<K, V> {
+    inner: DiffStream<MapDiff<K, V>, HashMap<K, V>>,
+}
+
+impl<K, V> HashMapSubscriberStream<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    fn new(rx: Receiver<BroadcastMessage<MapDiff<K, V>, HashMap<K, V>>>) -> Self {
+        Self { inner: DiffStream::new(rx) }
+    }
+}
+
+impl<K, V> Stream for HashMapSubscriberStream<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    type Item = MapDiff<K, V>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
+
+/// A batched stream of `MapDiff`s created from a [`HashMapSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct HashMapSubscriberBatchedStream<K, V> {
+    inner: BatchedDiffStream<MapDiff<K, V>, HashMap<K, V>>,
+}
+
+impl<K, V> HashMapSubscriberBatchedStream<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    fn new(rx: Receiver<BroadcastMessage<MapDiff<K, V>, HashMap<K, V>>>) -> Self {
+        Self { inner: BatchedDiffStream::new(rx) }
+    }
+}
+
+impl<K, V> Stream for HashMapSubscriberBatchedStream<K, V>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+{
+    type Item = Vec<MapDiff<K, V>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
@@ -6,14 +6,36 @@
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+mod blocking;
+mod broadcast;
+mod btree_map;
+mod hash_map;
 mod reusable_box;
+mod set;
 mod vector;
 
+pub use blocking::{BlockingIter, Elapsed};
+pub use broadcast::{LagPolicy, Lagged};
+pub use btree_map::{
+    BTreeMapDiff, BTreeMapSubscriber, BTreeMapSubscriberBatchedStream, BTreeMapSubscriberStream,
+    ObservableBTreeMap,
+};
+pub use hash_map::{
+    HashMapSubscriber, HashMapSubscriberBatchedStream, HashMapSubscriberStream, MapDiff,
+    ObservableHashMap, ObservableHashMapTransaction,
+};
+pub use set::{
+    ObservableHashSet, ObservableOrdSet, SetDiff, SetSubscriber, SetSubscriberBatchedStream,
+    SetSubscriberStream,
+};
 pub use vector::{
-    ObservableVector, ObservableVectorEntries, ObservableVectorEntry, ObservableVectorTransaction,
-    ObservableVectorTransactionEntries, ObservableVectorTransactionEntry, VectorDiff,
-    VectorSubscriber, VectorSubscriberBatchedStream, VectorSubscriberStream,
+    ObservableVector, ObservableVectorEntries, ObservableVectorEntry,
+    ObservableVectorNestedTransaction, ObservableVectorTransaction,
+    ObservableVectorTransactionEntries, ObservableVectorTransactionEntry,
+    ObservableVectorTransactionSavepoint, VectorDiff, VectorSubscriber,
+    VectorSubscriberBatchedStream, VectorSubscriberFallibleBatchedStream,
+    VectorSubscriberFallibleStream, VectorSubscriberStream,
 };
 
 #[doc(no_inline)]
-pub use imbl::Vector;
+pub use imbl::{HashMap, HashSet, OrdMap, OrdSet, Vector};
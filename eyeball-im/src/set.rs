@@ -0,0 +1,102 @@
+use std::hash::Hash;
+
+use imbl::{HashSet, OrdSet};
+
+use crate::broadcast::{BroadcastMessage, Diff};
+
+mod observable;
+mod subscriber;
+
+pub use self::{
+    observable::{ObservableHashSet, ObservableOrdSet},
+    subscriber::{SetSubscriber, SetSubscriberBatchedStream, SetSubscriberStream},
+};
+
+type SetMessage<T, S> = BroadcastMessage<SetDiff<T, S>, S>;
+
+/// A change to an [`ObservableHashSet`] or [`ObservableOrdSet`].
+///
+/// `S` is the type of the set, i.e. [`HashSet<T>`] or [`OrdSet<T>`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetDiff<T, S> {
+    /// A value was added to the set.
+    Insert {
+        /// The added value.
+        value: T,
+    },
+    /// A value was removed from the set.
+    Remove {
+        /// The removed value.
+        value: T,
+    },
+    /// The set was cleared.
+    Clear,
+    /// The subscriber lagged too far behind, and the next update that should
+    /// have been received has already been discarded from the internal buffer.
+    Reset {
+        /// The full set.
+        values: S,
+    },
+}
+
+impl<T> SetDiff<T, HashSet<T>>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Applies this [`SetDiff`] to a set.
+    ///
+    /// This is useful to keep two sets in sync.
+    pub fn apply(self, set: &mut HashSet<T>) {
+        match self {
+            SetDiff::Insert { value } => {
+                set.insert(value);
+            }
+            SetDiff::Remove { value } => {
+                set.remove(&value);
+            }
+            SetDiff::Clear => {
+                set.clear();
+            }
+            SetDiff::Reset { values } => {
+                *set = values;
+            }
+        }
+    }
+}
+
+impl<T> SetDiff<T, OrdSet<T>>
+where
+    T: Ord + Clone,
+{
+    /// Applies this [`SetDiff`] to a set.
+    ///
+    /// This is useful to keep two sets in sync.
+    pub fn apply(self, set: &mut OrdSet<T>) {
+        match self {
+            SetDiff::Insert { value } => {
+                set.insert(value);
+            }
+            SetDiff::Remove { value } => {
+                set.remove(&value);
+            }
+            SetDiff::Clear => {
+                set.clear();
+            }
+            SetDiff::Reset { values } => {
+                *set = values;
+            }
+        }
+    }
+}
+
+impl<T, S> Diff for SetDiff<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    type Values = S;
+
+    fn reset(values: Self::Values) -> Self {
+        SetDiff::Reset { values }
+    }
+}
@@ -0,0 +1,197 @@
+use std::{borrow::Borrow, fmt, hash::Hash, ops};
+
+use imbl::{HashSet, OrdSet};
+use tokio::sync::broadcast::{self, Sender};
+
+use super::{SetDiff, SetMessage, SetSubscriber};
+use crate::broadcast::{BroadcastMessage, OneOrManyDiffs};
+
+/// Define an observable wrapper around one of `imbl`'s sets.
+///
+/// The wrappers only differ in the bounds their values need, so they share a
+/// single implementation.
+macro_rules! observable_set {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident($set:ident) where T: $bound:ident $(+ $bounds:ident)*;
+        tracing_module = $module:literal;
+    ) => {
+        $(#[$attr])*
+        pub struct $name<T> {
+            values: $set<T>,
+            sender: Sender<SetMessage<T, $set<T>>>,
+        }
+
+        impl<T: $bound $(+ $bounds)* + Clone + 'static> $name<T> {
+            #[doc = concat!("Create a new `", stringify!($name), "`.")]
+            ///
+            /// As of the time of writing, this is equivalent to
+            #[doc = concat!("`", stringify!($name), "::with_capacity(16)`, but the internal")]
+            /// buffer capacity is subject to change in non-breaking releases.
+            ///
+            /// See [`with_capacity`][Self::with_capacity] for details about the
+            /// buffer capacity.
+            pub fn new() -> Self {
+                Self::with_capacity(16)
+            }
+
+            #[doc = concat!("Create a new `", stringify!($name), "` with the given capacity")]
+            /// for the inner buffer.
+            ///
+            /// Up to `capacity` updates that have not been received by all of
+            /// the subscribers yet will be retained in the inner buffer. If an
+            /// update happens while the buffer is at capacity, the oldest
+            /// update is discarded from it and all subscribers that have not
+            /// yet received it will instead see [`SetDiff::Reset`] as the next
+            /// update.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
+            pub fn with_capacity(capacity: usize) -> Self {
+                let (sender, _) = broadcast::channel(capacity);
+                Self { values: $set::new(), sender }
+            }
+
+            #[doc = concat!(
+                "Turn the `", stringify!($name), "` back into a regular `", stringify!($set), "`."
+            )]
+            pub fn into_inner(self) -> $set<T> {
+                self.values
+            }
+
+            /// Obtain a new subscriber.
+            ///
+            #[doc = concat!("If you put the `", stringify!($name), "` behind a lock, it is")]
+            /// highly recommended to make access of the elements and
+            /// subscribing one operation. Otherwise, the values could be
+            /// altered in between the reading of the values and subscribing to
+            /// changes.
+            pub fn subscribe(&self) -> SetSubscriber<T, $set<T>> {
+                let rx = self.sender.subscribe();
+                SetSubscriber::new(self.values.clone(), rx)
+            }
+
+            /// Add a value to the set and notify subscribers.
+            ///
+            /// Returns whether the value was newly inserted. If the set already
+            /// contained an equal value, subscribers will not be notified.
+            pub fn insert(&mut self, value: T) -> bool {
+                if self.values.insert(value.clone()).is_some() {
+                    return false;
+                }
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(target: concat!("eyeball_im::", $module, "::update"), "insert");
+
+                self.broadcast_diff(SetDiff::Insert { value });
+                true
+            }
+
+            /// Remove a value from the set and notify subscribers.
+            ///
+            /// Returns whether the value was present in the set. If it wasn't,
+            /// subscribers will not be notified.
+            pub fn remove<Q>(&mut self, value: &Q) -> bool
+            where
+                Q: $bound $(+ $bounds)* + ?Sized,
+                T: Borrow<Q>,
+            {
+                match self.values.remove(value) {
+                    Some(value) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            target: concat!("eyeball_im::", $module, "::update"),
+                            "remove"
+                        );
+
+                        self.broadcast_diff(SetDiff::Remove { value });
+                        true
+                    }
+                    None => false,
+                }
+            }
+
+            #[doc = concat!(
+                "Clear out all of the values in this `", stringify!($set), "` and notify subscribers."
+            )]
+            pub fn clear(&mut self) {
+                let already_empty = self.values.is_empty();
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    target: concat!("eyeball_im::", $module, "::update"),
+                    nop = already_empty.then_some(true),
+                    "clear"
+                );
+
+                if !already_empty {
+                    self.values.clear();
+                    self.broadcast_diff(SetDiff::Clear);
+                }
+            }
+
+            fn broadcast_diff(&self, diff: SetDiff<T, $set<T>>) {
+                if self.sender.receiver_count() != 0 {
+                    let msg = BroadcastMessage {
+                        diffs: OneOrManyDiffs::One(diff),
+                        state: self.values.clone(),
+                    };
+                    let _num_receivers = self.sender.send(msg).unwrap_or(0);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        target: concat!("eyeball_im::", $module, "::broadcast"),
+                        "New observable value broadcast to {_num_receivers} receivers"
+                    );
+                }
+            }
+        }
+
+        impl<T: $bound $(+ $bounds)* + Clone + 'static> Default for $name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> fmt::Debug for $name<T>
+        where
+            T: $bound $(+ $bounds)* + fmt::Debug,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("values", &self.values)
+                    .finish_non_exhaustive()
+            }
+        }
+
+        // Note: No DerefMut because all mutating must go through inherent
+        // methods that notify subscribers
+        impl<T> ops::Deref for $name<T> {
+            type Target = $set<T>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.values
+            }
+        }
+
+        impl<T: $bound $(+ $bounds)* + Clone + 'static> From<$set<T>> for $name<T> {
+            fn from(values: $set<T>) -> Self {
+                let mut this = Self::new();
+                this.values = values;
+                this
+            }
+        }
+    };
+}
+
+observable_set! {
+    /// An unordered set of values that broadcasts any changes made to it.
+    pub struct ObservableHashSet(HashSet) where T: Hash + Eq;
+    tracing_module = "hash_set";
+}
+
+observable_set! {
+    /// An ordered set of values that broadcasts any changes made to it.
+    pub struct ObservableOrdSet(OrdSet) where T: Ord;
+    tracing_module = "ord_set";
+}
@@ -0,0 +1,131 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::sync::broadcast::Receiver;
+
+use super::{SetDiff, SetMessage};
+use crate::broadcast::{BatchedDiffStream, DiffStream};
+
+/// A subscriber for updates of an [`ObservableHashSet`] or
+/// [`ObservableOrdSet`].
+///
+/// [`ObservableHashSet`]: super::ObservableHashSet
+/// [`ObservableOrdSet`]: super::ObservableOrdSet
+#[derive(Debug)]
+pub struct SetSubscriber<T, S> {
+    values: S,
+    rx: Receiver<SetMessage<T, S>>,
+}
+
+impl<T, S> SetSubscriber<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    pub(super) fn new(values: S, rx: Receiver<SetMessage<T, S>>) -> Self {
+        Self { values, rx }
+    }
+
+    /// Get the values the set contained when this subscriber was created.
+    pub fn values(&self) -> S {
+        self.values.clone()
+    }
+
+    /// Turn this `SetSubscriber` into a stream of `SetDiff`s.
+    pub fn into_stream(self) -> SetSubscriberStream<T, S> {
+        SetSubscriberStream::new(self.rx)
+    }
+
+    /// Turn this `SetSubscriber` into a stream of `Vec<SetDiff>`s.
+    pub fn into_batched_stream(self) -> SetSubscriberBatchedStream<T, S> {
+        SetSubscriberBatchedStream::new(self.rx)
+    }
+
+    /// Destructure this `SetSubscriber` into the initial values and a stream
+    /// of `SetDiff`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and `.into_stream()`
+    /// separately, but guarantees that the values are not unnecessarily cloned.
+    pub fn into_values_and_stream(self) -> (S, SetSubscriberStream<T, S>) {
+        let Self { values, rx } = self;
+        (values, SetSubscriberStream::new(rx))
+    }
+
+    /// Destructure this `SetSubscriber` into the initial values and a stream
+    /// of `Vec<SetDiff>`s.
+    ///
+    /// Semantically equivalent to calling `.values()` and
+    /// `.into_batched_stream()` separately, but guarantees that the values
+    /// are not unnecessarily cloned.
+    pub fn into_values_and_batched_stream(self) -> (S, SetSubscriberBatchedStream<T, S>) {
+        let Self { values, rx } = self;
+        (values, SetSubscriberBatchedStream::new(rx))
+    }
+}
+
+/// A stream of `SetDiff`s created from a [`SetSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct SetSubscriberStream<T, S> {
+    inner: DiffStream<SetDiff<T, S>, S>,
+}
+
+impl<T, S> SetSubscriberStream<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    fn new(rx: Receiver<SetMessage<T, S>>) -> Self {
+        Self { inner: DiffStream::new(rx) }
+    }
+}
+
+impl<T, S> Stream for SetSubscriberStream<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    type Item = SetDiff<T, S>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
+
+/// A batched stream of `SetDiff`s created from a [`SetSubscriber`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct SetSubscriberBatchedStream<T, S> {
+    inner: BatchedDiffStream<SetDiff<T, S>, S>,
+}
+
+impl<T, S> SetSubscriberBatchedStream<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    fn new(rx: Receiver<SetMessage<T, S>>) -> Self {
+        Self { inner: BatchedDiffStream::new(rx) }
+    }
+}
+
+impl<T, S> Stream for SetSubscriberBatchedStream<T, S>
+where
+    T: Clone + 'static,
+    S: Clone + 'static,
+{
+    type Item = Vec<SetDiff<T, S>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
@@ -1,25 +1,37 @@
-use std::{fmt, ops};
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{self, Bound, RangeBounds},
+};
 
 use imbl::Vector;
-use tokio::sync::broadcast::{self, Sender};
 
+use crate::broadcast::{BroadcastMessage, Diff, DiffSender, LagPolicy, OneOrManyDiffs};
+
+mod bulk;
+mod compact;
+mod compute;
 mod entry;
 mod subscriber;
 mod transaction;
 
 pub use self::{
     entry::{ObservableVectorEntries, ObservableVectorEntry},
-    subscriber::{VectorSubscriber, VectorSubscriberBatchedStream, VectorSubscriberStream},
+    subscriber::{
+        VectorSubscriber, VectorSubscriberBatchedStream, VectorSubscriberFallibleBatchedStream,
+        VectorSubscriberFallibleStream, VectorSubscriberStream,
+    },
     transaction::{
-        ObservableVectorTransaction, ObservableVectorTransactionEntries,
-        ObservableVectorTransactionEntry,
+        ObservableVectorNestedTransaction, ObservableVectorTransaction,
+        ObservableVectorTransactionEntries, ObservableVectorTransactionEntry,
+        ObservableVectorTransactionSavepoint,
     },
 };
 
 /// An ordered list of elements that broadcasts any changes made to it.
 pub struct ObservableVector<T> {
     values: Vector<T>,
-    sender: Sender<BroadcastMessage<T>>,
+    sender: DiffSender<VectorDiff<T>, Vector<T>>,
 }
 
 impl<T: Clone + 'static> ObservableVector<T> {
@@ -42,14 +54,15 @@ impl<T: Clone + 'static> ObservableVector<T> {
     /// subscribers yet will be retained in the inner buffer. If an update
     /// happens while the buffer is at capacity, the oldest update is discarded
     /// from it and all subscribers that have not yet received it will instead
-    /// see [`VectorDiff::Reset`] as the next update.
+    /// see [`VectorDiff::Reset`] as the next update. See
+    /// [`subscribe_with_lag_policy`][Self::subscribe_with_lag_policy] for other
+    /// ways of handling this.
     ///
     /// # Panics
     ///
     /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
     pub fn with_capacity(capacity: usize) -> Self {
-        let (sender, _) = broadcast::channel(capacity);
-        Self { values: Vector::new(), sender }
+        Self { values: Vector::new(), sender: DiffSender::new(capacity) }
     }
 
     /// Turn the `ObservableVector` back into a regular `Vector`.
@@ -64,8 +77,22 @@ impl<T: Clone + 'static> ObservableVector<T> {
     /// operation. Otherwise, the values could be altered in between the
     /// reading of the values and subscribing to changes.
     pub fn subscribe(&self) -> VectorSubscriber<T> {
-        let rx = self.sender.subscribe();
-        VectorSubscriber::new(self.values.clone(), rx)
+        self.subscribe_with_lag_policy(LagPolicy::Reset)
+    }
+
+    /// Obtain a new subscriber with the given policy for when it lags behind
+    /// by more than the capacity of the inner buffer.
+    ///
+    /// `subscribe()` is equivalent to calling this method with
+    /// [`LagPolicy::Reset`]. With [`LagPolicy::Error`], use the subscriber's
+    /// fallible streams, such as
+    /// [`into_fallible_stream`][VectorSubscriber::into_fallible_stream], to
+    /// observe the error.
+    ///
+    /// See [`subscribe`][Self::subscribe] for further details.
+    pub fn subscribe_with_lag_policy(&self, lag_policy: LagPolicy) -> VectorSubscriber<T> {
+        let rx = self.sender.subscribe(lag_policy);
+        VectorSubscriber::new(self.values.clone(), rx, lag_policy)
     }
 
     /// Append the given elements at the end of the `Vector` and notify
@@ -218,6 +245,257 @@ impl<T: Clone + 'static> ObservableVector<T> {
         }
     }
 
+    /// Move the element at position `from` to position `to` and notify
+    /// subscribers.
+    ///
+    /// The elements between the two positions are shifted by one, such that
+    /// the element ends up at index `to` afterwards. Does nothing if `from` and
+    /// `to` are equal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from >= len` or `to >= len`.
+    #[track_caller]
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let len = self.values.len();
+        if from >= len {
+            panic!("index out of bounds: the length is {len} but the index is {from}");
+        }
+        if to >= len {
+            panic!("index out of bounds: the length is {len} but the index is {to}");
+        }
+
+        if from != to {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::update",
+                "move_item(from = {from}, to = {to})"
+            );
+
+            let value = self.values.remove(from);
+            self.values.insert(to, value);
+            self.broadcast_diff(VectorDiff::Move { from, to });
+        }
+    }
+
+    /// Swap the elements at positions `first` and `second` and notify
+    /// subscribers.
+    ///
+    /// Does nothing if `first` and `second` are equal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `first >= len` or `second >= len`.
+    #[track_caller]
+    pub fn swap(&mut self, first: usize, second: usize) {
+        let len = self.values.len();
+        if first >= len {
+            panic!("index out of bounds: the length is {len} but the index is {first}");
+        }
+        if second >= len {
+            panic!("index out of bounds: the length is {len} but the index is {second}");
+        }
+
+        if first != second {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::update",
+                "swap(first = {first}, second = {second})"
+            );
+
+            self.values.swap(first, second);
+            self.broadcast_diff(VectorDiff::Swap { first, second });
+        }
+    }
+
+    /// Insert the given elements at the given position and notify
+    /// subscribers.
+    ///
+    /// Subscribers are notified with a single [`VectorDiff::InsertMany`]. Does
+    /// nothing if `values` is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    #[track_caller]
+    pub fn insert_many(&mut self, index: usize, values: Vector<T>) {
+        let len = self.values.len();
+        if index > len {
+            panic!("index out of bounds: the length is {len} but the index is {index}");
+        }
+
+        if !values.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::update",
+                "insert_many(index = {index}, len = {})",
+                values.len()
+            );
+
+            splice_values(&mut self.values, index..index, values.clone());
+            self.broadcast_diff(VectorDiff::InsertMany { index, values });
+        }
+    }
+
+    /// Remove the elements in the given range, notify subscribers and return
+    /// the elements.
+    ///
+    /// Subscribers are notified with a single [`VectorDiff::RemoveRange`].
+    /// Does nothing if the range is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or if it ends after the end
+    /// of the vector.
+    #[track_caller]
+    pub fn remove_range(&mut self, range: impl RangeBounds<usize>) -> Vector<T> {
+        let ops::Range { start, end } = resolve_range(range, self.values.len());
+        if start == end {
+            return Vector::new();
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::update",
+            "remove_range(start = {start}, end = {end})"
+        );
+
+        let values = splice_values(&mut self.values, start..end, Vector::new());
+        self.broadcast_diff(VectorDiff::RemoveRange { start, end });
+        values
+    }
+
+    /// Replace the elements in the given range with the given ones, notify
+    /// subscribers and return the replaced elements.
+    ///
+    /// Subscribers are notified with a single [`VectorDiff::Splice`]. Does
+    /// nothing if both the range and `values` are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or if it ends after the end
+    /// of the vector.
+    #[track_caller]
+    pub fn splice(&mut self, range: impl RangeBounds<usize>, values: Vector<T>) -> Vector<T> {
+        let ops::Range { start, end } = resolve_range(range, self.values.len());
+        if start == end && values.is_empty() {
+            return Vector::new();
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::update",
+            "splice(start = {start}, end = {end}, len = {})",
+            values.len()
+        );
+
+        let old_values = splice_values(&mut self.values, start..end, values.clone());
+        self.broadcast_diff(VectorDiff::Splice { start, end, values });
+        old_values
+    }
+
+    /// Retain only the elements for which the given closure returns `true`
+    /// and notify subscribers.
+    ///
+    /// Every run of consecutive removed elements results in a single diff,
+    /// and subscribers receive all of them as one batch. If no element is
+    /// removed, subscribers are not notified.
+    pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::update", "retain()");
+
+        let diffs = bulk::retain(&mut self.values, f);
+        self.broadcast_diffs(diffs);
+    }
+
+    /// Sort the elements with the given comparison function and notify
+    /// subscribers.
+    ///
+    /// The sort is stable. Subscribers receive a minimal list of
+    /// [`VectorDiff::Move`]s as one batch. If the elements are sorted
+    /// already, subscribers are not notified.
+    pub fn sort_by(&mut self, cmp: impl FnMut(&T, &T) -> Ordering) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::update", "sort_by()");
+
+        let sorted = bulk::sort_by(&mut self.values, cmp);
+        if self.sender.receiver_count() != 0 {
+            self.broadcast_diffs(bulk::moves(&sorted));
+        }
+    }
+
+    /// Remove all but the first of consecutive elements that resolve to the
+    /// same key and notify subscribers.
+    ///
+    /// Like [`retain`][Self::retain], subscribers receive a single diff per
+    /// run of removed elements, as one batch.
+    pub fn dedup_by_key<K: PartialEq>(&mut self, key: impl FnMut(&T) -> K) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::update", "dedup_by_key()");
+
+        let diffs = bulk::dedup_by_key(&mut self.values, key);
+        self.broadcast_diffs(diffs);
+    }
+
+    /// Replace all of the elements in this `Vector` with the given ones and
+    /// notify subscribers of the minimal set of changes.
+    ///
+    /// Instead of a single [`VectorDiff::Reset`], subscribers receive the
+    /// diffs computed by [`VectorDiff::compute`] as one batch. If the new
+    /// elements are equal to the current ones, subscribers are not notified.
+    pub fn replace_with_diff(&mut self, values: Vector<T>)
+    where
+        T: PartialEq,
+    {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::update",
+            "replace_with_diff(len = {})",
+            values.len()
+        );
+
+        self.replace_with_diffs(values, VectorDiff::compute);
+    }
+
+    /// Replace all of the elements in this `Vector` with the given ones and
+    /// notify subscribers of the minimal set of changes, matching up elements
+    /// by the given key function.
+    ///
+    /// Like [`replace_with_diff`][Self::replace_with_diff], but uses
+    /// [`VectorDiff::compute_by_key`] to compute the diffs.
+    pub fn replace_with_diff_by_key<K: PartialEq>(
+        &mut self,
+        values: Vector<T>,
+        key: impl FnMut(&T) -> K,
+    ) where
+        T: PartialEq,
+    {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::update",
+            "replace_with_diff_by_key(len = {})",
+            values.len()
+        );
+
+        self.replace_with_diffs(values, |old, new| VectorDiff::compute_by_key(old, new, key));
+    }
+
+    fn replace_with_diffs(
+        &mut self,
+        values: Vector<T>,
+        compute: impl FnOnce(&Vector<T>, &Vector<T>) -> Vec<VectorDiff<T>>,
+    ) {
+        if self.sender.receiver_count() == 0 {
+            // Nobody to notify, no need to compute the diffs.
+            self.values = values;
+            return;
+        }
+
+        let diffs = compute(&self.values, &values);
+        self.values = values;
+        self.broadcast_diffs(diffs);
+    }
+
     /// Gets an entry for the given index, through which only the element at
     /// that index alone can be updated or removed.
     ///
@@ -276,11 +554,30 @@ impl<T: Clone + 'static> ObservableVector<T> {
         ObservableVectorTransaction::new(self)
     }
 
+    fn broadcast_diffs(&self, diffs: Vec<VectorDiff<T>>) {
+        if diffs.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                target: "eyeball_im::vector::broadcast",
+                "Skipping broadcast of empty list of diffs"
+            );
+        } else if self.sender.receiver_count() != 0 {
+            let msg =
+                BroadcastMessage { diffs: OneOrManyDiffs::Many(diffs), state: self.values.clone() };
+            let _num_receivers = self.sender.send(msg);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::broadcast",
+                "New observable value broadcast to {_num_receivers} receivers"
+            );
+        }
+    }
+
     fn broadcast_diff(&self, diff: VectorDiff<T>) {
         if self.sender.receiver_count() != 0 {
             let msg =
                 BroadcastMessage { diffs: OneOrManyDiffs::One(diff), state: self.values.clone() };
-            let _num_receivers = self.sender.send(msg).unwrap_or(0);
+            let _num_receivers = self.sender.send(msg);
             #[cfg(feature = "tracing")]
             tracing::debug!(
                 target: "eyeball_im::vector::broadcast",
@@ -323,27 +620,6 @@ impl<T: Clone + 'static> From<Vector<T>> for ObservableVector<T> {
     }
 }
 
-#[derive(Clone)]
-struct BroadcastMessage<T> {
-    diffs: OneOrManyDiffs<T>,
-    state: Vector<T>,
-}
-
-#[derive(Clone)]
-enum OneOrManyDiffs<T> {
-    One(VectorDiff<T>),
-    Many(Vec<VectorDiff<T>>),
-}
-
-impl<T> OneOrManyDiffs<T> {
-    fn into_vec(self) -> Vec<VectorDiff<T>> {
-        match self {
-            OneOrManyDiffs::One(diff) => vec![diff],
-            OneOrManyDiffs::Many(diffs) => diffs,
-        }
-    }
-}
-
 /// A change to an [`ObservableVector`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum VectorDiff<T> {
@@ -401,6 +677,52 @@ pub enum VectorDiff<T> {
         /// The full list of elements.
         values: Vector<T>,
     },
+    /// An element was moved to another position.
+    Move {
+        /// The index that the element had.
+        from: usize,
+        /// The index of the element after the move.
+        ///
+        /// The elements between `from` and `to` were shifted by one towards
+        /// `from`.
+        to: usize,
+    },
+    /// Two elements swapped their positions.
+    Swap {
+        /// The index of one of the elements.
+        first: usize,
+        /// The index of the other element.
+        second: usize,
+    },
+    /// Multiple elements were inserted at the given position.
+    InsertMany {
+        /// The index of the first new element.
+        ///
+        /// The element that was previously at that index as well as all the
+        /// ones after it were shifted to the right.
+        index: usize,
+        /// The new elements.
+        values: Vector<T>,
+    },
+    /// Removal of a range of elements.
+    RemoveRange {
+        /// The index of the first removed element.
+        start: usize,
+        /// The index after the last removed element.
+        end: usize,
+    },
+    /// A range of elements was replaced by other elements.
+    ///
+    /// The number of new elements can differ from the number of replaced
+    /// elements.
+    Splice {
+        /// The index of the first replaced element.
+        start: usize,
+        /// The index after the last replaced element.
+        end: usize,
+        /// The new elements, starting at index `start`.
+        values: Vector<T>,
+    },
 }
 
 impl<T: Clone> VectorDiff<T> {
@@ -419,6 +741,15 @@ impl<T: Clone> VectorDiff<T> {
             VectorDiff::Remove { index } => VectorDiff::Remove { index },
             VectorDiff::Truncate { length } => VectorDiff::Truncate { length },
             VectorDiff::Reset { values } => VectorDiff::Reset { values: vector_map(values, f) },
+            VectorDiff::Move { from, to } => VectorDiff::Move { from, to },
+            VectorDiff::Swap { first, second } => VectorDiff::Swap { first, second },
+            VectorDiff::InsertMany { index, values } => {
+                VectorDiff::InsertMany { index, values: vector_map(values, f) }
+            }
+            VectorDiff::RemoveRange { start, end } => VectorDiff::RemoveRange { start, end },
+            VectorDiff::Splice { start, end, values } => {
+                VectorDiff::Splice { start, end, values: vector_map(values, f) }
+            }
         }
     }
 
@@ -465,10 +796,34 @@ impl<T: Clone> VectorDiff<T> {
             VectorDiff::Reset { values } => {
                 *vec = values;
             }
+            VectorDiff::Move { from, to } => {
+                let value = vec.remove(from);
+                vec.insert(to, value);
+            }
+            VectorDiff::Swap { first, second } => {
+                vec.swap(first, second);
+            }
+            VectorDiff::InsertMany { index, values } => {
+                splice_values(vec, index..index, values);
+            }
+            VectorDiff::RemoveRange { start, end } => {
+                splice_values(vec, start..end, Vector::new());
+            }
+            VectorDiff::Splice { start, end, values } => {
+                splice_values(vec, start..end, values);
+            }
         }
     }
 }
 
+impl<T: Clone + 'static> Diff for VectorDiff<T> {
+    type Values = Vector<T>;
+
+    fn reset(values: Self::Values) -> Self {
+        VectorDiff::Reset { values }
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<T> serde::Serialize for VectorDiff<T>
 where
@@ -535,10 +890,488 @@ where
                 state.serialize_field("values", values)?;
                 state.end()
             }
+            VectorDiff::Move { from, to } => {
+                let mut state = serializer.serialize_struct_variant(SELF_NAME, 11, "Move", 2)?;
+                state.serialize_field("from", from)?;
+                state.serialize_field("to", to)?;
+                state.end()
+            }
+            VectorDiff::Swap { first, second } => {
+                let mut state = serializer.serialize_struct_variant(SELF_NAME, 12, "Swap", 2)?;
+                state.serialize_field("first", first)?;
+                state.serialize_field("second", second)?;
+                state.end()
+            }
+            VectorDiff::InsertMany { index, values } => {
+                let mut state =
+                    serializer.serialize_struct_variant(SELF_NAME, 13, "InsertMany", 2)?;
+                state.serialize_field("index", index)?;
+                state.serialize_field("values", values)?;
+                state.end()
+            }
+            VectorDiff::RemoveRange { start, end } => {
+                let mut state =
+                    serializer.serialize_struct_variant(SELF_NAME, 14, "RemoveRange", 2)?;
+                state.serialize_field("start", start)?;
+                state.serialize_field("end", end)?;
+                state.end()
+            }
+            VectorDiff::Splice { start, end, values } => {
+                let mut state = serializer.serialize_struct_variant(SELF_NAME, 15, "Splice", 3)?;
+                state.serialize_field("start", start)?;
+                state.serialize_field("end", end)?;
+                state.serialize_field("values", values)?;
+                state.end()
+            }
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for VectorDiff<T>
+where
+    T: serde::Deserialize<'de> + Clone,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use std::marker::PhantomData;
+
+        use serde::de::{self, EnumAccess, IgnoredAny, MapAccess, SeqAccess, VariantAccess};
+
+        const SELF_NAME: &str = "VectorDiff";
+        const VARIANTS: &[&str] = &[
+            "Append",
+            "Clear",
+            "PushFront",
+            "PushBack",
+            "PopFront",
+            "PopBack",
+            "Insert",
+            "Set",
+            "Remove",
+            "Truncate",
+            "Reset",
+            "Move",
+            "Swap",
+            "InsertMany",
+            "RemoveRange",
+            "Splice",
+        ];
+
+        #[derive(Clone, Copy)]
+        enum Variant {
+            Append,
+            Clear,
+            PushFront,
+            PushBack,
+            PopFront,
+            PopBack,
+            Insert,
+            Set,
+            Remove,
+            Truncate,
+            Reset,
+            Move,
+            Swap,
+            InsertMany,
+            RemoveRange,
+            Splice,
+        }
+
+        impl Variant {
+            fn fields(self) -> &'static [&'static str] {
+                match self {
+                    Self::Append | Self::Reset => &["values"],
+                    Self::Clear | Self::PopFront | Self::PopBack => &[],
+                    Self::PushFront | Self::PushBack => &["value"],
+                    Self::Insert | Self::Set => &["index", "value"],
+                    Self::Remove => &["index"],
+                    Self::Truncate => &["length"],
+                    Self::Move => &["from", "to"],
+                    Self::Swap => &["first", "second"],
+                    Self::InsertMany => &["index", "values"],
+                    Self::RemoveRange => &["start", "end"],
+                    Self::Splice => &["start", "end", "values"],
+                }
+            }
+        }
+
+        struct VariantVisitor;
+
+        impl<'de> de::Visitor<'de> for VariantVisitor {
+            type Value = Variant;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("variant identifier")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(match value {
+                    0 => Variant::Append,
+                    1 => Variant::Clear,
+                    2 => Variant::PushFront,
+                    3 => Variant::PushBack,
+                    4 => Variant::PopFront,
+                    5 => Variant::PopBack,
+                    6 => Variant::Insert,
+                    7 => Variant::Set,
+                    8 => Variant::Remove,
+                    9 => Variant::Truncate,
+                    10 => Variant::Reset,
+                    11 => Variant::Move,
+                    12 => Variant::Swap,
+                    13 => Variant::InsertMany,
+                    14 => Variant::RemoveRange,
+                    15 => Variant::Splice,
+                    _ => {
+                        return Err(de::Error::invalid_value(
+                            de::Unexpected::Unsigned(value),
+                            &"variant index 0 <= i < 16",
+                        ))
+                    }
+                })
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(match value {
+                    "Append" => Variant::Append,
+                    "Clear" => Variant::Clear,
+                    "PushFront" => Variant::PushFront,
+                    "PushBack" => Variant::PushBack,
+                    "PopFront" => Variant::PopFront,
+                    "PopBack" => Variant::PopBack,
+                    "Insert" => Variant::Insert,
+                    "Set" => Variant::Set,
+                    "Remove" => Variant::Remove,
+                    "Truncate" => Variant::Truncate,
+                    "Reset" => Variant::Reset,
+                    "Move" => Variant::Move,
+                    "Swap" => Variant::Swap,
+                    "InsertMany" => Variant::InsertMany,
+                    "RemoveRange" => Variant::RemoveRange,
+                    "Splice" => Variant::Splice,
+                    _ => return Err(de::Error::unknown_variant(value, VARIANTS)),
+                })
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                match std::str::from_utf8(value) {
+                    Ok(value) => self.visit_str(value),
+                    Err(_) => {
+                        let value = String::from_utf8_lossy(value);
+                        Err(de::Error::unknown_variant(&value, VARIANTS))
+                    }
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for Variant {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_identifier(VariantVisitor)
+            }
+        }
+
+        // Field names are shared between the variants, which variant a field is
+        // valid for is checked when visiting the variant's fields.
+        enum Field {
+            Index,
+            Value,
+            Values,
+            Length,
+            From,
+            To,
+            First,
+            Second,
+            Start,
+            End,
+            Other(String),
+        }
+
+        impl Field {
+            fn name(&self) -> &str {
+                match self {
+                    Self::Index => "index",
+                    Self::Value => "value",
+                    Self::Values => "values",
+                    Self::Length => "length",
+                    Self::From => "from",
+                    Self::To => "to",
+                    Self::First => "first",
+                    Self::Second => "second",
+                    Self::Start => "start",
+                    Self::End => "end",
+                    Self::Other(name) => name,
+                }
+            }
+        }
+
+        struct FieldVisitor;
+
+        impl<'de> de::Visitor<'de> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("field identifier")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(match value {
+                    "index" => Field::Index,
+                    "value" => Field::Value,
+                    "values" => Field::Values,
+                    "length" => Field::Length,
+                    "from" => Field::From,
+                    "to" => Field::To,
+                    "first" => Field::First,
+                    "second" => Field::Second,
+                    "start" => Field::Start,
+                    "end" => Field::End,
+                    _ => Field::Other(value.to_owned()),
+                })
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                self.visit_str(&String::from_utf8_lossy(value))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct StructVariantVisitor<T> {
+            variant: Variant,
+            _marker: PhantomData<T>,
+        }
+
+        impl<'de, T> de::Visitor<'de> for StructVariantVisitor<T>
+        where
+            T: serde::Deserialize<'de> + Clone,
+        {
+            type Value = VectorDiff<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("struct variant of VectorDiff")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                macro_rules! next_element {
+                    ($idx:literal) => {
+                        seq.next_element()?.ok_or_else(|| de::Error::invalid_length($idx, &self))?
+                    };
+                }
+
+                Ok(match self.variant {
+                    Variant::Append => VectorDiff::Append { values: next_element!(0) },
+                    Variant::Clear => VectorDiff::Clear,
+                    Variant::PushFront => VectorDiff::PushFront { value: next_element!(0) },
+                    Variant::PushBack => VectorDiff::PushBack { value: next_element!(0) },
+                    Variant::PopFront => VectorDiff::PopFront,
+                    Variant::PopBack => VectorDiff::PopBack,
+                    Variant::Insert => {
+                        VectorDiff::Insert { index: next_element!(0), value: next_element!(1) }
+                    }
+                    Variant::Set => {
+                        VectorDiff::Set { index: next_element!(0), value: next_element!(1) }
+                    }
+                    Variant::Remove => VectorDiff::Remove { index: next_element!(0) },
+                    Variant::Truncate => VectorDiff::Truncate { length: next_element!(0) },
+                    Variant::Reset => VectorDiff::Reset { values: next_element!(0) },
+                    Variant::Move => {
+                        VectorDiff::Move { from: next_element!(0), to: next_element!(1) }
+                    }
+                    Variant::Swap => {
+                        VectorDiff::Swap { first: next_element!(0), second: next_element!(1) }
+                    }
+                    Variant::InsertMany => {
+                        VectorDiff::InsertMany { index: next_element!(0), values: next_element!(1) }
+                    }
+                    Variant::RemoveRange => {
+                        VectorDiff::RemoveRange { start: next_element!(0), end: next_element!(1) }
+                    }
+                    Variant::Splice => VectorDiff::Splice {
+                        start: next_element!(0),
+                        end: next_element!(1),
+                        values: next_element!(2),
+                    },
+                })
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let fields = self.variant.fields();
+
+                let mut index = None;
+                let mut value = None;
+                let mut values = None;
+                let mut length = None;
+                let mut from = None;
+                let mut to = None;
+                let mut first = None;
+                let mut second = None;
+                let mut start = None;
+                let mut end = None;
+
+                fn set_once<T, E: de::Error>(
+                    slot: &mut Option<T>,
+                    field: &'static str,
+                    val: T,
+                ) -> Result<(), E> {
+                    match slot {
+                        Some(_) => Err(de::Error::duplicate_field(field)),
+                        None => {
+                            *slot = Some(val);
+                            Ok(())
+                        }
+                    }
+                }
+
+                while let Some(field) = map.next_key::<Field>()? {
+                    if !fields.contains(&field.name()) {
+                        map.next_value::<IgnoredAny>()?;
+                        continue;
+                    }
+
+                    match field {
+                        Field::Index => set_once(&mut index, "index", map.next_value()?)?,
+                        Field::Value => set_once(&mut value, "value", map.next_value()?)?,
+                        Field::Values => set_once(&mut values, "values", map.next_value()?)?,
+                        Field::Length => set_once(&mut length, "length", map.next_value()?)?,
+                        Field::From => set_once(&mut from, "from", map.next_value()?)?,
+                        Field::To => set_once(&mut to, "to", map.next_value()?)?,
+                        Field::First => set_once(&mut first, "first", map.next_value()?)?,
+                        Field::Second => set_once(&mut second, "second", map.next_value()?)?,
+                        Field::Start => set_once(&mut start, "start", map.next_value()?)?,
+                        Field::End => set_once(&mut end, "end", map.next_value()?)?,
+                        Field::Other(_) => unreachable!("unknown fields are skipped above"),
+                    }
+                }
+
+                macro_rules! required {
+                    ($field:ident) => {
+                        $field.ok_or_else(|| {
+                            <A::Error as de::Error>::missing_field(stringify!($field))
+                        })?
+                    };
+                }
+
+                Ok(match self.variant {
+                    Variant::Append => VectorDiff::Append { values: required!(values) },
+                    Variant::Clear => VectorDiff::Clear,
+                    Variant::PushFront => VectorDiff::PushFront { value: required!(value) },
+                    Variant::PushBack => VectorDiff::PushBack { value: required!(value) },
+                    Variant::PopFront => VectorDiff::PopFront,
+                    Variant::PopBack => VectorDiff::PopBack,
+                    Variant::Insert => {
+                        VectorDiff::Insert { index: required!(index), value: required!(value) }
+                    }
+                    Variant::Set => {
+                        VectorDiff::Set { index: required!(index), value: required!(value) }
+                    }
+                    Variant::Remove => VectorDiff::Remove { index: required!(index) },
+                    Variant::Truncate => VectorDiff::Truncate { length: required!(length) },
+                    Variant::Reset => VectorDiff::Reset { values: required!(values) },
+                    Variant::Move => VectorDiff::Move { from: required!(from), to: required!(to) },
+                    Variant::Swap => {
+                        VectorDiff::Swap { first: required!(first), second: required!(second) }
+                    }
+                    Variant::InsertMany => VectorDiff::InsertMany {
+                        index: required!(index),
+                        values: required!(values),
+                    },
+                    Variant::RemoveRange => {
+                        VectorDiff::RemoveRange { start: required!(start), end: required!(end) }
+                    }
+                    Variant::Splice => VectorDiff::Splice {
+                        start: required!(start),
+                        end: required!(end),
+                        values: required!(values),
+                    },
+                })
+            }
+        }
+
+        struct VectorDiffVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> de::Visitor<'de> for VectorDiffVisitor<T>
+        where
+            T: serde::Deserialize<'de> + Clone,
+        {
+            type Value = VectorDiff<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("enum VectorDiff")
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (variant, access) = data.variant::<Variant>()?;
+                access.struct_variant(
+                    variant.fields(),
+                    StructVariantVisitor { variant, _marker: PhantomData },
+                )
+            }
+        }
+
+        deserializer.deserialize_enum(SELF_NAME, VARIANTS, VectorDiffVisitor(PhantomData))
+    }
+}
+
 fn vector_map<T: Clone, U: Clone>(v: Vector<T>, f: impl FnMut(T) -> U) -> Vector<U> {
     v.into_iter().map(f).collect()
 }
+
+/// Resolve `range` to a `Range` within a vector of length `len`.
+#[track_caller]
+fn resolve_range(range: impl RangeBounds<usize>, len: usize) -> ops::Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end {
+        panic!("range starts at {start} but ends at {end}");
+    }
+    if end > len {
+        panic!("range end index {end} out of range for vector of length {len}");
+    }
+
+    start..end
+}
+
+/// Replace the elements of `vec` in `range` with `values`, returning the
+/// replaced elements.
+fn splice_values<T: Clone>(
+    vec: &mut Vector<T>,
+    range: ops::Range<usize>,
+    values: Vector<T>,
+) -> Vector<T> {
+    let mut old_values = vec.split_off(range.start);
+    let rest = old_values.split_off(range.end - range.start);
+    vec.append(values);
+    vec.append(rest);
+    old_values
+}
@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+
+use imbl::Vector;
+
+use super::VectorDiff;
+
+/// Remove the elements for which `keep` returns `false` from `values` and
+/// return the diffs describing the removal.
+///
+/// Every run of consecutive removed elements turns into a single diff: a
+/// [`Remove`][VectorDiff::Remove] or [`RemoveRange`][VectorDiff::RemoveRange],
+/// or a [`Truncate`][VectorDiff::Truncate] at the end of the vector. If no
+/// element is kept, the only diff is a [`Clear`][VectorDiff::Clear].
+pub(super) fn retain<T: Clone>(
+    values: &mut Vector<T>,
+    mut keep: impl FnMut(&T) -> bool,
+) -> Vec<VectorDiff<T>> {
+    let mut diffs = Vec::new();
+    let mut kept = Vector::new();
+    // Number of elements removed since the last kept one.
+    let mut num_removed = 0;
+
+    for value in values.iter() {
+        if keep(value) {
+            if num_removed != 0 {
+                diffs.push(remove_run(kept.len(), num_removed));
+                num_removed = 0;
+            }
+            kept.push_back(value.clone());
+        } else {
+            num_removed += 1;
+        }
+    }
+
+    if num_removed != 0 {
+        if kept.is_empty() {
+            diffs.push(VectorDiff::Clear);
+        } else {
+            diffs.push(VectorDiff::Truncate { length: kept.len() });
+        }
+    }
+
+    if !diffs.is_empty() {
+        *values = kept;
+    }
+
+    diffs
+}
+
+fn remove_run<T: Clone>(start: usize, len: usize) -> VectorDiff<T> {
+    if len == 1 {
+        VectorDiff::Remove { index: start }
+    } else {
+        VectorDiff::RemoveRange { start, end: start + len }
+    }
+}
+
+/// Remove all but the first of consecutive elements of `values` that resolve
+/// to the same key and return the diffs describing the removal.
+///
+/// See [`retain`] for the diffs that are returned.
+pub(super) fn dedup_by_key<T: Clone, K: PartialEq>(
+    values: &mut Vector<T>,
+    mut key: impl FnMut(&T) -> K,
+) -> Vec<VectorDiff<T>> {
+    let mut previous_key = None;
+    retain(values, |value| {
+        let key = key(value);
+        let is_duplicate = previous_key.as_ref() == Some(&key);
+        previous_key = Some(key);
+        !is_duplicate
+    })
+}
+
+/// Sort `values` with the given comparison function, and return the
+/// positions the sorted elements had before sorting.
+///
+/// The sort is stable.
+pub(super) fn sort_by<T: Clone>(
+    values: &mut Vector<T>,
+    mut cmp: impl FnMut(&T, &T) -> Ordering,
+) -> Vec<usize> {
+    let mut sorted: Vec<_> = (0..values.len()).collect();
+    sorted.sort_by(|&a, &b| cmp(&values[a], &values[b]));
+
+    if sorted.iter().enumerate().any(|(index, &old_index)| index != old_index) {
+        *values = sorted.iter().map(|&old_index| values[old_index].clone()).collect();
+    }
+
+    sorted
+}
+
+/// Compute a minimal list of [`Move`][VectorDiff::Move]s that put the
+/// elements of a vector at the positions returned by [`sort_by`].
+///
+/// The elements forming a longest increasing subsequence of the sorted
+/// positions stay in place, every other element is moved once, right after
+/// the element preceding it in sorted order.
+pub(super) fn moves<T: Clone>(sorted: &[usize]) -> Vec<VectorDiff<T>> {
+    let mut positions = vec![0; sorted.len()];
+    for (index, &old_index) in sorted.iter().enumerate() {
+        positions[old_index] = index;
+    }
+    let stays = longest_increasing_subsequence(&positions);
+
+    // The current order of the elements is tracked through slots that sort
+    // in that order: `(old_index, 0)` for elements that weren't moved, and
+    // `(anchor, n)` for the `n`th element moved after the staying element
+    // with the old index `anchor` (or the front of the vector, for -1).
+    let mut new_slots = vec![None; sorted.len()];
+    let (mut anchor, mut anchor_index) = (-1, -1);
+    for (index, &old_index) in sorted.iter().enumerate() {
+        if stays[old_index] {
+            (anchor, anchor_index) = (old_index as isize, index as isize);
+        } else {
+            new_slots[old_index] = Some((anchor, (index as isize - anchor_index) as usize));
+        }
+    }
+
+    let mut slots: Vec<_> = (0..sorted.len() as isize).map(|old_index| (old_index, 0)).collect();
+    slots.extend(new_slots.iter().flatten());
+    slots.sort_unstable();
+    let slot_index = |slot| slots.binary_search(&slot).expect("all slots are known");
+
+    // Which of the slots are occupied, initially by all elements in their
+    // original order.
+    let mut occupied = FenwickTree::new(slots.len());
+    for old_index in 0..sorted.len() {
+        occupied.add(slot_index((old_index as isize, 0)), 1);
+    }
+
+    // Elements are moved in sorted order, such that the element preceding
+    // each of them is always at its final place relative to the other
+    // elements that are not going to be moved.
+    let mut diffs = Vec::new();
+    for &old_index in sorted {
+        let new_slot = match new_slots[old_index] {
+            Some(new_slot) => new_slot,
+            None => continue,
+        };
+
+        let old_slot = slot_index((old_index as isize, 0));
+        let from = occupied.prefix_sum(old_slot);
+        occupied.add(old_slot, -1);
+        let new_slot = slot_index(new_slot);
+        let to = occupied.prefix_sum(new_slot);
+        occupied.add(new_slot, 1);
+
+        if from != to {
+            diffs.push(VectorDiff::Move { from, to });
+        }
+    }
+
+    diffs
+}
+
+/// A Fenwick tree (binary indexed tree) of counts, for finding the current
+/// index of an element among the occupied slots in logarithmic time.
+struct FenwickTree {
+    tree: Vec<isize>,
+}
+
+impl FenwickTree {
+    fn new(len: usize) -> Self {
+        Self { tree: vec![0; len + 1] }
+    }
+
+    /// Add `delta` to the count at `index`.
+    fn add(&mut self, index: usize, delta: isize) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// The sum of the counts before `index`.
+    fn prefix_sum(&self, index: usize) -> usize {
+        let (mut i, mut sum) = (index, 0);
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum as usize
+    }
+}
+
+/// Find a longest strictly increasing subsequence of `values`, and return
+/// whether each element is part of it.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<bool> {
+    // `tails[len]` is the index of the smallest element that ends an
+    // increasing subsequence of length `len + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    // The index of the element preceding each element in the subsequence it
+    // ends.
+    let mut predecessors = vec![None; values.len()];
+
+    for (index, &value) in values.iter().enumerate() {
+        let len = tails.partition_point(|&tail| values[tail] < value);
+        predecessors[index] = len.checked_sub(1).map(|previous| tails[previous]);
+        if len == tails.len() {
+            tails.push(index);
+        } else {
+            tails[len] = index;
+        }
+    }
+
+    let mut in_subsequence = vec![false; values.len()];
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        in_subsequence[index] = true;
+        next = predecessors[index];
+    }
+
+    in_subsequence
+}
@@ -0,0 +1,161 @@
+use imbl::Vector;
+
+use super::VectorDiff;
+
+/// Merge adjacent diffs where possible, such that applying the result to a
+/// vector of length `len` has the same effect as applying `diffs` to it.
+pub(super) fn compact<T: Clone>(diffs: Vec<VectorDiff<T>>, len: usize) -> Vec<VectorDiff<T>> {
+    let mut compacted = Compacted { diffs: Vec::new(), lengths: Vec::new(), initial_len: len };
+    for diff in diffs {
+        compacted.push(diff);
+    }
+
+    compacted.diffs
+}
+
+struct Compacted<T> {
+    diffs: Vec<VectorDiff<T>>,
+    // The length of the vector after applying each of the diffs.
+    lengths: Vec<usize>,
+    initial_len: usize,
+}
+
+impl<T: Clone> Compacted<T> {
+    fn len(&self) -> usize {
+        self.lengths.last().copied().unwrap_or(self.initial_len)
+    }
+
+    fn pop(&mut self) -> Option<VectorDiff<T>> {
+        self.lengths.pop();
+        self.diffs.pop()
+    }
+
+    /// Push the given diff without trying to merge it.
+    fn push_unmerged(&mut self, diff: VectorDiff<T>) {
+        let len = len_after(&diff, self.len());
+        self.diffs.push(diff);
+        self.lengths.push(len);
+    }
+
+    /// Push the given diff, merging it with the previous diffs if possible.
+    fn push(&mut self, diff: VectorDiff<T>) {
+        if let VectorDiff::Clear | VectorDiff::Reset { .. } = diff {
+            // The previous diffs don't matter anymore.
+            self.diffs.clear();
+            self.lengths.clear();
+            self.push_unmerged(diff);
+            return;
+        }
+
+        let last = self.pop();
+        // The length of the vector before `last`.
+        let len = self.len();
+
+        match (last, diff) {
+            (Some(VectorDiff::Clear), VectorDiff::Append { values }) => {
+                self.push_unmerged(VectorDiff::Reset { values });
+            }
+            (
+                Some(VectorDiff::Clear),
+                VectorDiff::PushBack { value } | VectorDiff::PushFront { value },
+            ) => {
+                self.push_unmerged(VectorDiff::Reset { values: Vector::unit(value) });
+            }
+            (Some(VectorDiff::Reset { mut values }), VectorDiff::Append { values: new_values }) => {
+                values.append(new_values);
+                self.push_unmerged(VectorDiff::Reset { values });
+            }
+            (Some(VectorDiff::Reset { mut values }), VectorDiff::PushBack { value }) => {
+                values.push_back(value);
+                self.push_unmerged(VectorDiff::Reset { values });
+            }
+            (Some(VectorDiff::Reset { mut values }), VectorDiff::Truncate { length }) => {
+                values.truncate(length);
+                self.push_unmerged(VectorDiff::Reset { values });
+            }
+
+            (Some(VectorDiff::PushBack { value }), VectorDiff::PushBack { value: new_value }) => {
+                self.push(VectorDiff::Append { values: Vector::from_iter([value, new_value]) });
+            }
+            (Some(VectorDiff::PushBack { value }), VectorDiff::Append { values: new_values }) => {
+                let mut values = Vector::unit(value);
+                values.append(new_values);
+                self.push(VectorDiff::Append { values });
+            }
+            (Some(VectorDiff::Append { mut values }), VectorDiff::PushBack { value }) => {
+                values.push_back(value);
+                self.push_unmerged(VectorDiff::Append { values });
+            }
+            (
+                Some(VectorDiff::Append { mut values }),
+                VectorDiff::Append { values: new_values },
+            ) => {
+                values.append(new_values);
+                self.push_unmerged(VectorDiff::Append { values });
+            }
+
+            (Some(VectorDiff::PushBack { value }), VectorDiff::Truncate { length }) => {
+                if length > len {
+                    // The pushed value is kept.
+                    self.push_unmerged(VectorDiff::PushBack { value });
+                } else if length < len {
+                    self.push(VectorDiff::Truncate { length });
+                }
+                // Otherwise, the truncation only removed the pushed value.
+            }
+            (Some(VectorDiff::Append { mut values }), VectorDiff::Truncate { length }) => {
+                if length >= len {
+                    values.truncate(length - len);
+                    if !values.is_empty() {
+                        self.push_unmerged(VectorDiff::Append { values });
+                    }
+                } else {
+                    self.push(VectorDiff::Truncate { length });
+                }
+            }
+            (Some(VectorDiff::Truncate { .. }), VectorDiff::Truncate { length }) => {
+                self.push(VectorDiff::Truncate { length });
+            }
+
+            (Some(VectorDiff::PushBack { .. }), VectorDiff::PopBack)
+            | (Some(VectorDiff::PushFront { .. }), VectorDiff::PopFront) => {}
+            (Some(VectorDiff::Insert { index, .. }), VectorDiff::Remove { index: removed })
+                if index == removed => {}
+
+            (Some(VectorDiff::Insert { index, .. }), VectorDiff::Set { index: set, value })
+                if index == set =>
+            {
+                self.push_unmerged(VectorDiff::Insert { index, value });
+            }
+            (Some(VectorDiff::Set { index, .. }), VectorDiff::Set { index: set, value })
+                if index == set =>
+            {
+                self.push(VectorDiff::Set { index, value });
+            }
+
+            (last, diff) => {
+                if let Some(last) = last {
+                    self.push_unmerged(last);
+                }
+                self.push_unmerged(diff);
+            }
+        }
+    }
+}
+
+/// The length of a vector of length `len` after applying `diff` to it.
+fn len_after<T: Clone>(diff: &VectorDiff<T>, len: usize) -> usize {
+    match diff {
+        VectorDiff::Append { values } | VectorDiff::InsertMany { values, .. } => len + values.len(),
+        VectorDiff::Clear => 0,
+        VectorDiff::PushFront { .. } | VectorDiff::PushBack { .. } | VectorDiff::Insert { .. } => {
+            len + 1
+        }
+        VectorDiff::PopFront | VectorDiff::PopBack | VectorDiff::Remove { .. } => len - 1,
+        VectorDiff::Set { .. } | VectorDiff::Move { .. } | VectorDiff::Swap { .. } => len,
+        VectorDiff::Truncate { length } => *length,
+        VectorDiff::Reset { values } => values.len(),
+        VectorDiff::RemoveRange { start, end } => len - (end - start),
+        VectorDiff::Splice { start, end, values } => len - (end - start) + values.len(),
+    }
+}
@@ -0,0 +1,245 @@
+use std::{iter, ops};
+
+use imbl::Vector;
+
+use super::VectorDiff;
+
+impl<T: Clone + PartialEq> VectorDiff<T> {
+    /// Compute a minimal list of diffs that turns `old` into `new`.
+    ///
+    /// The diffs only consist of [`Insert`][Self::Insert],
+    /// [`Remove`][Self::Remove] and [`Set`][Self::Set], with indices that are
+    /// valid when applying the diffs to `old` in order. The number of inserted
+    /// and removed elements is minimal, based on the [Myers diff algorithm];
+    /// a removal directly followed by an insertion at the same position is
+    /// merged into a `Set`.
+    ///
+    /// [Myers diff algorithm]: http://www.xmailserver.org/diff2.pdf
+    pub fn compute(old: &Vector<T>, new: &Vector<T>) -> Vec<VectorDiff<T>> {
+        let old_refs: Vec<_> = old.iter().collect();
+        let new_refs: Vec<_> = new.iter().collect();
+        let edits = myers(&old_refs, &new_refs);
+        edits_to_diffs(old, new, &edits)
+    }
+
+    /// Compute a minimal list of diffs that turns `old` into `new`, matching
+    /// up elements by the given key function.
+    ///
+    /// Like [`compute`][Self::compute], but elements of `old` and `new` are
+    /// considered the same element if their keys are equal. Such elements
+    /// that are otherwise unequal are updated in place using
+    /// [`Set`][Self::Set], rather than being removed and re-inserted.
+    pub fn compute_by_key<K: PartialEq>(
+        old: &Vector<T>,
+        new: &Vector<T>,
+        mut key: impl FnMut(&T) -> K,
+    ) -> Vec<VectorDiff<T>> {
+        let old_keys: Vec<_> = old.iter().map(&mut key).collect();
+        let new_keys: Vec<_> = new.iter().map(&mut key).collect();
+        let edits = myers(&old_keys, &new_keys);
+        edits_to_diffs(old, new, &edits)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    /// Keep the current element of the old list, it corresponds to the
+    /// current element of the new list.
+    Keep,
+    /// Remove the current element of the old list.
+    Remove,
+    /// Insert the current element of the new list.
+    Insert,
+}
+
+/// Turn the shortest edit script from `myers` into `VectorDiff`s.
+fn edits_to_diffs<T: Clone + PartialEq>(
+    old: &Vector<T>,
+    new: &Vector<T>,
+    edits: &[Edit],
+) -> Vec<VectorDiff<T>> {
+    let mut diffs = Vec::new();
+    // Positions in the old list, the new list and the list the diffs are
+    // applied to.
+    let (mut old_idx, mut new_idx, mut idx) = (0, 0, 0);
+
+    let mut edits = edits;
+    while let Some((&edit, rest)) = edits.split_first() {
+        if edit == Edit::Keep {
+            // With a key function, kept elements can still have changed.
+            if old[old_idx] != new[new_idx] {
+                diffs.push(VectorDiff::Set { index: idx, value: new[new_idx].clone() });
+            }
+            old_idx += 1;
+            new_idx += 1;
+            idx += 1;
+            edits = rest;
+            continue;
+        }
+
+        // Handle the whole run of removals and insertions up to the next kept
+        // element at once, such that pairs of them can be merged into `Set`s.
+        let run_len = edits.iter().position(|&e| e == Edit::Keep).unwrap_or(edits.len());
+        let (run, rest) = edits.split_at(run_len);
+        let num_removals = run.iter().filter(|&&e| e == Edit::Remove).count();
+        let num_insertions = run.len() - num_removals;
+        let num_sets = num_removals.min(num_insertions);
+
+        for _ in 0..num_sets {
+            diffs.push(VectorDiff::Set { index: idx, value: new[new_idx].clone() });
+            old_idx += 1;
+            new_idx += 1;
+            idx += 1;
+        }
+        for _ in num_sets..num_removals {
+            diffs.push(VectorDiff::Remove { index: idx });
+            old_idx += 1;
+        }
+        for _ in num_sets..num_insertions {
+            diffs.push(VectorDiff::Insert { index: idx, value: new[new_idx].clone() });
+            new_idx += 1;
+            idx += 1;
+        }
+
+        edits = rest;
+    }
+
+    diffs
+}
+
+/// Compute the shortest edit script turning `old` into `new`.
+///
+/// This is the linear space variant of the algorithm: rather than recording
+/// the furthest reaching paths of every round for backtracking, it finds the
+/// middle snake of a shortest path and recurses on the parts before and after
+/// it.
+fn myers<K: PartialEq>(old: &[K], new: &[K]) -> Vec<Edit> {
+    let max_d = (old.len() + new.len() + 1) / 2 + 1;
+    let mut v_forward = Diagonals::new(max_d);
+    let mut v_backward = Diagonals::new(max_d);
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    conquer(old, new, &mut v_forward, &mut v_backward, &mut edits);
+    edits
+}
+
+fn conquer<K: PartialEq>(
+    old: &[K],
+    new: &[K],
+    v_forward: &mut Diagonals,
+    v_backward: &mut Diagonals,
+    edits: &mut Vec<Edit>,
+) {
+    // Common prefixes and suffixes are kept as-is, only search for a snake in
+    // the part in between.
+    let prefix_len = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix_len..], &new[prefix_len..]);
+    let suffix_len = old.iter().rev().zip(new.iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[..old.len() - suffix_len], &new[..new.len() - suffix_len]);
+
+    edits.extend(iter::repeat(Edit::Keep).take(prefix_len));
+    if old.is_empty() {
+        edits.extend(iter::repeat(Edit::Insert).take(new.len()));
+    } else if new.is_empty() {
+        edits.extend(iter::repeat(Edit::Remove).take(old.len()));
+    } else {
+        let (x, y) = find_middle_snake(old, new, v_forward, v_backward);
+        conquer(&old[..x], &new[..y], v_forward, v_backward, edits);
+        conquer(&old[x..], &new[y..], v_forward, v_backward, edits);
+    }
+    edits.extend(iter::repeat(Edit::Keep).take(suffix_len));
+}
+
+/// Find a point on a shortest path through the edit graph of `old` and `new`
+/// that splits it into two parts with (about) the same number of edits.
+///
+/// `old` and `new` must be non-empty and must not have a common prefix or
+/// suffix, such that the returned point is neither the start nor the end.
+fn find_middle_snake<K: PartialEq>(
+    old: &[K],
+    new: &[K],
+    v_forward: &mut Diagonals,
+    v_backward: &mut Diagonals,
+) -> (usize, usize) {
+    let (n, m) = (old.len(), new.len());
+    // The forward and backward searches are centered on different diagonals.
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+
+    // `v[k]` is the furthest x reached on diagonal k = x - y, where the
+    // backward search counts from the end of both lists.
+    v_forward[1] = 0;
+    v_backward[1] = 0;
+
+    for d in 0..=((n + m + 1) / 2) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && v_forward[k - 1] < v_forward[k + 1]) {
+                v_forward[k + 1]
+            } else {
+                v_forward[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let snake_start = (x, y);
+            if x < n && y < m {
+                x += old[x..].iter().zip(&new[y..]).take_while(|(a, b)| a == b).count();
+            }
+            v_forward[k] = x;
+
+            if odd && (k - delta).abs() < d && x + v_backward[delta - k] >= n {
+                return snake_start;
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && v_backward[k - 1] < v_backward[k + 1]) {
+                v_backward[k + 1]
+            } else {
+                v_backward[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let snake_len = old[..n - x]
+                    .iter()
+                    .rev()
+                    .zip(new[..m - y].iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                x += snake_len;
+                y += snake_len;
+            }
+            v_backward[k] = x;
+
+            if !odd && (k - delta).abs() <= d && x + v_forward[delta - k] >= n {
+                return (n - x, m - y);
+            }
+        }
+    }
+
+    unreachable!("the forward and backward searches always meet");
+}
+
+/// The furthest reaching x coordinates of the diagonals of an edit graph,
+/// indexed by diagonal.
+struct Diagonals {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl Diagonals {
+    fn new(max_d: usize) -> Self {
+        Self { offset: max_d as isize, v: vec![0; 2 * max_d] }
+    }
+}
+
+impl ops::Index<isize> for Diagonals {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl ops::IndexMut<isize> for Diagonals {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
@@ -1,10 +1,21 @@
-use std::{fmt, mem, ops};
+use std::{
+    cmp::Ordering,
+    fmt, mem,
+    ops::{self, RangeBounds},
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
 
 use imbl::Vector;
 
-use crate::vector::OneOrManyDiffs;
+use super::{
+    bulk, compact::compact, entry::EntryIndex, resolve_range, splice_values, ObservableVector,
+    VectorDiff,
+};
+use crate::broadcast::{BroadcastMessage, OneOrManyDiffs};
 
-use super::{entry::EntryIndex, BroadcastMessage, ObservableVector, VectorDiff};
+/// The ID of the next transaction, such that savepoints can be tied to the
+/// transaction that created them.
+static NEXT_TRANSACTION_ID: AtomicU64 = AtomicU64::new(0);
 
 /// A transaction that allows making multiple updates to an `ObservableVector`
 /// as an atomic unit.
@@ -15,16 +26,23 @@ use super::{entry::EntryIndex, BroadcastMessage, ObservableVector, VectorDiff};
 pub struct ObservableVectorTransaction<'o, T: Clone> {
     // The observable vector being modified, only modified on commit.
     inner: &'o mut ObservableVector<T>,
+    // The unique ID of this transaction.
+    id: u64,
     // A clone of the observable's values, what the methods operate on until commit.
     values: Vector<T>,
     // The batched updates, to be sent to subscribers on commit.
     batch: Vec<VectorDiff<T>>,
+    // The IDs of the savepoints that can still be rolled back to, in order.
+    savepoints: Vec<usize>,
+    // The ID of the next savepoint.
+    next_savepoint_id: usize,
 }
 
 impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
     pub(super) fn new(inner: &'o mut ObservableVector<T>) -> Self {
         let values = inner.values.clone();
-        Self { inner, values, batch: Vec::new() }
+        let id = NEXT_TRANSACTION_ID.fetch_add(1, AtomicOrdering::Relaxed);
+        Self { inner, id, values, batch: Vec::new(), savepoints: Vec::new(), next_savepoint_id: 0 }
     }
 
     /// Commit this transaction, persisting the changes and notifying
@@ -44,7 +62,7 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
         } else {
             let diffs = OneOrManyDiffs::Many(mem::take(&mut self.batch));
             let msg = BroadcastMessage { diffs, state: self.inner.values.clone() };
-            let _num_receivers = self.inner.sender.send(msg).unwrap_or(0);
+            let _num_receivers = self.inner.sender.send(msg);
             #[cfg(feature = "tracing")]
             tracing::debug!(
                 target: "eyeball_im::vector::broadcast",
@@ -53,6 +71,20 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
         }
     }
 
+    /// Commit this transaction like [`commit`][Self::commit], but merge
+    /// adjacent updates before notifying subscribers.
+    ///
+    /// Subscribers receive fewer, larger updates with the same overall effect.
+    /// For example, consecutive [`VectorDiff::PushBack`]s are merged into a
+    /// single [`VectorDiff::Append`], a [`VectorDiff::Clear`] followed by
+    /// appends turns into a [`VectorDiff::Reset`], and an element that is
+    /// inserted and then removed again is not part of the updates at all.
+    pub fn commit_compacted(mut self) {
+        let batch = mem::take(&mut self.batch);
+        self.batch = compact(batch, self.inner.values.len());
+        self.commit();
+    }
+
     /// Roll back all changes made using this transaction so far.
     ///
     /// Same as dropping the transaction and starting a new one, semantically.
@@ -62,6 +94,70 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
 
         self.values = self.inner.values.clone();
         self.batch.clear();
+        self.savepoints.clear();
+    }
+
+    /// Create a savepoint, that the changes made using this transaction can
+    /// be rolled back to with [`rollback_to`][Self::rollback_to].
+    pub fn savepoint(&mut self) -> ObservableVectorTransactionSavepoint<T> {
+        let id = self.next_savepoint_id;
+        self.next_savepoint_id += 1;
+        self.savepoints.push(id);
+
+        ObservableVectorTransactionSavepoint {
+            transaction_id: self.id,
+            id,
+            values: self.values.clone(),
+            batch_len: self.batch.len(),
+        }
+    }
+
+    /// Roll back the changes made using this transaction since the given
+    /// savepoint was created.
+    ///
+    /// The savepoint stays valid, so it can be rolled back to multiple times.
+    /// The savepoints created after it are invalidated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the savepoint was invalidated, by rolling back to an earlier
+    /// savepoint or the whole transaction, or if it was created by another
+    /// transaction.
+    #[track_caller]
+    pub fn rollback_to(&mut self, savepoint: &ObservableVectorTransactionSavepoint<T>) {
+        if !self.try_rollback_to(savepoint) {
+            panic!("savepoint was invalidated or belongs to another transaction");
+        }
+    }
+
+    fn try_rollback_to(&mut self, savepoint: &ObservableVectorTransactionSavepoint<T>) -> bool {
+        if savepoint.transaction_id != self.id {
+            return false;
+        }
+        let position = match self.savepoints.iter().position(|&id| id == savepoint.id) {
+            Some(position) => position,
+            None => return false,
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("rollback to savepoint {}", savepoint.id);
+
+        self.savepoints.truncate(position + 1);
+        self.values = savepoint.values.clone();
+        self.batch.truncate(savepoint.batch_len);
+        true
+    }
+
+    /// Start a nested transaction, whose changes are part of this transaction
+    /// once it is committed.
+    ///
+    /// The nested transaction dereferences to this transaction, so it can be
+    /// modified through the same methods. If the nested transaction is
+    /// dropped without [`.commit()`](ObservableVectorNestedTransaction::commit)
+    /// being called, the changes made through it are rolled back.
+    pub fn transaction(&mut self) -> ObservableVectorNestedTransaction<'_, 'o, T> {
+        let savepoint = self.savepoint();
+        ObservableVectorNestedTransaction { inner: self, savepoint: Some(savepoint) }
     }
 
     /// Append the given elements at the end of the `Vector` and notify
@@ -83,7 +179,11 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
         tracing::debug!(target: "eyeball_im::vector::transaction::update", "clear");
 
         self.values.clear();
-        self.batch.clear(); // All previous batched updates are irrelevant now
+        // All previous batched updates are irrelevant now, unless a savepoint
+        // can still bring them back.
+        if self.savepoints.is_empty() {
+            self.batch.clear();
+        }
         self.add_to_batch(VectorDiff::Clear);
     }
 
@@ -219,6 +319,189 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
         }
     }
 
+    /// Move the element at position `from` to position `to` and notify
+    /// subscribers.
+    ///
+    /// The elements between the two positions are shifted by one, such that
+    /// the element ends up at index `to` afterwards. Does nothing if `from` and
+    /// `to` are equal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from >= len` or `to >= len`.
+    #[track_caller]
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let len = self.values.len();
+        if from >= len {
+            panic!("index out of bounds: the length is {len} but the index is {from}");
+        }
+        if to >= len {
+            panic!("index out of bounds: the length is {len} but the index is {to}");
+        }
+
+        if from != to {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::transaction::update",
+                "move_item(from = {from}, to = {to})"
+            );
+
+            let value = self.values.remove(from);
+            self.values.insert(to, value);
+            self.add_to_batch(VectorDiff::Move { from, to });
+        }
+    }
+
+    /// Swap the elements at positions `first` and `second` and notify
+    /// subscribers.
+    ///
+    /// Does nothing if `first` and `second` are equal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `first >= len` or `second >= len`.
+    #[track_caller]
+    pub fn swap(&mut self, first: usize, second: usize) {
+        let len = self.values.len();
+        if first >= len {
+            panic!("index out of bounds: the length is {len} but the index is {first}");
+        }
+        if second >= len {
+            panic!("index out of bounds: the length is {len} but the index is {second}");
+        }
+
+        if first != second {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::transaction::update",
+                "swap(first = {first}, second = {second})"
+            );
+
+            self.values.swap(first, second);
+            self.add_to_batch(VectorDiff::Swap { first, second });
+        }
+    }
+
+    /// Insert the given elements at the given position and notify
+    /// subscribers.
+    ///
+    /// Does nothing if `values` is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    #[track_caller]
+    pub fn insert_many(&mut self, index: usize, values: Vector<T>) {
+        let len = self.values.len();
+        if index > len {
+            panic!("index out of bounds: the length is {len} but the index is {index}");
+        }
+
+        if !values.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "eyeball_im::vector::transaction::update",
+                "insert_many(index = {index}, len = {})",
+                values.len()
+            );
+
+            splice_values(&mut self.values, index..index, values.clone());
+            self.add_to_batch(VectorDiff::InsertMany { index, values });
+        }
+    }
+
+    /// Remove the elements in the given range, notify subscribers and return
+    /// the elements.
+    ///
+    /// Does nothing if the range is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or if it ends after the end
+    /// of the vector.
+    #[track_caller]
+    pub fn remove_range(&mut self, range: impl RangeBounds<usize>) -> Vector<T> {
+        let ops::Range { start, end } = resolve_range(range, self.values.len());
+        if start == end {
+            return Vector::new();
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::transaction::update",
+            "remove_range(start = {start}, end = {end})"
+        );
+
+        let values = splice_values(&mut self.values, start..end, Vector::new());
+        self.add_to_batch(VectorDiff::RemoveRange { start, end });
+        values
+    }
+
+    /// Replace the elements in the given range with the given ones, notify
+    /// subscribers and return the replaced elements.
+    ///
+    /// Does nothing if both the range and `values` are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range starts after it ends, or if it ends after the end
+    /// of the vector.
+    #[track_caller]
+    pub fn splice(&mut self, range: impl RangeBounds<usize>, values: Vector<T>) -> Vector<T> {
+        let ops::Range { start, end } = resolve_range(range, self.values.len());
+        if start == end && values.is_empty() {
+            return Vector::new();
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "eyeball_im::vector::transaction::update",
+            "splice(start = {start}, end = {end}, len = {})",
+            values.len()
+        );
+
+        let old_values = splice_values(&mut self.values, start..end, values.clone());
+        self.add_to_batch(VectorDiff::Splice { start, end, values });
+        old_values
+    }
+
+    /// Retain only the elements for which the given closure returns `true`.
+    ///
+    /// Every run of consecutive removed elements results in a single diff.
+    pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::transaction::update", "retain()");
+
+        let diffs = bulk::retain(&mut self.values, f);
+        self.add_all_to_batch(diffs);
+    }
+
+    /// Sort the elements with the given comparison function.
+    ///
+    /// The sort is stable, and results in a minimal list of
+    /// [`VectorDiff::Move`]s.
+    pub fn sort_by(&mut self, cmp: impl FnMut(&T, &T) -> Ordering) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::transaction::update", "sort_by()");
+
+        let sorted = bulk::sort_by(&mut self.values, cmp);
+        if self.inner.sender.receiver_count() != 0 {
+            self.add_all_to_batch(bulk::moves(&sorted));
+        }
+    }
+
+    /// Remove all but the first of consecutive elements that resolve to the
+    /// same key.
+    ///
+    /// Every run of consecutive removed elements results in a single diff.
+    pub fn dedup_by_key<K: PartialEq>(&mut self, key: impl FnMut(&T) -> K) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "eyeball_im::vector::transaction::update", "dedup_by_key()");
+
+        let diffs = bulk::dedup_by_key(&mut self.values, key);
+        self.add_all_to_batch(diffs);
+    }
+
     /// Gets an entry for the given index through which only the element at that
     /// index alone can be updated or removed.
     ///
@@ -275,6 +558,12 @@ impl<'o, T: Clone + 'static> ObservableVectorTransaction<'o, T> {
             self.batch.push(diff);
         }
     }
+
+    fn add_all_to_batch(&mut self, diffs: Vec<VectorDiff<T>>) {
+        if self.inner.sender.receiver_count() != 0 {
+            self.batch.extend(diffs);
+        }
+    }
 }
 
 impl<T> fmt::Debug for ObservableVectorTransaction<'_, T>
@@ -307,6 +596,100 @@ impl<T: Clone> Drop for ObservableVectorTransaction<'_, T> {
     }
 }
 
+/// A point in an [`ObservableVectorTransaction`] that its changes can be rolled
+/// back to.
+///
+/// Obtained from [`ObservableVectorTransaction::savepoint`].
+pub struct ObservableVectorTransactionSavepoint<T> {
+    // The ID of the transaction that created the savepoint.
+    transaction_id: u64,
+    // The ID of the savepoint, unique within its transaction.
+    id: usize,
+    values: Vector<T>,
+    batch_len: usize,
+}
+
+impl<T> fmt::Debug for ObservableVectorTransactionSavepoint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableVectorTransactionSavepoint")
+            .field("transaction_id", &self.transaction_id)
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A transaction nested in an [`ObservableVectorTransaction`].
+///
+/// Obtained from [`ObservableVectorTransaction::transaction`]. The changes
+/// made through it are rolled back when it is dropped, unless it is finalized
+/// with [`.commit()`](Self::commit).
+pub struct ObservableVectorNestedTransaction<'a, 'o, T: Clone + 'static> {
+    inner: &'a mut ObservableVectorTransaction<'o, T>,
+    // Only `None` after commit.
+    savepoint: Option<ObservableVectorTransactionSavepoint<T>>,
+}
+
+impl<T: Clone + 'static> ObservableVectorNestedTransaction<'_, '_, T> {
+    /// Commit this nested transaction, keeping its changes as part of the
+    /// parent transaction.
+    pub fn commit(mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("commit (nested)");
+
+        if let Some(savepoint) = self.savepoint.take() {
+            self.inner.savepoints.retain(|&id| id != savepoint.id);
+        }
+    }
+
+    /// Roll back all changes made using this nested transaction so far.
+    pub fn rollback(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("rollback (nested, explicit)");
+
+        if let Some(savepoint) = &self.savepoint {
+            // If the savepoint was invalidated, the parent was rolled back
+            // further already.
+            self.inner.try_rollback_to(savepoint);
+        }
+    }
+}
+
+impl<T> fmt::Debug for ObservableVectorNestedTransaction<'_, '_, T>
+where
+    T: Clone + fmt::Debug + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableVectorNestedTransaction")
+            .field("values", &self.inner.values)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'o, T: Clone + 'static> ops::Deref for ObservableVectorNestedTransaction<'_, 'o, T> {
+    type Target = ObservableVectorTransaction<'o, T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner
+    }
+}
+
+impl<T: Clone + 'static> ops::DerefMut for ObservableVectorNestedTransaction<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner
+    }
+}
+
+impl<T: Clone + 'static> Drop for ObservableVectorNestedTransaction<'_, '_, T> {
+    fn drop(&mut self) {
+        if let Some(savepoint) = self.savepoint.take() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("rollback (nested, drop)");
+
+            self.inner.try_rollback_to(&savepoint);
+        }
+    }
+}
+
 /// A handle to a single value in an [`ObservableVector`], obtained from a
 /// transaction.
 pub struct ObservableVectorTransactionEntry<'a, 'o, T: Clone> {
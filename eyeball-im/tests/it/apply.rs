@@ -29,3 +29,45 @@ fn reset_clear() {
     VectorDiff::Reset { values: vector![] }.apply(&mut vec);
     assert_eq!(vec, vector![]);
 }
+
+#[test]
+fn move_forward() {
+    let mut vec = vector![1, 2, 3, 4];
+    VectorDiff::Move { from: 1, to: 3 }.apply(&mut vec);
+    assert_eq!(vec, vector![1, 3, 4, 2]);
+}
+
+#[test]
+fn move_backward() {
+    let mut vec = vector![1, 2, 3, 4];
+    VectorDiff::Move { from: 3, to: 0 }.apply(&mut vec);
+    assert_eq!(vec, vector![4, 1, 2, 3]);
+}
+
+#[test]
+fn swap() {
+    let mut vec = vector![1, 2, 3, 4];
+    VectorDiff::Swap { first: 0, second: 2 }.apply(&mut vec);
+    assert_eq!(vec, vector![3, 2, 1, 4]);
+}
+
+#[test]
+fn insert_many() {
+    let mut vec = vector![1, 4];
+    VectorDiff::InsertMany { index: 1, values: vector![2, 3] }.apply(&mut vec);
+    assert_eq!(vec, vector![1, 2, 3, 4]);
+}
+
+#[test]
+fn remove_range() {
+    let mut vec = vector![1, 2, 3, 4];
+    VectorDiff::RemoveRange { start: 1, end: 3 }.apply(&mut vec);
+    assert_eq!(vec, vector![1, 4]);
+}
+
+#[test]
+fn splice() {
+    let mut vec = vector![1, 2, 3, 4];
+    VectorDiff::Splice { start: 1, end: 2, values: vector![5, 6] }.apply(&mut vec);
+    assert_eq!(vec, vector![1, 5, 6, 3, 4]);
+}
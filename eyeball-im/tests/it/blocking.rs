@@ -0,0 +1,79 @@
+use std::{
+    sync::{mpsc, Arc, Barrier},
+    thread,
+    time::Duration,
+};
+
+use imbl::vector;
+
+use eyeball_im::{ObservableVector, VectorDiff};
+
+#[test]
+fn next_blocking() {
+    let mut ob = ObservableVector::new();
+    let mut sub = ob.subscribe().into_stream();
+    let barrier = Arc::new(Barrier::new(2));
+
+    let handle = thread::spawn({
+        let barrier = barrier.clone();
+        move || {
+            // Only update once the main thread is about to block.
+            barrier.wait();
+            ob.push_back(1);
+            ob.push_back(2);
+        }
+    });
+
+    barrier.wait();
+    assert_eq!(sub.next_blocking(), Some(VectorDiff::PushBack { value: 1 }));
+    assert_eq!(sub.next_blocking(), Some(VectorDiff::PushBack { value: 2 }));
+    handle.join().unwrap();
+    assert_eq!(sub.next_blocking(), None);
+}
+
+#[test]
+fn next_blocking_timeout() {
+    let mut ob = ObservableVector::new();
+    let mut sub = ob.subscribe().into_batched_stream();
+
+    assert!(sub.next_blocking_timeout(Duration::from_millis(10)).is_err());
+
+    let mut txn = ob.transaction();
+    txn.push_back('a');
+    txn.push_back('b');
+    txn.commit();
+    assert_eq!(
+        sub.next_blocking_timeout(Duration::from_millis(10)),
+        Ok(Some(vec![VectorDiff::PushBack { value: 'a' }, VectorDiff::PushBack { value: 'b' }]))
+    );
+
+    drop(ob);
+    assert_eq!(sub.next_blocking_timeout(Duration::from_millis(10)), Ok(None));
+}
+
+#[test]
+fn blocking_iter() {
+    let mut ob = ObservableVector::new();
+    let (values, mut sub) = ob.subscribe().into_values_and_stream();
+
+    let (ack_tx, ack_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        for i in 0..3 {
+            ob.push_back(i);
+            // Wait for the diff to be received before sending the next one.
+            ack_rx.recv().unwrap();
+        }
+        ob.remove(1);
+    });
+
+    let mut values = values;
+    for diff in sub.blocking_iter() {
+        diff.apply(&mut values);
+        // The receiver is gone after the last update.
+        let _ = ack_tx.send(());
+    }
+    handle.join().unwrap();
+
+    assert_eq!(values, vector![0, 2]);
+}
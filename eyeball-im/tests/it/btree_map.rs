@@ -0,0 +1,148 @@
+use imbl::OrdMap;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+use eyeball_im::{BTreeMapDiff, ObservableBTreeMap};
+
+#[test]
+fn positional_diffs() {
+    let mut ob = ObservableBTreeMap::new();
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.insert('b', 1);
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 0, key: 'b', value: 1 });
+
+    ob.insert('d', 2);
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 1, key: 'd', value: 2 });
+
+    ob.insert('a', 3);
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 0, key: 'a', value: 3 });
+
+    ob.insert('d', 4);
+    assert_next_eq!(sub, BTreeMapDiff::Update { index: 2, key: 'd', value: 4 });
+
+    assert_eq!(ob.remove(&'b'), Some(1));
+    assert_next_eq!(sub, BTreeMapDiff::Remove { index: 1, key: 'b' });
+
+    assert_eq!(ob.remove(&'x'), None);
+    assert_pending!(sub);
+
+    ob.clear();
+    assert_next_eq!(sub, BTreeMapDiff::Clear);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn positional_diffs_random() {
+    let mut ob = ObservableBTreeMap::from(OrdMap::from_iter([(10_u32, 0_u32), (20, 0), (30, 0)]));
+    let mut sub = ob.subscribe().into_stream();
+    let mut state = 0x2545_f491_u32;
+
+    for value in 0..500_u32 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let key = state % 50;
+        let index = ob.keys().take_while(|k| **k < key).count();
+
+        if state % 3 == 0 {
+            let removed = ob.remove(&key);
+            if removed.is_some() {
+                assert_next_eq!(sub, BTreeMapDiff::Remove { index, key });
+            }
+        } else if ob.insert(key, value).is_some() {
+            assert_next_eq!(sub, BTreeMapDiff::Update { index, key, value });
+        } else {
+            assert_next_eq!(sub, BTreeMapDiff::Insert { index, key, value });
+        }
+        assert_pending!(sub);
+    }
+}
+
+#[test]
+fn range() {
+    let mut ob = ObservableBTreeMap::from(OrdMap::from_iter([(1, 'a'), (5, 'e'), (9, 'i')]));
+    let (values, mut sub) = ob.subscribe_range(3..7).into_values_and_stream();
+    assert_eq!(values, OrdMap::from_iter([(5, 'e')]));
+
+    // Below the range, shifts the absolute index of all entries in the range.
+    ob.insert(2, 'b');
+    assert_pending!(sub);
+
+    ob.insert(4, 'd');
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 0, key: 4, value: 'd' });
+
+    ob.insert(6, 'f');
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 2, key: 6, value: 'f' });
+
+    // Above the range.
+    ob.insert(7, 'g');
+    ob.remove(&9);
+    assert_pending!(sub);
+
+    ob.remove(&1);
+    ob.insert(5, 'E');
+    assert_next_eq!(sub, BTreeMapDiff::Update { index: 1, key: 5, value: 'E' });
+
+    ob.remove(&4);
+    assert_next_eq!(sub, BTreeMapDiff::Remove { index: 0, key: 4 });
+}
+
+#[test]
+fn range_clear() {
+    let mut ob = ObservableBTreeMap::from(OrdMap::from_iter([(1, 'a'), (5, 'e')]));
+    let mut sub = ob.subscribe_range(..=4).into_stream();
+    let mut empty_sub = ob.subscribe_range(10..).into_stream();
+
+    ob.clear();
+    assert_next_eq!(sub, BTreeMapDiff::Clear);
+    assert_pending!(empty_sub);
+
+    ob.insert(3, 'c');
+    assert_next_eq!(sub, BTreeMapDiff::Insert { index: 0, key: 3, value: 'c' });
+    assert_pending!(empty_sub);
+}
+
+#[test]
+fn range_lag() {
+    let mut ob = ObservableBTreeMap::with_capacity(1);
+    let mut sub = ob.subscribe_range('c'..'e').into_stream();
+
+    ob.insert('a', 0);
+    ob.insert('c', 1);
+    ob.insert('d', 2);
+
+    assert_next_eq!(sub, BTreeMapDiff::Reset { values: OrdMap::from_iter([('c', 1), ('d', 2)]) });
+    assert_pending!(sub);
+
+    // Offset is recomputed from the reset state.
+    ob.insert('b', 3);
+    assert_pending!(sub);
+    ob.insert('e', 4);
+    assert_pending!(sub);
+
+    ob.remove(&'c');
+    assert_next_eq!(sub, BTreeMapDiff::Remove { index: 0, key: 'c' });
+}
+
+#[test]
+fn range_batched() {
+    let mut ob = ObservableBTreeMap::new();
+    let mut st = ob.subscribe_range(10..20).into_batched_stream();
+
+    ob.insert(5, ());
+    ob.insert(25, ());
+    assert_pending!(st);
+
+    ob.insert(1, ());
+    ob.insert(15, ());
+    ob.insert(12, ());
+    assert_next_eq!(
+        st,
+        vec![
+            BTreeMapDiff::Insert { index: 0, key: 15, value: () },
+            BTreeMapDiff::Insert { index: 0, key: 12, value: () },
+        ]
+    );
+}
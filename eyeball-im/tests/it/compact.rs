@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use imbl::{vector, Vector};
+use stream_assert::{assert_next_eq, assert_pending};
+
+use eyeball_im::{ObservableVector, VectorDiff};
+
+#[test]
+fn push_back_and_truncate() {
+    let mut ob = ObservableVector::from(vector![0]);
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    for i in 1..=100 {
+        txn.push_back(i);
+    }
+    txn.truncate(51);
+    txn.commit_compacted();
+
+    assert_next_eq!(st, vec![VectorDiff::Append { values: (1..=50).collect() }]);
+    assert_eq!(ob.len(), 51);
+
+    // Truncating further than the appended values.
+    let mut txn = ob.transaction();
+    txn.push_back(51);
+    txn.append(vector![52, 53]);
+    txn.truncate(10);
+    txn.truncate(5);
+    txn.commit_compacted();
+
+    assert_next_eq!(st, vec![VectorDiff::Truncate { length: 5 }]);
+
+    // Truncating exactly the pushed value.
+    let mut txn = ob.transaction();
+    txn.push_back(5);
+    txn.truncate(5);
+    txn.commit_compacted();
+
+    assert_pending!(st);
+    assert_eq!(ob.len(), 5);
+}
+
+#[test]
+fn clear_and_append() {
+    let mut ob = ObservableVector::from(vector![1, 2]);
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.push_back(3);
+    txn.clear();
+    txn.append(vector![4, 5]);
+    txn.push_back(6);
+    txn.append(vector![7]);
+    txn.commit_compacted();
+
+    assert_next_eq!(st, vec![VectorDiff::Reset { values: vector![4, 5, 6, 7] }]);
+}
+
+#[test]
+fn set_and_remove() {
+    let mut ob = ObservableVector::from(vector![1, 2, 3]);
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.set(1, 4);
+    txn.set(1, 5);
+    txn.insert(0, 6);
+    txn.set(0, 7);
+    txn.remove(0);
+    txn.push_front(8);
+    txn.pop_front();
+    txn.set(2, 9);
+    txn.commit_compacted();
+
+    assert_next_eq!(
+        st,
+        vec![VectorDiff::Set { index: 1, value: 5 }, VectorDiff::Set { index: 2, value: 9 }]
+    );
+    assert_eq!(*ob, vector![1, 5, 9]);
+
+    // Nothing is broadcast if all updates cancel each other out.
+    let mut txn = ob.transaction();
+    txn.insert(1, 10);
+    txn.remove(1);
+    txn.commit_compacted();
+
+    assert_pending!(st);
+}
+
+#[test]
+fn random() {
+    // Simple deterministic pseudo-random generator (xorshift).
+    let mut state = 0x2545_f491_u32;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % bound
+    };
+
+    let mut ob = ObservableVector::new();
+    let mut st = ob.subscribe().into_batched_stream();
+    let mut values = Vector::new();
+
+    for i in 0..500 {
+        let mut txn = ob.transaction();
+        for _ in 0..next(10) {
+            let len = txn.len();
+            match next(10) {
+                0 => txn.push_back(i),
+                1 => txn.push_front(i),
+                2 => txn.append((0..next(4)).map(|j| i + j).collect()),
+                3 if len > 0 => {
+                    txn.pop_back();
+                }
+                4 if len > 0 => {
+                    txn.pop_front();
+                }
+                5 => txn.insert(next(len + 1), i),
+                6 if len > 0 => {
+                    txn.remove(next(len));
+                }
+                7 if len > 0 => {
+                    txn.set(next(len), i);
+                }
+                8 => txn.truncate(next(len + 1)),
+                _ if next(4) == 0 => txn.clear(),
+                _ => {}
+            }
+        }
+        txn.commit_compacted();
+
+        while let Ok(Some(diffs)) = st.next_blocking_timeout(Duration::ZERO) {
+            for diff in diffs {
+                diff.apply(&mut values);
+            }
+        }
+        assert_eq!(values, *ob, "after step {i}");
+    }
+}
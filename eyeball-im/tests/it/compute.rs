@@ -0,0 +1,206 @@
+use imbl::{vector, Vector};
+use stream_assert::{assert_next_eq, assert_pending};
+
+use eyeball_im::{ObservableVector, VectorDiff};
+
+fn num_changes(diffs: &[VectorDiff<char>]) -> usize {
+    diffs
+        .iter()
+        .map(|diff| match diff {
+            // A `Set` replaces a removal and an insertion.
+            VectorDiff::Set { .. } => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+fn lcs_len(a: &Vector<char>, b: &Vector<char>) -> usize {
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            table[i + 1][j + 1] =
+                if x == y { table[i][j] + 1 } else { table[i][j + 1].max(table[i + 1][j]) };
+        }
+    }
+    table[a.len()][b.len()]
+}
+
+fn check(old: Vector<char>, new: Vector<char>) -> Vec<VectorDiff<char>> {
+    let diffs = VectorDiff::compute(&old, &new);
+    let mut values = old;
+    for diff in diffs.clone() {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, new);
+    diffs
+}
+
+#[test]
+fn equal() {
+    assert_eq!(check(vector![], vector![]), vec![]);
+    assert_eq!(check(vector!['a', 'b', 'c'], vector!['a', 'b', 'c']), vec![]);
+}
+
+#[test]
+fn insert() {
+    assert_eq!(
+        check(vector!['a', 'c'], vector!['a', 'b', 'c', 'd']),
+        vec![
+            VectorDiff::Insert { index: 1, value: 'b' },
+            VectorDiff::Insert { index: 3, value: 'd' }
+        ]
+    );
+    assert_eq!(
+        check(vector![], vector!['a', 'b']),
+        vec![
+            VectorDiff::Insert { index: 0, value: 'a' },
+            VectorDiff::Insert { index: 1, value: 'b' }
+        ]
+    );
+}
+
+#[test]
+fn remove() {
+    assert_eq!(
+        check(vector!['a', 'b', 'c', 'd'], vector!['b', 'd']),
+        vec![VectorDiff::Remove { index: 0 }, VectorDiff::Remove { index: 1 }]
+    );
+    assert_eq!(
+        check(vector!['a', 'b'], vector![]),
+        vec![VectorDiff::Remove { index: 0 }, VectorDiff::Remove { index: 0 }]
+    );
+}
+
+#[test]
+fn set() {
+    assert_eq!(
+        check(vector!['a', 'b', 'c'], vector!['a', 'x', 'c']),
+        vec![VectorDiff::Set { index: 1, value: 'x' }]
+    );
+    assert_eq!(
+        check(vector!['a', 'b', 'c'], vector!['a', 'x', 'y', 'z']),
+        vec![
+            VectorDiff::Set { index: 1, value: 'x' },
+            VectorDiff::Set { index: 2, value: 'y' },
+            VectorDiff::Insert { index: 3, value: 'z' },
+        ]
+    );
+}
+
+#[test]
+fn move_is_remove_and_insert() {
+    assert_eq!(
+        check(vector!['a', 'b', 'c', 'd'], vector!['b', 'c', 'd', 'a']),
+        vec![VectorDiff::Remove { index: 0 }, VectorDiff::Insert { index: 3, value: 'a' }]
+    );
+}
+
+#[test]
+fn minimal() {
+    // Classic example from the paper, edit distance 5.
+    let old: Vector<_> = "abcabba".chars().collect();
+    let new: Vector<_> = "cbabac".chars().collect();
+    assert_eq!(num_changes(&check(old, new)), 5);
+}
+
+#[test]
+fn random() {
+    // Simple deterministic pseudo-random generator (xorshift).
+    let mut state = 0x2545_f491_u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    let random_vector = |next: &mut dyn FnMut() -> u32| -> Vector<char> {
+        let len = next() % 20;
+        (0..len).map(|_| (b'a' + (next() % 5) as u8) as char).collect()
+    };
+
+    for _ in 0..500 {
+        let old = random_vector(&mut next);
+        let new = random_vector(&mut next);
+        let diffs = check(old.clone(), new.clone());
+        assert_eq!(num_changes(&diffs), old.len() + new.len() - 2 * lcs_len(&old, &new));
+    }
+}
+
+#[test]
+fn large_disjoint() {
+    // Every element has to be replaced, which is the worst case for the
+    // algorithm's running time, and used to be for its memory usage, too.
+    let old: Vector<u32> = (0..2_000).collect();
+    let new: Vector<u32> = (2_000..4_000).collect();
+
+    let diffs = VectorDiff::compute(&old, &new);
+    assert_eq!(diffs.len(), 2_000);
+    assert!(diffs.iter().all(|diff| matches!(diff, VectorDiff::Set { .. })));
+
+    let mut values = old;
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, new);
+}
+
+#[test]
+fn by_key() {
+    let old = vector![(1, 'a'), (2, 'b'), (3, 'c')];
+    let new = vector![(1, 'a'), (2, 'x'), (4, 'd')];
+
+    let diffs = VectorDiff::compute_by_key(&old, &new, |&(id, _)| id);
+    assert_eq!(
+        diffs,
+        vec![
+            VectorDiff::Set { index: 1, value: (2, 'x') },
+            VectorDiff::Set { index: 2, value: (4, 'd') }
+        ]
+    );
+
+    let old = vector![(1, 'a'), (2, 'b'), (3, 'c')];
+    let new = vector![(2, 'x'), (3, 'c'), (1, 'a')];
+    let diffs = VectorDiff::compute_by_key(&old, &new, |&(id, _)| id);
+    assert_eq!(
+        diffs,
+        vec![
+            VectorDiff::Remove { index: 0 },
+            VectorDiff::Set { index: 0, value: (2, 'x') },
+            VectorDiff::Insert { index: 2, value: (1, 'a') },
+        ]
+    );
+}
+
+#[test]
+fn replace_with_diff() {
+    let mut ob = ObservableVector::from(vector!['a', 'b', 'c']);
+    let mut sub = ob.subscribe().into_batched_stream();
+
+    ob.replace_with_diff(vector!['a', 'b', 'c']);
+    assert_pending!(sub);
+
+    ob.replace_with_diff(vector!['b', 'c', 'd']);
+    assert_eq!(*ob, vector!['b', 'c', 'd']);
+    assert_next_eq!(
+        sub,
+        vec![VectorDiff::Remove { index: 0 }, VectorDiff::Insert { index: 2, value: 'd' }]
+    );
+    assert_pending!(sub);
+}
+
+#[test]
+fn replace_with_diff_by_key() {
+    let mut ob = ObservableVector::from(vector![(1, 'a'), (2, 'b')]);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.replace_with_diff_by_key(vector![(1, 'a'), (2, 'x')], |&(id, _)| id);
+    assert_next_eq!(sub, VectorDiff::Set { index: 1, value: (2, 'x') });
+    assert_pending!(sub);
+}
+
+#[test]
+fn replace_with_diff_no_subscribers() {
+    let mut ob = ObservableVector::from(vector![1, 2, 3]);
+    ob.replace_with_diff(vector![3, 4]);
+    assert_eq!(*ob, vector![3, 4]);
+}
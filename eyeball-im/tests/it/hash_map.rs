@@ -0,0 +1,135 @@
+use imbl::HashMap;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending, assert_ready};
+
+use eyeball_im::{MapDiff, ObservableHashMap};
+
+#[test]
+fn insert_update_remove() {
+    let mut ob = ObservableHashMap::new();
+    let mut sub = ob.subscribe().into_stream();
+
+    assert_eq!(ob.insert("a", 1), None);
+    assert_next_eq!(sub, MapDiff::Insert { key: "a", value: 1 });
+
+    assert_eq!(ob.insert("a", 2), Some(1));
+    assert_next_eq!(sub, MapDiff::Update { key: "a", value: 2 });
+
+    assert_eq!(ob.remove("b"), None);
+    assert_pending!(sub);
+
+    assert_eq!(ob.remove("a"), Some(2));
+    assert_next_eq!(sub, MapDiff::Remove { key: "a" });
+    assert!(ob.is_empty());
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn clear() {
+    let mut ob = ObservableHashMap::from(HashMap::from_iter([(1, 'a'), (2, 'b')]));
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.clear();
+    assert_next_eq!(sub, MapDiff::Clear);
+
+    // The map is empty now, no diff expected.
+    ob.clear();
+    assert_pending!(sub);
+}
+
+#[test]
+fn lag() {
+    let mut ob = ObservableHashMap::with_capacity(1);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.insert(1, "one");
+    ob.insert(2, "two");
+
+    assert_next_eq!(sub, MapDiff::Reset { values: HashMap::from_iter([(1, "one"), (2, "two")]) });
+    assert_pending!(sub);
+}
+
+#[test]
+fn lagging_batch_stream() {
+    let mut ob = ObservableHashMap::new();
+    let (values, mut st) = ob.subscribe().into_values_and_batched_stream();
+    assert!(values.is_empty());
+
+    ob.insert('x', 0);
+    ob.insert('x', 1);
+    ob.remove(&'x');
+
+    assert_next_eq!(
+        st,
+        vec![
+            MapDiff::Insert { key: 'x', value: 0 },
+            MapDiff::Update { key: 'x', value: 1 },
+            MapDiff::Remove { key: 'x' },
+        ]
+    );
+    assert_pending!(st);
+}
+
+#[test]
+fn transaction() {
+    let mut ob = ObservableHashMap::new();
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.insert("a", 1);
+    txn.insert("b", 2);
+    assert_eq!(txn.get("b"), Some(&2));
+    assert_pending!(st);
+
+    txn.commit();
+    assert_next_eq!(
+        st,
+        vec![MapDiff::Insert { key: "a", value: 1 }, MapDiff::Insert { key: "b", value: 2 }]
+    );
+
+    let mut txn = ob.transaction();
+    txn.remove("a");
+    txn.clear();
+    txn.insert("c", 3);
+    txn.commit();
+    assert_next_eq!(st, vec![MapDiff::Clear, MapDiff::Insert { key: "c", value: 3 }]);
+    assert_eq!(*ob, HashMap::from_iter([("c", 3)]));
+}
+
+#[test]
+fn transaction_rollback() {
+    let mut ob = ObservableHashMap::new();
+    let mut st = ob.subscribe().into_stream();
+
+    let mut txn = ob.transaction();
+    txn.insert(1, 1);
+    drop(txn);
+    assert_pending!(st);
+
+    let mut txn = ob.transaction();
+    txn.insert(1, 1);
+    txn.rollback();
+    txn.insert(2, 2);
+    txn.commit();
+
+    assert_next_eq!(st, MapDiff::Insert { key: 2, value: 2 });
+    assert_pending!(st);
+}
+
+#[test]
+fn apply() {
+    let mut ob = ObservableHashMap::new();
+    let (mut copy, mut st) = ob.subscribe().into_values_and_stream();
+
+    ob.insert(1, 'a');
+    ob.insert(2, 'b');
+    ob.insert(1, 'c');
+    ob.remove(&2);
+
+    for _ in 0..4 {
+        assert_ready!(st).apply(&mut copy);
+    }
+    assert_pending!(st);
+    assert_eq!(copy, *ob);
+}
@@ -0,0 +1,118 @@
+use imbl::vector;
+use stream_assert::{assert_closed, assert_next_eq, assert_next_matches, assert_pending};
+
+use eyeball_im::{LagPolicy, ObservableVector, VectorDiff};
+
+#[test]
+fn reset() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let sub = ob.subscribe_with_lag_policy(LagPolicy::Reset);
+    assert_eq!(sub.lag_policy(), LagPolicy::Reset);
+    let mut sub = sub.into_fallible_stream();
+
+    ob.push_back(1);
+    ob.push_back(2);
+    assert_next_eq!(sub, Ok(VectorDiff::Reset { values: vector![1, 2] }));
+    assert_pending!(sub);
+}
+
+#[test]
+fn error() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let mut sub = ob.subscribe_with_lag_policy(LagPolicy::Error).into_fallible_stream();
+
+    ob.push_back(1);
+    assert_next_eq!(sub, Ok(VectorDiff::PushBack { value: 1 }));
+
+    ob.push_back(2);
+    ob.push_back(3);
+    let next = assert_next_matches!(sub, Err(e) => e);
+    assert_eq!(next.to_string(), "subscriber lagged too far behind");
+
+    // The stream ends after the error, even with further updates
+    ob.push_back(4);
+    assert_closed!(sub);
+}
+
+#[test]
+fn error_batched() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let mut sub = ob.subscribe_with_lag_policy(LagPolicy::Error).into_fallible_batched_stream();
+
+    ob.push_back(1);
+    ob.push_back(2);
+    assert_next_matches!(sub, Err(_));
+    assert_closed!(sub);
+}
+
+#[test]
+fn error_infallible_stream() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let mut sub = ob.subscribe_with_lag_policy(LagPolicy::Error).into_stream();
+
+    // Infallible streams fall back to resetting
+    ob.push_back(1);
+    ob.push_back(2);
+    assert_next_eq!(sub, VectorDiff::Reset { values: vector![1, 2] });
+}
+
+#[test]
+fn unbounded() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let (values, mut sub) =
+        ob.subscribe_with_lag_policy(LagPolicy::Unbounded).into_values_and_fallible_stream();
+    assert!(values.is_empty());
+
+    for i in 0..100 {
+        ob.push_back(i);
+    }
+    for i in 0..100 {
+        assert_next_eq!(sub, Ok(VectorDiff::PushBack { value: i }));
+    }
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn unbounded_batched() {
+    let mut ob = ObservableVector::with_capacity(1);
+    let mut sub = ob.subscribe_with_lag_policy(LagPolicy::Unbounded).into_batched_stream();
+    let mut reset_sub = ob.subscribe().into_batched_stream();
+
+    ob.push_back(0);
+    let mut txn = ob.transaction();
+    txn.push_back(1);
+    txn.remove(0);
+    txn.commit();
+    ob.push_front(2);
+
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::PushBack { value: 0 },
+            VectorDiff::PushBack { value: 1 },
+            VectorDiff::Remove { index: 0 },
+            VectorDiff::PushFront { value: 2 },
+        ]
+    );
+    assert_pending!(sub);
+    assert_next_eq!(reset_sub, vec![VectorDiff::Reset { values: vector![2, 1] }]);
+}
+
+#[test]
+fn unbounded_dropped_subscriber() {
+    let mut ob = ObservableVector::new();
+    let sub = ob.subscribe_with_lag_policy(LagPolicy::Unbounded);
+
+    // Without any subscribers, the transaction doesn't collect diffs
+    drop(sub);
+    let mut txn = ob.transaction();
+    txn.push_back(1);
+    txn.commit();
+
+    let mut sub = ob.subscribe_with_lag_policy(LagPolicy::Unbounded).into_stream();
+    ob.push_back(2);
+    assert_next_eq!(sub, VectorDiff::PushBack { value: 2 });
+}
@@ -1,5 +1,7 @@
 #![allow(missing_docs)]
 
+use std::time::Duration;
+
 use imbl::{vector, Vector};
 use stream_assert::{assert_closed, assert_next_eq, assert_pending};
 
@@ -7,9 +9,16 @@ use eyeball_im::{ObservableVector, ObservableVectorEntry, VectorDiff};
 
 mod apply;
 mod batch;
+mod blocking;
+mod btree_map;
+mod compact;
+mod compute;
 mod entry;
+mod hash_map;
+mod lag_policy;
 #[cfg(feature = "serde")]
 mod serde;
+mod set;
 
 #[test]
 fn lag() {
@@ -62,6 +71,254 @@ fn truncate() {
     assert!(ob.is_empty());
 }
 
+#[test]
+fn move_item() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2, 3, 4]);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.move_item(2, 2);
+    assert_pending!(sub);
+
+    ob.move_item(0, 2);
+    assert_next_eq!(sub, VectorDiff::Move { from: 0, to: 2 });
+    assert_eq!(*ob, vector![2, 3, 1, 4]);
+
+    ob.move_item(3, 0);
+    assert_next_eq!(sub, VectorDiff::Move { from: 3, to: 0 });
+    assert_eq!(*ob, vector![4, 2, 3, 1]);
+}
+
+#[test]
+fn swap() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2, 3, 4]);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.swap(1, 1);
+    assert_pending!(sub);
+
+    ob.swap(3, 0);
+    assert_next_eq!(sub, VectorDiff::Swap { first: 3, second: 0 });
+    assert_eq!(*ob, vector![4, 2, 3, 1]);
+
+    let mut txn = ob.transaction();
+    txn.swap(0, 1);
+    txn.move_item(3, 1);
+    txn.commit();
+    assert_next_eq!(sub, VectorDiff::Swap { first: 0, second: 1 });
+    assert_next_eq!(sub, VectorDiff::Move { from: 3, to: 1 });
+    assert_eq!(*ob, vector![2, 1, 4, 3]);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds: the length is 2 but the index is 2")]
+fn move_item_out_of_bounds() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2]);
+    ob.move_item(0, 2);
+}
+
+#[test]
+fn insert_many() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 4]);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.insert_many(1, vector![]);
+    assert_pending!(sub);
+
+    ob.insert_many(1, vector![2, 3]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 1, values: vector![2, 3] });
+    assert_eq!(*ob, vector![1, 2, 3, 4]);
+
+    ob.insert_many(4, vector![5]);
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 4, values: vector![5] });
+    assert_eq!(*ob, vector![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn remove_range() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2, 3, 4, 5]);
+    let mut sub = ob.subscribe().into_stream();
+
+    assert_eq!(ob.remove_range(2..2), vector![]);
+    assert_pending!(sub);
+
+    assert_eq!(ob.remove_range(1..3), vector![2, 3]);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 1, end: 3 });
+    assert_eq!(*ob, vector![1, 4, 5]);
+
+    assert_eq!(ob.remove_range(1..), vector![4, 5]);
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 1, end: 3 });
+    assert_eq!(*ob, vector![1]);
+}
+
+#[test]
+fn splice() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2, 3, 4]);
+    let mut sub = ob.subscribe().into_stream();
+
+    assert_eq!(ob.splice(1..1, vector![]), vector![]);
+    assert_pending!(sub);
+
+    assert_eq!(ob.splice(1..=2, vector![5, 6, 7]), vector![2, 3]);
+    assert_next_eq!(sub, VectorDiff::Splice { start: 1, end: 3, values: vector![5, 6, 7] });
+    assert_eq!(*ob, vector![1, 5, 6, 7, 4]);
+
+    let mut txn = ob.transaction();
+    txn.splice(..2, vector![]);
+    txn.insert_many(0, vector![8, 9]);
+    txn.remove_range(3..);
+    txn.commit();
+    assert_next_eq!(sub, VectorDiff::Splice { start: 0, end: 2, values: vector![] });
+    assert_next_eq!(sub, VectorDiff::InsertMany { index: 0, values: vector![8, 9] });
+    assert_next_eq!(sub, VectorDiff::RemoveRange { start: 3, end: 5 });
+    assert_eq!(*ob, vector![8, 9, 6]);
+}
+
+#[test]
+#[should_panic(expected = "range end index 3 out of range for vector of length 2")]
+fn remove_range_out_of_bounds() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2]);
+    ob.remove_range(1..3);
+}
+
+#[test]
+fn retain() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    let mut sub = ob.subscribe().into_batched_stream();
+
+    ob.retain(|value| *value < 10);
+    assert_pending!(sub);
+
+    ob.retain(|value| ![2, 3, 5, 8, 9].contains(value));
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::RemoveRange { start: 1, end: 3 },
+            VectorDiff::Remove { index: 2 },
+            VectorDiff::Truncate { length: 4 },
+        ]
+    );
+    assert_eq!(*ob, vector![1, 4, 6, 7]);
+
+    let mut txn = ob.transaction();
+    txn.push_back(10);
+    txn.retain(|value| value % 2 == 1);
+    txn.commit();
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::PushBack { value: 10 },
+            VectorDiff::RemoveRange { start: 1, end: 3 },
+            VectorDiff::Truncate { length: 2 },
+        ]
+    );
+    assert_eq!(*ob, vector![1, 7]);
+
+    ob.retain(|_| false);
+    assert_next_eq!(sub, vec![VectorDiff::Clear]);
+}
+
+#[test]
+fn sort_by() {
+    let mut ob: ObservableVector<(i32, char)> =
+        ObservableVector::from(vector![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')]);
+    let mut sub = ob.subscribe().into_batched_stream();
+
+    // The sort is stable.
+    ob.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_next_eq!(
+        sub,
+        vec![VectorDiff::Move { from: 0, to: 3 }, VectorDiff::Move { from: 1, to: 3 }]
+    );
+    assert_eq!(*ob, vector![(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c')]);
+
+    ob.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_pending!(sub);
+
+    let mut txn = ob.transaction();
+    txn.sort_by(|a, b| b.cmp(a));
+    txn.commit();
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::Move { from: 2, to: 3 },
+            VectorDiff::Move { from: 1, to: 3 },
+            VectorDiff::Move { from: 0, to: 3 },
+        ]
+    );
+    assert_eq!(*ob, vector![(1, 'c'), (1, 'a'), (0, 'd'), (0, 'b')]);
+}
+
+#[test]
+fn sort_by_random() {
+    let mut ob = ObservableVector::<u32>::new();
+    let mut sub = ob.subscribe().into_batched_stream();
+    let mut values = Vector::new();
+    let mut seed = 0x2545_f491_u32;
+
+    for len in 0..100 {
+        let mut txn = ob.transaction();
+        txn.clear();
+        for _ in 0..len {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            txn.push_back(seed % 20);
+        }
+        txn.sort_by(Ord::cmp);
+        txn.commit();
+
+        while let Ok(Some(diffs)) = sub.next_blocking_timeout(Duration::ZERO) {
+            for diff in diffs {
+                diff.apply(&mut values);
+            }
+        }
+        let mut expected: Vec<_> = ob.iter().copied().collect();
+        expected.sort();
+        assert_eq!(values, expected.into_iter().collect::<Vector<_>>());
+    }
+}
+
+#[test]
+fn sort_by_large() {
+    // Reversing moves every element but one, which used to take quadratic time.
+    let mut ob: ObservableVector<u32> = (0..5_000).rev().collect::<Vector<_>>().into();
+    let (mut values, mut sub) = ob.subscribe().into_values_and_batched_stream();
+
+    ob.sort_by(Ord::cmp);
+    let diffs = sub.next_blocking_timeout(Duration::ZERO).unwrap().unwrap();
+    assert_eq!(diffs.len(), 4_999);
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, (0..5_000).collect::<Vector<_>>());
+}
+
+#[test]
+fn dedup_by_key() {
+    let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 3, 2, 4, 6, 5, 7, 8]);
+    let mut sub = ob.subscribe().into_batched_stream();
+
+    ob.dedup_by_key(|value| *value);
+    assert_pending!(sub);
+
+    ob.dedup_by_key(|value| value % 2);
+    assert_next_eq!(
+        sub,
+        vec![
+            VectorDiff::Remove { index: 1 },
+            VectorDiff::RemoveRange { start: 2, end: 4 },
+            VectorDiff::Remove { index: 3 },
+        ]
+    );
+    assert_eq!(*ob, vector![1, 2, 5, 8]);
+
+    let mut txn = ob.transaction();
+    txn.dedup_by_key(|value| value / 4);
+    txn.commit();
+    assert_next_eq!(sub, vec![VectorDiff::Remove { index: 1 }]);
+    assert_eq!(*ob, vector![1, 5, 8]);
+}
+
 #[test]
 fn clear() {
     let mut ob: ObservableVector<i32> = ObservableVector::from(vector![1, 2]);
@@ -149,6 +406,133 @@ fn transaction_rollback() {
     assert_next_eq!(st, VectorDiff::Insert { index: 0, value: 123 });
 }
 
+#[test]
+fn transaction_savepoint() {
+    let mut ob = ObservableVector::from(vector![1]);
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.push_back(2);
+    let savepoint = txn.savepoint();
+    txn.push_back(3);
+    let later_savepoint = txn.savepoint();
+    txn.push_back(4);
+
+    txn.rollback_to(&later_savepoint);
+    assert_eq!(*txn, vector![1, 2, 3]);
+    txn.push_front(0);
+
+    // A savepoint can be rolled back to multiple times.
+    txn.rollback_to(&savepoint);
+    assert_eq!(*txn, vector![1, 2]);
+    txn.pop_front();
+    txn.rollback_to(&savepoint);
+    txn.push_back(5);
+    txn.commit();
+
+    assert_next_eq!(st, vec![VectorDiff::PushBack { value: 2 }, VectorDiff::PushBack { value: 5 }]);
+    assert_eq!(*ob, vector![1, 2, 5]);
+}
+
+#[test]
+fn transaction_rollback_past_clear() {
+    let mut ob = ObservableVector::from(vector![1, 2]);
+    let (mut values, mut st) = ob.subscribe().into_values_and_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.push_back(3);
+    let savepoint = txn.savepoint();
+    txn.clear();
+    txn.push_back(9);
+    txn.rollback_to(&savepoint);
+    txn.commit();
+    assert_eq!(*ob, vector![1, 2, 3]);
+
+    // Dropping a nested transaction rolls back past the clear, too.
+    let mut txn = ob.transaction();
+    let mut nested = txn.transaction();
+    nested.clear();
+    drop(nested);
+    txn.push_back(4);
+    txn.commit();
+    assert_eq!(*ob, vector![1, 2, 3, 4]);
+
+    while let Ok(Some(diffs)) = st.next_blocking_timeout(Duration::ZERO) {
+        for diff in diffs {
+            diff.apply(&mut values);
+        }
+    }
+    assert_eq!(values, *ob);
+}
+
+#[test]
+#[should_panic]
+fn transaction_rollback_to_invalidated_savepoint() {
+    let mut ob = ObservableVector::<i32>::new();
+    let mut txn = ob.transaction();
+    let savepoint = txn.savepoint();
+    let later_savepoint = txn.savepoint();
+    txn.rollback_to(&savepoint);
+    txn.rollback_to(&later_savepoint);
+}
+
+#[test]
+#[should_panic(expected = "savepoint was invalidated or belongs to another transaction")]
+fn transaction_rollback_to_savepoint_of_other_transaction() {
+    let mut ob = ObservableVector::<i32>::new();
+    let mut other_ob = ObservableVector::from(vector![1, 2, 3]);
+
+    // Both savepoints are the first of their transaction.
+    let mut other_txn = other_ob.transaction();
+    let other_savepoint = other_txn.savepoint();
+    let mut txn = ob.transaction();
+    let _savepoint = txn.savepoint();
+    txn.rollback_to(&other_savepoint);
+}
+
+#[test]
+fn nested_transaction() {
+    let mut ob = ObservableVector::new();
+    let mut st = ob.subscribe().into_batched_stream();
+
+    let mut txn = ob.transaction();
+    txn.push_back(1);
+
+    // Changes are kept on commit.
+    let mut nested = txn.transaction();
+    nested.push_back(2);
+    nested.commit();
+
+    // Changes are rolled back on drop.
+    let mut nested = txn.transaction();
+    nested.push_back(3);
+    let mut inner = nested.transaction();
+    inner.push_back(4);
+    inner.commit();
+    assert_eq!(**nested, vector![1, 2, 3, 4]);
+    drop(nested);
+    assert_eq!(*txn, vector![1, 2]);
+
+    // Changes are rolled back explicitly, but the nested transaction can be
+    // used further.
+    let mut nested = txn.transaction();
+    nested.set(0, 5);
+    nested.rollback();
+    nested.push_front(6);
+    nested.commit();
+
+    txn.commit();
+    assert_next_eq!(
+        st,
+        vec![
+            VectorDiff::PushBack { value: 1 },
+            VectorDiff::PushBack { value: 2 },
+            VectorDiff::PushFront { value: 6 },
+        ]
+    );
+    assert_eq!(*ob, vector![6, 1, 2]);
+}
+
 #[test]
 fn transaction_no_subscribers() {
     let mut ob = ObservableVector::new();
@@ -1,5 +1,5 @@
 use eyeball_im::VectorDiff;
-use imbl::vector;
+use imbl::{vector, Vector};
 
 macro_rules! test {
     ($test_name:ident: $vector_diff:expr => $json:expr) => {
@@ -10,6 +10,9 @@ macro_rules! test {
 
             assert_eq!(json, $json);
 
+            let deserialized: VectorDiff<char> = serde_json::from_str(&json)?;
+            assert_eq!(deserialized, vector_diff);
+
             Ok(())
         }
     };
@@ -26,3 +29,48 @@ test!(set: VectorDiff::Set { index: 42, value: 'a' } => r#"{"Set":{"index":42,"v
 test!(remove: VectorDiff::Remove { index: 42 } => r#"{"Remove":{"index":42}}"#);
 test!(truncate: VectorDiff::Truncate { length: 3 } => r#"{"Truncate":{"length":3}}"#);
 test!(reset: VectorDiff::Reset { values: vector!['a', 'b'] } => r#"{"Reset":{"values":["a","b"]}}"#);
+test!(move_: VectorDiff::Move { from: 1, to: 3 } => r#"{"Move":{"from":1,"to":3}}"#);
+test!(swap: VectorDiff::Swap { first: 1, second: 3 } => r#"{"Swap":{"first":1,"second":3}}"#);
+test!(insert_many: VectorDiff::InsertMany { index: 1, values: vector!['a'] } => r#"{"InsertMany":{"index":1,"values":["a"]}}"#);
+test!(remove_range: VectorDiff::RemoveRange { start: 1, end: 3 } => r#"{"RemoveRange":{"start":1,"end":3}}"#);
+test!(splice: VectorDiff::Splice { start: 1, end: 3, values: vector!['a'] } => r#"{"Splice":{"start":1,"end":3,"values":["a"]}}"#);
+
+#[test]
+fn deserialize_errors() {
+    let res = serde_json::from_str::<VectorDiff<char>>(r#"{"Rotate":{}}"#);
+    assert!(res.unwrap_err().to_string().starts_with("unknown variant `Rotate`"));
+
+    let res = serde_json::from_str::<VectorDiff<char>>(r#"{"Insert":{"value":"a"}}"#);
+    assert!(res.unwrap_err().to_string().starts_with("missing field `index`"));
+
+    let res = serde_json::from_str::<VectorDiff<char>>(r#"{"Remove":{"index":1,"index":2}}"#);
+    assert!(res.unwrap_err().to_string().starts_with("duplicate field `index`"));
+}
+
+#[test]
+fn deserialize_ignores_unknown_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let diff: VectorDiff<char> =
+        serde_json::from_str(r#"{"Remove":{"index":1,"value":"a","extra":[1,2]}}"#)?;
+    assert_eq!(diff, VectorDiff::Remove { index: 1 });
+
+    Ok(())
+}
+
+#[test]
+fn replay() -> Result<(), Box<dyn std::error::Error>> {
+    let diffs = vec![
+        VectorDiff::Append { values: vector!['a', 'b', 'c'] },
+        VectorDiff::Remove { index: 1 },
+        VectorDiff::PushFront { value: 'z' },
+        VectorDiff::Set { index: 2, value: 'y' },
+    ];
+    let json = serde_json::to_string(&diffs)?;
+
+    let mut values = Vector::new();
+    for diff in serde_json::from_str::<Vec<VectorDiff<char>>>(&json)? {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, vector!['z', 'a', 'y']);
+
+    Ok(())
+}
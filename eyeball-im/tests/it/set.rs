@@ -0,0 +1,88 @@
+use imbl::{HashSet, OrdSet};
+use stream_assert::{assert_closed, assert_next_eq, assert_pending, assert_ready};
+
+use eyeball_im::{ObservableHashSet, ObservableOrdSet, SetDiff};
+
+#[test]
+fn hash_set() {
+    let mut ob = ObservableHashSet::new();
+    let mut sub = ob.subscribe().into_stream();
+
+    assert!(ob.insert("alice"));
+    assert_next_eq!(sub, SetDiff::Insert { value: "alice" });
+
+    assert!(!ob.insert("alice"));
+    assert_pending!(sub);
+
+    assert!(ob.insert("bob"));
+    assert!(ob.remove("alice"));
+    assert!(!ob.remove("alice"));
+    assert_next_eq!(sub, SetDiff::Insert { value: "bob" });
+    assert_next_eq!(sub, SetDiff::Remove { value: "alice" });
+    assert_pending!(sub);
+
+    ob.clear();
+    assert_next_eq!(sub, SetDiff::Clear);
+    ob.clear();
+    assert_pending!(sub);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn hash_set_lag() {
+    let mut ob = ObservableHashSet::with_capacity(1);
+    let mut sub = ob.subscribe().into_stream();
+    let mut batched_sub = ob.subscribe().into_batched_stream();
+
+    ob.insert(1);
+    ob.insert(2);
+    ob.remove(&1);
+
+    assert_next_eq!(sub, SetDiff::Reset { values: HashSet::unit(2) });
+    assert_pending!(sub);
+    assert_next_eq!(batched_sub, vec![SetDiff::Reset { values: HashSet::unit(2) }]);
+    assert_pending!(batched_sub);
+}
+
+#[test]
+fn ord_set() {
+    let mut ob = ObservableOrdSet::from(OrdSet::from_iter([3, 1]));
+    let (mut values, mut sub) = ob.subscribe().into_values_and_batched_stream();
+
+    ob.insert(2);
+    ob.insert(3);
+    ob.remove(&1);
+    ob.insert(4);
+
+    let diffs = assert_ready!(sub);
+    assert_eq!(
+        diffs,
+        vec![
+            SetDiff::Insert { value: 2 },
+            SetDiff::Remove { value: 1 },
+            SetDiff::Insert { value: 4 },
+        ]
+    );
+    for diff in diffs {
+        diff.apply(&mut values);
+    }
+    assert_eq!(values, *ob);
+    assert_eq!(values.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+}
+
+#[test]
+fn ord_set_lag() {
+    let mut ob = ObservableOrdSet::with_capacity(2);
+    let mut sub = ob.subscribe().into_stream();
+
+    ob.insert('a');
+    ob.insert('b');
+    assert_next_eq!(sub, SetDiff::Insert { value: 'a' });
+
+    ob.insert('c');
+    ob.insert('d');
+    assert_next_eq!(sub, SetDiff::Reset { values: OrdSet::from_iter(['a', 'b', 'c', 'd']) });
+    assert_pending!(sub);
+}
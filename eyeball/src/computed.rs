@@ -0,0 +1,397 @@
+//! This module defines a [`Computed`] type whose value is derived from other
+//! observables.
+//!
+//! Use this for values that should be kept up to date with one or more
+//! [`SharedObservable`](crate::SharedObservable)s or
+//! [`Subscriber`](crate::Subscriber)s, without spawning a task that
+//! forwards their updates to a new observable.
+
+use std::{
+    fmt,
+    future::poll_fn,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use crate::{state::ObservableState, subscriber::SubscriberExt};
+
+/// A value that is computed from other observables and broadcasts changes to
+/// subscribers.
+///
+/// The value is only recomputed when it is requested (through
+/// [`get`][Self::get] or a [`ComputedSubscriber`]) and at least one of its
+/// sources has been updated since it was last computed.
+///
+/// ```
+/// use eyeball::{Computed, ComputedSource, SharedObservable};
+///
+/// let first_name = SharedObservable::new("Jane".to_owned());
+/// let last_name = SharedObservable::new("Doe".to_owned());
+///
+/// let full_name =
+///     Computed::new([ComputedSource::from(&first_name), ComputedSource::from(&last_name)], {
+///         let first_name = first_name.clone();
+///         let last_name = last_name.clone();
+///         move || format!("{} {}", *first_name.read(), *last_name.read())
+///     });
+/// let mut subscriber = full_name.subscribe();
+/// assert_eq!(full_name.get(), "Jane Doe");
+///
+/// last_name.set("Roe".to_owned());
+/// assert_eq!(subscriber.next_now(), "Jane Roe");
+/// ```
+pub struct Computed<T> {
+    inner: Arc<ComputedInner<T>>,
+    /// Ugly hack to track the amount of clones of this observable,
+    /// *excluding subscribers*.
+    _num_clones: Arc<()>,
+}
+
+impl<T: Send + 'static> Computed<T> {
+    /// Create a new `Computed` from the given sources and the function that
+    /// computes its value.
+    ///
+    /// `compute` is called once right away to get the initial value, and after
+    /// that whenever the value is requested after any of the `sources` have
+    /// been updated. It will usually read the current value of the sources,
+    /// but the sources are only used to track whether a recomputation is
+    /// necessary, so it is up to `compute` how it accesses their values.
+    pub fn new<F>(sources: impl IntoIterator<Item = ComputedSource>, compute: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        let sources: Vec<_> = sources.into_iter().collect();
+        // Read the versions before computing the value, such that updates
+        // happening in between lead to another recomputation.
+        let source_versions = sources.iter().map(|source| source.0.version()).collect();
+        let cache = ComputedCache {
+            value: compute(),
+            source_versions,
+            version: 1,
+            closed: false,
+            wakers: Vec::new(),
+        };
+
+        let inner = ComputedInner { compute: Box::new(compute), sources, cache: Mutex::new(cache) };
+        Self { inner: Arc::new(inner), _num_clones: Arc::new(()) }
+    }
+
+    /// Obtain a new subscriber.
+    ///
+    /// Calling `.next().await` on the returned subscriber only resolves once
+    /// the value has changed again after the call to `subscribe`.
+    ///
+    /// See [`subscribe_reset`][Self::subscribe_reset] if you want to obtain a
+    /// subscriber that immediately yields without any updates.
+    pub fn subscribe(&self) -> ComputedSubscriber<T> {
+        let version = {
+            let mut cache = self.inner.cache.lock().unwrap();
+            self.inner.refresh(&mut cache);
+            cache.version
+        };
+        ComputedSubscriber { inner: Arc::clone(&self.inner), observed_version: version }
+    }
+
+    /// Obtain a new subscriber that immediately yields.
+    ///
+    /// `.subscribe_reset()` is equivalent to `.subscribe()` with a subsequent
+    /// call to [`.reset()`][ComputedSubscriber::reset] on the returned
+    /// subscriber.
+    pub fn subscribe_reset(&self) -> ComputedSubscriber<T> {
+        ComputedSubscriber { inner: Arc::clone(&self.inner), observed_version: 0 }
+    }
+
+    /// Get a clone of the current value, recomputing it if necessary.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.inner.get()
+    }
+
+    /// Get the number of `Computed` clones.
+    ///
+    /// This always returns at least `1` since `self` is included in the count.
+    ///
+    /// Be careful when using this. The result is only reliable if it is exactly
+    /// `1`, as otherwise it could be incremented right after your call to this
+    /// function, before you look at its result or do anything based on that.
+    #[must_use]
+    pub fn observable_count(&self) -> usize {
+        Arc::strong_count(&self._num_clones)
+    }
+
+    /// Get the number of subscribers.
+    ///
+    /// Be careful when using this. The result can change right after your call
+    /// to this function, before you look at its result or do anything based
+    /// on that.
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        Arc::strong_count(&self.inner) - self.observable_count()
+    }
+}
+
+impl<T> Clone for Computed<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), _num_clones: self._num_clones.clone() }
+    }
+}
+
+impl<T> fmt::Debug for Computed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Computed")
+            .field("num_sources", &self.inner.sources.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Computed<T> {
+    fn drop(&mut self) {
+        // Only close the cache if there are no other clones of this
+        // `Computed`.
+        if Arc::strong_count(&self._num_clones) == 1 {
+            let mut cache = self.inner.cache.lock().unwrap();
+            cache.closed = true;
+            wake(mem::take(&mut cache.wakers));
+        }
+    }
+}
+
+/// A source of a [`Computed`] value.
+///
+/// Create one from a [`SharedObservable`](crate::SharedObservable) or a
+/// [`Subscriber`](crate::Subscriber) using its [`From`] implementations.
+#[derive(Clone)]
+pub struct ComputedSource(Arc<dyn SourceState>);
+
+impl ComputedSource {
+    pub(crate) fn new(state: Arc<dyn SourceState>) -> Self {
+        Self(state)
+    }
+}
+
+impl fmt::Debug for ComputedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComputedSource").field("version", &self.0.version()).finish()
+    }
+}
+
+/// Type-erased access to the version of an [`ObservableState`].
+pub(crate) trait SourceState: Send + Sync {
+    fn version(&self) -> u64;
+    fn poll_update(&self, observed_version: &mut u64, cx: &Context<'_>) -> Poll<Option<()>>;
+}
+
+impl<T: Send + Sync> SourceState for std::sync::RwLock<ObservableState<T>> {
+    fn version(&self) -> u64 {
+        self.read().unwrap().version()
+    }
+
+    fn poll_update(&self, observed_version: &mut u64, cx: &Context<'_>) -> Poll<Option<()>> {
+        self.read().unwrap().poll_update(observed_version, cx)
+    }
+}
+
+impl<T: Send + Sync> SourceState for readlock::SharedReadLock<ObservableState<T>> {
+    fn version(&self) -> u64 {
+        self.lock().version()
+    }
+
+    fn poll_update(&self, observed_version: &mut u64, cx: &Context<'_>) -> Poll<Option<()>> {
+        self.lock().poll_update(observed_version, cx)
+    }
+}
+
+/// A subscriber for updates of a [`Computed`] value.
+#[must_use]
+pub struct ComputedSubscriber<T> {
+    inner: Arc<ComputedInner<T>>,
+    observed_version: u64,
+}
+
+impl<T: Send + 'static> ComputedSubscriber<T> {
+    /// Wait for the value to change and get a clone of the updated value.
+    ///
+    /// Awaiting returns `Some(_)` after the value changed, or `None` after the
+    /// `Computed` (and all of its clones) is dropped.
+    pub async fn next(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        poll_fn(|cx| self.poll_next_value(cx)).await
+    }
+
+    /// Get a clone of the current value without waiting for an update.
+    ///
+    /// The returned value is marked as observed such that a subsequent call of
+    /// [`next`][Self::next] won't return the same value again. See
+    /// [`get`][Self::get] for a function that doesn't mark the value as
+    /// observed.
+    #[must_use]
+    pub fn next_now(&mut self) -> T
+    where
+        T: Clone,
+    {
+        let mut cache = self.inner.cache.lock().unwrap();
+        self.inner.refresh(&mut cache);
+        self.observed_version = cache.version;
+        cache.value.clone()
+    }
+
+    /// Get a clone of the current value without waiting for an update.
+    ///
+    /// The returned value is **not** marked as observed such that a
+    /// subsequent call of [`next`][Self::next] will return the same value
+    /// again if it has not been observed before.
+    #[must_use]
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.inner.get()
+    }
+
+    /// Reset the observed version of the value.
+    ///
+    /// After calling this, it is guaranteed that the next call to
+    /// `.next().await` will resolve immediately.
+    pub fn reset(&mut self) {
+        self.observed_version = 0;
+    }
+
+    fn poll_next_value(&mut self, cx: &Context<'_>) -> Poll<Option<T>>
+    where
+        T: Clone,
+    {
+        let mut cache = self.inner.cache.lock().unwrap();
+        loop {
+            self.inner.refresh(&mut cache);
+            if self.observed_version < cache.version {
+                self.observed_version = cache.version;
+                return Poll::Ready(Some(cache.value.clone()));
+            }
+            if cache.closed {
+                return Poll::Ready(None);
+            }
+
+            // Register the waker with every source. If any of them changed
+            // since the refresh above, recompute instead of waiting.
+            let mut changed = false;
+            for (source, &seen) in self.inner.sources.iter().zip(&cache.source_versions) {
+                let mut version = seen;
+                match source.0.poll_update(&mut version, cx) {
+                    Poll::Ready(Some(())) => changed = true,
+                    // A source that was closed after its last update was seen
+                    // can still have an update that wasn't seen.
+                    Poll::Ready(None) => changed |= seen != 0,
+                    Poll::Pending => {}
+                }
+            }
+
+            if !changed {
+                cache.wakers.push(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<T> Clone for ComputedSubscriber<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), observed_version: self.observed_version }
+    }
+}
+
+impl<T> fmt::Debug for ComputedSubscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComputedSubscriber")
+            .field("observed_version", &self.observed_version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone + Send + 'static> Stream for ComputedSubscriber<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_value(cx)
+    }
+}
+
+impl<T: Clone + Send + 'static> SubscriberExt for ComputedSubscriber<T> {
+    fn get(&self) -> T {
+        self.inner.get()
+    }
+
+    fn reset(&mut self) {
+        self.observed_version = 0;
+    }
+}
+
+struct ComputedInner<T> {
+    compute: Box<dyn Fn() -> T + Send + Sync>,
+    sources: Vec<ComputedSource>,
+    cache: Mutex<ComputedCache<T>>,
+}
+
+impl<T> ComputedInner<T> {
+    fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        let mut cache = self.cache.lock().unwrap();
+        self.refresh(&mut cache);
+        cache.value.clone()
+    }
+
+    /// Recompute the value if any of the sources changed since it was last
+    /// computed.
+    fn refresh(&self, cache: &mut ComputedCache<T>) {
+        let mut changed = false;
+        for (source, seen) in self.sources.iter().zip(&mut cache.source_versions) {
+            // Closed sources have version `0`, which is also seen as a change
+            // (exactly once) since the last update before closing may not
+            // have been seen yet.
+            let version = source.0.version();
+            if version != *seen {
+                *seen = version;
+                changed = true;
+            }
+        }
+
+        if changed {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Recomputing value");
+
+            cache.value = (self.compute)();
+            cache.version += 1;
+            wake(mem::take(&mut cache.wakers));
+        }
+    }
+}
+
+struct ComputedCache<T> {
+    /// The last computed value.
+    value: T,
+    /// The versions of the sources at the time `value` was computed.
+    source_versions: Vec<u64>,
+    /// The version of the value.
+    ///
+    /// Starts at 1 and is incremented by 1 each time the value is recomputed.
+    version: u64,
+    /// Whether all clones of the `Computed` have been dropped.
+    closed: bool,
+    /// Wakers of subscribers that are waiting for the value to change.
+    wakers: Vec<Waker>,
+}
+
+fn wake(wakers: Vec<Waker>) {
+    for waker in wakers {
+        waker.wake();
+    }
+}
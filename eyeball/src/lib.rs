@@ -3,8 +3,9 @@
 //! This crate implements a basic form of the [Observer pattern][] for Rust.
 //! It provides [`Observable<T>`] as a type that semi-transparently wraps an
 //! inner value `T` and broadcasts changes to any associated [`Subscriber<T>`]s.
-//! `Subscriber`s can currently only be polled for updates using `async` /
-//! `.await`, but this may change in the future.
+//! `Subscriber`s are usually polled for updates using `async` / `.await`, but
+//! they can also block the current thread while waiting for an update, see
+//! [`Subscriber::next_blocking`].
 //!
 //! There is also [`SharedObservable<T>`] as another variation which
 //! implements [`Clone`] but not [`Deref`][std::ops::Deref]. It is more
@@ -65,12 +66,15 @@
 //!
 //! Cargo features:
 //!
+//! - `serde`: Implement `Serialize` and `Deserialize` for [`Observable`] and
+//!   [`SharedObservable`], (de)serializing them as their inner value
 //! - `tracing`: Emit [tracing] events when updates are sent out
 //!
 //! [Observer pattern]: https://en.wikipedia.org/wiki/Observer_pattern
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+mod computed;
 mod lock;
 mod read_guard;
 mod shared;
@@ -83,9 +87,10 @@ mod unique;
 pub use self::lock::AsyncLock;
 #[doc(inline)]
 pub use self::{
+    computed::{Computed, ComputedSource, ComputedSubscriber},
     lock::SyncLock,
     read_guard::ObservableReadGuard,
     shared::{ObservableWriteGuard, SharedObservable, WeakObservable},
-    subscriber::Subscriber,
+    subscriber::{Subscriber, SubscriberExt},
     unique::Observable,
 };
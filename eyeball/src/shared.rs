@@ -20,7 +20,9 @@ use readlock_tokio::{
 
 #[cfg(feature = "async-lock")]
 use crate::AsyncLock;
-use crate::{lock::Lock, state::ObservableState, ObservableReadGuard, Subscriber, SyncLock};
+use crate::{
+    lock::Lock, state::ObservableState, ComputedSource, ObservableReadGuard, Subscriber, SyncLock,
+};
 
 /// A value whose changes will be broadcast to subscribers.
 ///
@@ -424,6 +426,32 @@ where
     }
 }
 
+impl<T: Send + Sync + 'static> From<&SharedObservable<T>> for ComputedSource {
+    fn from(observable: &SharedObservable<T>) -> Self {
+        ComputedSource::new(observable.state.clone())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SharedObservable<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        T::serialize(&self.read(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for SharedObservable<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
 impl<T, L: Lock> Drop for SharedObservable<T, L> {
     fn drop(&mut self) {
         // Only close the state if there are no other clones of this
@@ -7,15 +7,24 @@ use std::{
     fmt,
     future::{poll_fn, Future},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use futures_core::Stream;
 
-use crate::{lock::Lock, state::ObservableState, ObservableReadGuard, SyncLock};
+use crate::{lock::Lock, state::ObservableState, ComputedSource, ObservableReadGuard, SyncLock};
 
 #[cfg(feature = "async-lock")]
 pub(crate) mod async_lock;
+mod blocking;
+mod ext;
+
+pub use self::{
+    blocking::{BlockingIter, Elapsed},
+    ext::{CombineLatest, DedupBy, DedupEq, Filter, Map, SubscriberExt, Zip},
+};
 
 /// A subscriber for updates of an `Observable`.
 #[must_use]
@@ -45,6 +54,45 @@ impl<T> Subscriber<T> {
         Next::new(self)
     }
 
+    /// Block the current thread until there is an update and get a clone of
+    /// the updated value.
+    ///
+    /// Returns `Some(_)` after an update happened, or `None` after the
+    /// `Observable` (and all clones for `shared::Observable`) is dropped.
+    ///
+    /// This is the blocking equivalent of [`next`][Self::next], for use outside
+    /// of async code. Do not call it from within an async runtime, it will
+    /// block the runtime's worker thread.
+    pub fn next_blocking(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        blocking::block_on(|cx| self.poll_next_ref(cx).map(opt_guard_to_owned))
+    }
+
+    /// Block the current thread until there is an update or the given timeout
+    /// elapses, and get a clone of the updated value.
+    ///
+    /// Like [`next_blocking`][Self::next_blocking], but returns an [`Elapsed`]
+    /// error if there was no update and the `Observable` was not dropped
+    /// within the given `timeout`.
+    pub fn next_blocking_timeout(&mut self, timeout: Duration) -> Result<Option<T>, Elapsed>
+    where
+        T: Clone,
+    {
+        blocking::block_on_timeout(timeout, |cx| self.poll_next_ref(cx).map(opt_guard_to_owned))
+    }
+
+    /// Get an iterator that blocks the current thread until the next update on
+    /// each call to `next`.
+    ///
+    /// Iteration ends after the `Observable` (and all clones for
+    /// `shared::Observable`) is dropped. See
+    /// [`next_blocking`][Self::next_blocking] for details.
+    pub fn blocking_iter(&mut self) -> BlockingIter<'_, T> {
+        BlockingIter::new(self)
+    }
+
     /// Get a clone of the inner value without waiting for an update.
     ///
     /// If the returned value has not been observed by this subscriber before,
@@ -187,6 +235,12 @@ where
     }
 }
 
+impl<T: Send + Sync + 'static> From<&Subscriber<T>> for ComputedSource {
+    fn from(subscriber: &Subscriber<T>) -> Self {
+        ComputedSource::new(Arc::new(subscriber.state.clone()))
+    }
+}
+
 impl<T: Clone> Stream for Subscriber<T> {
     type Item = T;
 
@@ -0,0 +1,105 @@
+use std::{
+    error::Error,
+    fmt,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use super::Subscriber;
+
+/// Error returned by [`Subscriber::next_blocking_timeout`] if no update
+/// happened within the given timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// Blocking iterator returned by [`Subscriber::blocking_iter`].
+///
+/// Every call to `next` blocks the current thread until there is an update,
+/// and iteration ends once the `Observable` (and all clones for
+/// `SharedObservable`) is dropped.
+#[must_use]
+#[derive(Debug)]
+pub struct BlockingIter<'a, T> {
+    subscriber: &'a mut Subscriber<T>,
+}
+
+impl<'a, T> BlockingIter<'a, T> {
+    pub(super) fn new(subscriber: &'a mut Subscriber<T>) -> Self {
+        Self { subscriber }
+    }
+}
+
+impl<T: Clone> Iterator for BlockingIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.subscriber.next_blocking()
+    }
+}
+
+/// Call `poll` until it is ready, parking the current thread in between.
+pub(super) fn block_on<T>(poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+    match block_on_until(None, poll) {
+        Ok(value) => value,
+        Err(Elapsed(())) => unreachable!("no deadline was set"),
+    }
+}
+
+/// Call `poll` until it is ready or the timeout elapses, parking the current
+/// thread in between.
+pub(super) fn block_on_timeout<T>(
+    timeout: Duration,
+    poll: impl FnMut(&mut Context<'_>) -> Poll<T>,
+) -> Result<T, Elapsed> {
+    // If the deadline is too far in the future to be represented, wait forever
+    block_on_until(Instant::now().checked_add(timeout), poll)
+}
+
+fn block_on_until<T>(
+    deadline: Option<Instant>,
+    mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>,
+) -> Result<T, Elapsed> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(value) = poll(&mut cx) {
+            return Ok(value);
+        }
+
+        // Parking can wake up spuriously, so the loop re-polls in any case.
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(Elapsed(()));
+                }
+                thread::park_timeout(deadline - now);
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+/// A waker that unparks the thread that created it.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use super::Subscriber;
+
+/// Combinators for [`Subscriber`]s that keep access to the current value.
+///
+/// Unlike the combinators of stream extension traits such as
+/// `futures::StreamExt`, the adapters returned by these methods are
+/// subscribers themselves: they can be asked for their current value using
+/// [`get`][Self::get] without waiting for an update, and they can be
+/// [`reset`][Self::reset] such that the next poll yields immediately.
+pub trait SubscriberExt: Stream + Unpin + Sized {
+    /// Get the current value without waiting for an update.
+    ///
+    /// This does not mark the value as observed, see [`Subscriber::get`].
+    fn get(&self) -> Self::Item;
+
+    /// Reset the observed version of the current value.
+    ///
+    /// After calling this, the next poll will resolve immediately, see
+    /// [`Subscriber::reset`].
+    fn reset(&mut self);
+
+    /// Transform the values of this subscriber with the given function.
+    ///
+    /// The function is called for every update, as well as every time the
+    /// current value is requested through [`get`][SubscriberExt::get].
+    fn map_value<U, F>(self, f: F) -> Map<Self, F>
+    where
+        F: Fn(Self::Item) -> U,
+    {
+        Map { inner: self, f }
+    }
+
+    /// Only forward updates for which the given predicate returns `true`.
+    ///
+    /// The current value of the returned subscriber is the last value that was
+    /// accepted. Before the first accepted update, it is the current value of
+    /// `self` at the time of calling this method, **even if that value doesn't
+    /// satisfy the predicate**; check it yourself if that matters.
+    fn filter_value<F>(self, f: F) -> Filter<Self, F>
+    where
+        Self::Item: Clone,
+        F: FnMut(&Self::Item) -> bool,
+    {
+        let last = self.get();
+        Filter { inner: self, f, last, pending_reset: false }
+    }
+
+    /// Skip updates that are considered equal to the previously observed value
+    /// by the given function.
+    ///
+    /// The current value of `self` at the time of calling this method counts
+    /// as observed.
+    fn dedup_by<F>(self, same: F) -> DedupBy<Self, F>
+    where
+        Self::Item: Clone,
+        F: FnMut(&Self::Item, &Self::Item) -> bool,
+    {
+        let last = Some(self.get());
+        DedupBy { inner: self, same, last }
+    }
+
+    /// Skip updates that are equal to the previously observed value.
+    ///
+    /// Shorthand for `.dedup_by(|a, b| a == b)`.
+    fn dedup(self) -> DedupBy<Self, DedupEq<Self::Item>>
+    where
+        Self::Item: Clone + PartialEq,
+    {
+        let eq: DedupEq<Self::Item> = PartialEq::eq;
+        self.dedup_by(eq)
+    }
+
+    /// Pair up the updates of this subscriber with the updates of another one.
+    ///
+    /// The returned subscriber only yields once both inputs have been updated,
+    /// and ends as soon as either of them ends. If an input is updated more
+    /// than once before the other one is, only its latest value is paired up.
+    /// Its current value is the pair of the current values of both inputs.
+    fn zip_latest<S: SubscriberExt>(self, other: S) -> Zip<Self, S> {
+        Zip { a: self, b: other, a_next: None, b_next: None }
+    }
+
+    /// Combine the values of this subscriber with the values of another one.
+    ///
+    /// The returned subscriber yields whenever either input is updated, with
+    /// the latest value of the other input. It ends once both inputs have
+    /// ended. Its current value is the pair of the current values of both
+    /// inputs.
+    fn combine_latest<S: SubscriberExt>(self, other: S) -> CombineLatest<Self, S> {
+        CombineLatest { a: self, b: other, a_done: false, b_done: false }
+    }
+}
+
+/// Type of the equality function used by [`SubscriberExt::dedup`].
+pub type DedupEq<T> = fn(&T, &T) -> bool;
+
+impl<T: Clone> SubscriberExt for Subscriber<T> {
+    fn get(&self) -> T {
+        self.read().clone()
+    }
+
+    fn reset(&mut self) {
+        self.observed_version = 0;
+    }
+}
+
+/// Subscriber returned by [`SubscriberExt::map_value`].
+#[must_use]
+pub struct Map<S, F> {
+    inner: S,
+    f: F,
+}
+
+// The adapters never project pins to their fields, so this is fine regardless
+// of the closure type
+impl<S: Unpin, F> Unpin for Map<S, F> {}
+
+impl<S, F, U> Stream for Map<S, F>
+where
+    S: SubscriberExt,
+    F: Fn(S::Item) -> U,
+{
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        Pin::new(&mut this.inner).poll_next(cx).map(|opt| opt.map(&this.f))
+    }
+}
+
+impl<S, F, U> SubscriberExt for Map<S, F>
+where
+    S: SubscriberExt,
+    F: Fn(S::Item) -> U,
+{
+    fn get(&self) -> U {
+        (self.f)(self.inner.get())
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for Map<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+/// Subscriber returned by [`SubscriberExt::filter_value`].
+///
+/// Its current value is not checked against the predicate until there is an
+/// update, see [`SubscriberExt::filter_value`].
+#[must_use]
+pub struct Filter<S: Stream, F> {
+    inner: S,
+    f: F,
+    /// The last accepted value.
+    last: S::Item,
+    /// Whether the subscriber was reset and has not yielded since.
+    pending_reset: bool,
+}
+
+impl<S: Stream + Unpin, F> Unpin for Filter<S, F> {}
+
+impl<S, F> Stream for Filter<S, F>
+where
+    S: SubscriberExt,
+    S::Item: Clone,
+    F: FnMut(&S::Item) -> bool,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let value = match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(value)) => value,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if (this.f)(&value) {
+                this.pending_reset = false;
+                this.last = value.clone();
+                return Poll::Ready(Some(value));
+            }
+
+            // The inner subscriber yields immediately after a reset, but the
+            // value it yields can be filtered out. Yield the last accepted
+            // value in that case to uphold the reset guarantee.
+            if this.pending_reset {
+                this.pending_reset = false;
+                return Poll::Ready(Some(this.last.clone()));
+            }
+        }
+    }
+}
+
+impl<S, F> SubscriberExt for Filter<S, F>
+where
+    S: SubscriberExt,
+    S::Item: Clone,
+    F: FnMut(&S::Item) -> bool,
+{
+    fn get(&self) -> S::Item {
+        self.last.clone()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.pending_reset = true;
+    }
+}
+
+impl<S, F> fmt::Debug for Filter<S, F>
+where
+    S: Stream + fmt::Debug,
+    S::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Subscriber returned by [`SubscriberExt::dedup_by`] and
+/// [`SubscriberExt::dedup`].
+#[must_use]
+pub struct DedupBy<S: Stream, F> {
+    inner: S,
+    same: F,
+    /// The last observed value, or `None` after a reset.
+    last: Option<S::Item>,
+}
+
+impl<S: Stream + Unpin, F> Unpin for DedupBy<S, F> {}
+
+impl<S, F> Stream for DedupBy<S, F>
+where
+    S: SubscriberExt,
+    S::Item: Clone,
+    F: FnMut(&S::Item, &S::Item) -> bool,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let value = match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(value)) => value,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let is_dup = match &this.last {
+                Some(last) => (this.same)(last, &value),
+                None => false,
+            };
+            if !is_dup {
+                this.last = Some(value.clone());
+                return Poll::Ready(Some(value));
+            }
+        }
+    }
+}
+
+impl<S, F> SubscriberExt for DedupBy<S, F>
+where
+    S: SubscriberExt,
+    S::Item: Clone,
+    F: FnMut(&S::Item, &S::Item) -> bool,
+{
+    fn get(&self) -> S::Item {
+        self.inner.get()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.last = None;
+    }
+}
+
+impl<S, F> fmt::Debug for DedupBy<S, F>
+where
+    S: Stream + fmt::Debug,
+    S::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupBy")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Subscriber returned by [`SubscriberExt::zip_latest`].
+#[must_use]
+pub struct Zip<A: Stream, B: Stream> {
+    a: A,
+    b: B,
+    /// Latest update of `a` that is waiting for an update of `b`.
+    a_next: Option<A::Item>,
+    /// Latest update of `b` that is waiting for an update of `a`.
+    b_next: Option<B::Item>,
+}
+
+impl<A: Stream + Unpin, B: Stream + Unpin> Unpin for Zip<A, B> {}
+
+impl<A: SubscriberExt, B: SubscriberExt> Stream for Zip<A, B> {
+    type Item = (A::Item, B::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if poll_latest(&mut this.a, &mut this.a_next, cx)
+            || poll_latest(&mut this.b, &mut this.b_next, cx)
+        {
+            return Poll::Ready(None);
+        }
+
+        match (this.a_next.take(), this.b_next.take()) {
+            (Some(a), Some(b)) => Poll::Ready(Some((a, b))),
+            (a_next, b_next) => {
+                this.a_next = a_next;
+                this.b_next = b_next;
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<A: SubscriberExt, B: SubscriberExt> SubscriberExt for Zip<A, B> {
+    fn get(&self) -> Self::Item {
+        (self.a.get(), self.b.get())
+    }
+
+    fn reset(&mut self) {
+        self.a.reset();
+        self.b.reset();
+        self.a_next = None;
+        self.b_next = None;
+    }
+}
+
+impl<A, B> fmt::Debug for Zip<A, B>
+where
+    A: Stream + fmt::Debug,
+    B: Stream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zip").field("a", &self.a).field("b", &self.b).finish_non_exhaustive()
+    }
+}
+
+/// Poll `stream` until it is pending, replacing `next` with every update.
+///
+/// Returns `true` if the stream has ended.
+fn poll_latest<S: Stream + Unpin>(
+    stream: &mut S,
+    next: &mut Option<S::Item>,
+    cx: &mut Context<'_>,
+) -> bool {
+    loop {
+        match Pin::new(&mut *stream).poll_next(cx) {
+            Poll::Ready(Some(value)) => *next = Some(value),
+            Poll::Ready(None) => return true,
+            Poll::Pending => return false,
+        }
+    }
+}
+
+/// Subscriber returned by [`SubscriberExt::combine_latest`].
+#[must_use]
+#[derive(Debug)]
+pub struct CombineLatest<A, B> {
+    a: A,
+    b: B,
+    a_done: bool,
+    b_done: bool,
+}
+
+impl<A: SubscriberExt, B: SubscriberExt> Stream for CombineLatest<A, B> {
+    type Item = (A::Item, B::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        let a = poll_unless_done(&mut this.a, &mut this.a_done, cx);
+        let b = poll_unless_done(&mut this.b, &mut this.b_done, cx);
+
+        match (a, b) {
+            (None, None) if this.a_done && this.b_done => Poll::Ready(None),
+            (None, None) => Poll::Pending,
+            (a, b) => Poll::Ready(Some((
+                a.unwrap_or_else(|| this.a.get()),
+                b.unwrap_or_else(|| this.b.get()),
+            ))),
+        }
+    }
+}
+
+impl<A: SubscriberExt, B: SubscriberExt> SubscriberExt for CombineLatest<A, B> {
+    fn get(&self) -> Self::Item {
+        (self.a.get(), self.b.get())
+    }
+
+    fn reset(&mut self) {
+        self.a.reset();
+        self.b.reset();
+    }
+}
+
+/// Poll the given subscriber if it has not ended yet, returning its update if
+/// there is one.
+fn poll_unless_done<S: SubscriberExt>(
+    subscriber: &mut S,
+    done: &mut bool,
+    cx: &mut Context<'_>,
+) -> Option<S::Item> {
+    if *done {
+        return None;
+    }
+
+    match Pin::new(subscriber).poll_next(cx) {
+        Poll::Ready(Some(value)) => Some(value),
+        Poll::Ready(None) => {
+            *done = true;
+            None
+        }
+        Poll::Pending => None,
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    sync::{mpsc, Arc, Barrier},
+    thread,
+    time::Duration,
+};
+
+use eyeball::{Observable, SharedObservable};
+
+#[test]
+fn next_blocking() {
+    let ob = SharedObservable::new(0);
+    let mut subscriber = ob.subscribe();
+    let barrier = Arc::new(Barrier::new(2));
+
+    let handle = thread::spawn({
+        let barrier = barrier.clone();
+        move || {
+            // Only update once the main thread is about to block.
+            barrier.wait();
+            ob.set(1);
+            ob
+        }
+    });
+
+    barrier.wait();
+    assert_eq!(subscriber.next_blocking(), Some(1));
+    drop(handle.join().unwrap());
+    assert_eq!(subscriber.next_blocking(), None);
+}
+
+#[test]
+fn next_blocking_ready() {
+    let mut ob = Observable::new("A");
+    let mut subscriber = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, "B");
+    assert_eq!(subscriber.next_blocking(), Some("B"));
+
+    drop(ob);
+    assert_eq!(subscriber.next_blocking(), None);
+}
+
+#[test]
+fn next_blocking_timeout() {
+    let ob = SharedObservable::new(0);
+    let mut subscriber = ob.subscribe();
+
+    let result = subscriber.next_blocking_timeout(Duration::from_millis(10));
+    assert!(result.is_err());
+
+    let handle = thread::spawn({
+        let ob = ob.clone();
+        move || ob.set(1)
+    });
+    assert_eq!(subscriber.next_blocking_timeout(Duration::from_secs(10)), Ok(Some(1)));
+    handle.join().unwrap();
+
+    drop(ob);
+    assert_eq!(subscriber.next_blocking_timeout(Duration::from_millis(10)), Ok(None));
+}
+
+#[test]
+fn blocking_iter() {
+    let ob = SharedObservable::new(0);
+    let mut subscriber = ob.subscribe();
+    let (ack_tx, ack_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        for i in 1..=3 {
+            ob.set(i);
+            // Wait for the value to be received, so none of them are skipped.
+            ack_rx.recv().unwrap();
+        }
+    });
+
+    let mut values = Vec::new();
+    for value in subscriber.blocking_iter() {
+        values.push(value);
+        ack_tx.send(()).unwrap();
+    }
+    handle.join().unwrap();
+
+    assert_eq!(values, [1, 2, 3]);
+}
@@ -0,0 +1,143 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use eyeball::{Computed, ComputedSource, Observable, SharedObservable, SubscriberExt};
+use macro_rules_attribute::apply;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+fn sum(a: &SharedObservable<i32>, b: &SharedObservable<i32>) -> Computed<i32> {
+    let (a2, b2) = (a.clone(), b.clone());
+    Computed::new([a.into(), b.into()], move || a2.get() + b2.get())
+}
+
+#[test]
+fn get() {
+    let a = SharedObservable::new(1);
+    let b = SharedObservable::new(2);
+    let computed = sum(&a, &b);
+    assert_eq!(computed.get(), 3);
+
+    a.set(10);
+    assert_eq!(computed.get(), 12);
+    b.set(20);
+    assert_eq!(computed.get(), 30);
+}
+
+#[test]
+fn lazy() {
+    let a = SharedObservable::new(1);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let computed = Computed::new([ComputedSource::from(&a)], {
+        let a = a.clone();
+        let calls = calls.clone();
+        move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            a.get() * 2
+        }
+    });
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    a.set(2);
+    a.set(3);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    assert_eq!(computed.get(), 6);
+    assert_eq!(computed.get(), 6);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn subscribe() {
+    let a = SharedObservable::new(1);
+    let b = SharedObservable::new(2);
+    let computed = sum(&a, &b);
+    let mut sub = computed.subscribe();
+    assert_pending!(sub);
+
+    a.set(2);
+    assert_next_eq!(sub, 4);
+    assert_pending!(sub);
+
+    a.set(3);
+    b.set(3);
+    assert_next_eq!(sub, 6);
+    assert_pending!(sub);
+
+    sub.reset();
+    assert_next_eq!(sub, 6);
+
+    let mut sub2 = computed.subscribe_reset();
+    assert_next_eq!(sub2, 6);
+    assert_pending!(sub2);
+}
+
+#[apply(test!)]
+async fn next() {
+    let a = SharedObservable::new(1);
+    let b = SharedObservable::new(2);
+    let computed = sum(&a, &b);
+    let mut sub = computed.subscribe();
+
+    b.set(5);
+    assert_eq!(sub.next().await, Some(6));
+    assert_eq!(sub.next_now(), 6);
+
+    drop(computed);
+    assert_eq!(sub.next().await, None);
+    // The last value is still available after the `Computed` is dropped
+    assert_eq!(sub.get(), 6);
+}
+
+#[test]
+fn subscriber_source() {
+    let mut ob = Observable::new("a".to_owned());
+    let source = Observable::subscribe(&ob);
+    let computed = Computed::new([ComputedSource::from(&source)], move || source.get().len());
+    let mut sub = computed.subscribe();
+
+    Observable::set(&mut ob, "abc".to_owned());
+    assert_next_eq!(sub, 3);
+
+    // The last update before the source is closed is not lost
+    Observable::set(&mut ob, "ab".to_owned());
+    drop(ob);
+    assert_next_eq!(sub, 2);
+    assert_pending!(sub);
+
+    drop(computed);
+    assert_closed!(sub);
+}
+
+#[test]
+fn clones() {
+    let a = SharedObservable::new(1);
+    let b = SharedObservable::new(2);
+    let computed = sum(&a, &b);
+    let computed2 = computed.clone();
+    let mut sub = computed.subscribe();
+    assert_eq!(computed.observable_count(), 2);
+    assert_eq!(computed.subscriber_count(), 1);
+
+    drop(computed);
+    a.set(0);
+    assert_next_eq!(sub, 2);
+    assert_eq!(computed2.get(), 2);
+
+    drop(computed2);
+    assert_closed!(sub);
+}
+
+#[test]
+fn ext() {
+    let a = SharedObservable::new(1);
+    let b = SharedObservable::new(2);
+    let computed = sum(&a, &b);
+    let mut sub = computed.subscribe().map_value(|n| n % 2 == 0).dedup();
+
+    a.set(3);
+    assert_pending!(sub);
+    a.set(4);
+    assert_next_eq!(sub, true);
+}
@@ -0,0 +1,171 @@
+use eyeball::{SharedObservable, SubscriberExt};
+use futures_util::StreamExt;
+use stream_assert::{assert_closed, assert_next_eq, assert_pending};
+
+#[test]
+fn map_value() {
+    let ob = SharedObservable::new(1);
+    let mut sub = ob.subscribe().map_value(|n| n * 10);
+    assert_eq!(sub.get(), 10);
+    assert_pending!(sub);
+
+    ob.set(2);
+    assert_eq!(sub.get(), 20);
+    assert_next_eq!(sub, 20);
+    assert_pending!(sub);
+
+    sub.reset();
+    assert_next_eq!(sub, 20);
+
+    drop(ob);
+    assert_closed!(sub);
+}
+
+#[test]
+fn filter_value() {
+    let ob = SharedObservable::new(1);
+    let mut sub = ob.subscribe().filter_value(|n| n % 2 == 0);
+    assert_eq!(sub.get(), 1);
+
+    ob.set(3);
+    assert_pending!(sub);
+    assert_eq!(sub.get(), 1);
+
+    ob.set(4);
+    assert_next_eq!(sub, 4);
+    assert_eq!(sub.get(), 4);
+
+    ob.set(5);
+    assert_pending!(sub);
+    assert_eq!(sub.get(), 4);
+
+    // The current value is filtered out, so a reset yields the last accepted
+    // value
+    sub.reset();
+    assert_next_eq!(sub, 4);
+    assert_pending!(sub);
+}
+
+#[test]
+fn dedup() {
+    let ob = SharedObservable::new("a");
+    let mut sub = ob.subscribe().dedup();
+
+    ob.set("a");
+    assert_pending!(sub);
+
+    ob.set("b");
+    assert_next_eq!(sub, "b");
+
+    ob.set("b");
+    assert_pending!(sub);
+
+    sub.reset();
+    assert_next_eq!(sub, "b");
+    assert_pending!(sub);
+}
+
+#[test]
+fn dedup_by() {
+    let ob = SharedObservable::new(10);
+    let mut sub = ob.subscribe().dedup_by(|a, b| a / 10 == b / 10);
+
+    ob.set(15);
+    assert_pending!(sub);
+    assert_eq!(sub.get(), 15);
+
+    ob.set(21);
+    assert_next_eq!(sub, 21);
+}
+
+#[test]
+fn zip_latest() {
+    let a = SharedObservable::new(0);
+    let b = SharedObservable::new('a');
+    let mut sub = a.subscribe().zip_latest(b.subscribe());
+    assert_eq!(sub.get(), (0, 'a'));
+
+    // `a` is updated again while its previous update is waiting
+    a.set(1);
+    assert_pending!(sub);
+    a.set(2);
+    assert_pending!(sub);
+    b.set('b');
+    assert_next_eq!(sub, (2, 'b'));
+
+    b.set('c');
+    assert_pending!(sub);
+    a.set(3);
+    a.set(4);
+    assert_next_eq!(sub, (4, 'c'));
+
+    b.set('d');
+    assert_pending!(sub);
+    a.set(5);
+    assert_next_eq!(sub, (5, 'd'));
+
+    drop(b);
+    a.set(6);
+    assert_closed!(sub);
+}
+
+#[test]
+fn combine_latest() {
+    let a = SharedObservable::new(0);
+    let b = SharedObservable::new('a');
+    let mut sub = a.subscribe().combine_latest(b.subscribe());
+    assert_pending!(sub);
+
+    a.set(1);
+    assert_next_eq!(sub, (1, 'a'));
+
+    b.set('b');
+    assert_next_eq!(sub, (1, 'b'));
+
+    a.set(2);
+    b.set('c');
+    assert_next_eq!(sub, (2, 'c'));
+    assert_pending!(sub);
+
+    // Ended inputs keep their last value
+    drop(a);
+    b.set('d');
+    assert_next_eq!(sub, (2, 'd'));
+    assert_eq!(sub.get(), (2, 'd'));
+
+    drop(b);
+    assert_closed!(sub);
+}
+
+#[test]
+fn chained() {
+    let ob = SharedObservable::new(1);
+    let mut sub = ob.subscribe().map_value(|n| n % 3).dedup().filter_value(|n| *n != 0);
+
+    ob.set(4);
+    assert_pending!(sub);
+
+    ob.set(5);
+    assert_next_eq!(sub, 2);
+
+    ob.set(6);
+    assert_pending!(sub);
+    assert_eq!(sub.get(), 2);
+}
+
+#[test]
+fn alongside_stream_ext() {
+    let ob = SharedObservable::new(1);
+    let mut sub = ob
+        .subscribe()
+        .map_value(|n| n * 10)
+        .filter_value(|n| n % 20 == 0)
+        .zip_latest(ob.subscribe())
+        // `StreamExt::map`, which doesn't support `get` anymore
+        .map(|(a, b)| a + b);
+
+    ob.set(2);
+    assert_next_eq!(sub, 22);
+    ob.set(3);
+    assert_pending!(sub);
+}
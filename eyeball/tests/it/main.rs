@@ -17,5 +17,10 @@ macro_rules! test {
 
 #[cfg(feature = "async-lock")]
 mod async_lock;
+mod blocking;
+mod computed;
+mod ext;
+#[cfg(feature = "serde")]
+mod serde;
 mod shared;
 mod unique;
@@ -0,0 +1,49 @@
+use eyeball::{Observable, SharedObservable};
+
+#[test]
+fn observable_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let ob = Observable::new(vec![1, 2, 3]);
+    let json = serde_json::to_string(&ob)?;
+    assert_eq!(json, "[1,2,3]");
+
+    let ob: Observable<Vec<u8>> = serde_json::from_str(&json)?;
+    assert_eq!(*ob, [1, 2, 3]);
+
+    Ok(())
+}
+
+#[test]
+fn shared_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let ob = SharedObservable::new("hello".to_owned());
+    ob.set("world".to_owned());
+    let json = serde_json::to_string(&ob)?;
+    assert_eq!(json, r#""world""#);
+
+    let ob: SharedObservable<String> = serde_json::from_str(&json)?;
+    assert_eq!(ob.get(), "world");
+    assert_eq!(ob.observable_count(), 1);
+    assert_eq!(ob.subscriber_count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn nested_fields() -> Result<(), Box<dyn std::error::Error>> {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Settings {
+        theme: SharedObservable<String>,
+        font_size: Observable<u32>,
+    }
+
+    let json = r#"{"theme":"dark","font_size":12}"#;
+    let settings: Settings = serde_json::from_str(json)?;
+
+    let mut subscriber = settings.theme.subscribe();
+    settings.theme.set("light".to_owned());
+    assert_eq!(subscriber.next_now(), "light");
+
+    let json = serde_json::to_value(&settings)?;
+    assert_eq!(json, serde_json::json!({ "theme": "light", "font_size": 12 }));
+
+    Ok(())
+}